@@ -0,0 +1,100 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The AArch64 calling conventions
+//!
+//! `SyscallRegs` holds the registers of a Linux system call and `CallRegs`
+//! the integer registers of a function call (AAPCS64). Use `Arguments` to
+//! pack typed arguments into them.
+
+use crate::{Errno, Register};
+
+/// The registers of a system call
+///
+/// The number is passed in `x8` and the arguments in `x0` to `x5`. The
+/// result is returned in `x0`, with `x1` as the second return value of a
+/// few system calls.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct SyscallRegs {
+    /// The system call number (`x8`)
+    pub number: Register<usize>,
+
+    /// The arguments (`x0` to `x5`)
+    pub args: [Register<usize>; 6],
+
+    /// The return values (`x0`, `x1`)
+    pub ret: [Register<usize>; 2],
+}
+
+impl SyscallRegs {
+    /// Creates the registers of a system call
+    #[inline]
+    pub fn new(number: impl Into<Register<usize>>, args: impl Into<[Register<usize>; 6]>) -> Self {
+        Self {
+            number: number.into(),
+            args: args.into(),
+            ret: Default::default(),
+        }
+    }
+
+    /// Decodes the first return value
+    #[inline]
+    pub fn result(&self) -> Result<Register<usize>, Errno> {
+        Errno::decode(self.ret[0])
+    }
+
+    /// Encodes a result into the first return value
+    #[inline]
+    pub fn set_result(&mut self, result: Result<Register<usize>, Errno>) {
+        self.ret[0] = Errno::encode(result);
+    }
+}
+
+/// The integer registers of a function call
+///
+/// The arguments are passed in `x0` to `x7` and the result is returned in
+/// `x0` and `x1`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct CallRegs {
+    /// The arguments (`x0` to `x7`)
+    pub args: [Register<usize>; 8],
+
+    /// The return values (`x0`, `x1`)
+    pub ret: [Register<usize>; 2],
+}
+
+impl CallRegs {
+    /// Creates the registers of a function call
+    #[inline]
+    pub fn new(args: impl Into<[Register<usize>; 8]>) -> Self {
+        Self {
+            args: args.into(),
+            ret: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Arguments;
+
+    #[test]
+    fn syscall() {
+        let mut regs = SyscallRegs::new(63usize, Arguments::new().arg(-100isize));
+        assert_eq!(isize::from(regs.args[0]), -100);
+
+        regs.ret[0] = Register::from(-2isize);
+        assert_eq!(regs.result(), Err(Errno(2)));
+    }
+
+    #[test]
+    fn call() {
+        let value = 0u64;
+        let regs = CallRegs::new(Arguments::new().arg(&value as *const u64).arg(-1i8));
+        assert_eq!(usize::from(regs.args[0]), &value as *const u64 as usize);
+        assert_eq!(usize::from(regs.args[1]), usize::MAX);
+        assert_eq!(usize::from(regs.args[7]), 0);
+    }
+}
@@ -0,0 +1,180 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The general-purpose register file
+//!
+//! `RegisterFile` holds the integer state of a thread: X0 to X30, SP, PC
+//! and PSTATE. It is the state saved on exceptions, VM exits and signals.
+
+use crate::Register;
+
+use core::fmt::{Debug, Formatter, Result};
+use core::mem::size_of;
+use core::ops::{Index, IndexMut};
+
+/// The general-purpose registers, SP, PC and PSTATE
+///
+/// Indexing the register file with a register number selects that register
+/// (`regs[30]` is the link register X30). Number 31 is not a register: it
+/// encodes either SP or XZR, depending on the instruction.
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct RegisterFile {
+    /// X0 to X30
+    pub x: [Register<u64>; 31],
+
+    /// The stack pointer
+    pub sp: Register<u64>,
+
+    /// The program counter
+    pub pc: Register<u64>,
+
+    /// The process state (as in SPSR)
+    pub pstate: Register<u64>,
+}
+
+const _: () = assert!(size_of::<RegisterFile>() == size_of::<[Register<u64>; RegisterFile::LEN]>());
+
+impl RegisterFile {
+    /// The number of registers
+    pub const LEN: usize = 34;
+
+    /// The number of general-purpose registers
+    pub const GPRS: usize = 31;
+
+    /// The names of the registers in layout order
+    pub const NAMES: [&'static str; Self::LEN] = [
+        "x0", "x1", "x2", "x3", "x4", "x5", "x6", "x7", "x8", "x9", "x10", "x11", "x12", "x13",
+        "x14", "x15", "x16", "x17", "x18", "x19", "x20", "x21", "x22", "x23", "x24", "x25", "x26",
+        "x27", "x28", "x29", "x30", "sp", "pc", "pstate",
+    ];
+
+    /// Returns the registers in layout order
+    #[inline]
+    pub fn as_array(&self) -> &[Register<u64>; Self::LEN] {
+        // SAFETY: the structure consists of `LEN` registers and nothing else.
+        unsafe { &*(self as *const Self).cast() }
+    }
+
+    /// Returns the registers in layout order, mutably
+    #[inline]
+    pub fn as_array_mut(&mut self) -> &mut [Register<u64>; Self::LEN] {
+        // SAFETY: the structure consists of `LEN` registers and nothing else.
+        unsafe { &mut *(self as *mut Self).cast() }
+    }
+
+    /// Returns the general-purpose register with the given number
+    #[inline]
+    pub fn gpr(&self, number: usize) -> Option<Register<u64>> {
+        self.x.get(number).copied()
+    }
+
+    /// Returns the general-purpose register with the given number, mutably
+    #[inline]
+    pub fn gpr_mut(&mut self, number: usize) -> Option<&mut Register<u64>> {
+        self.x.get_mut(number)
+    }
+
+    /// Iterates over the registers in layout order
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, Register<u64>> {
+        self.as_array().iter()
+    }
+
+    /// Iterates mutably over the registers in layout order
+    #[inline]
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, Register<u64>> {
+        self.as_array_mut().iter_mut()
+    }
+}
+
+impl Index<usize> for RegisterFile {
+    type Output = Register<u64>;
+
+    #[inline]
+    fn index(&self, number: usize) -> &Self::Output {
+        &self.x[number]
+    }
+}
+
+impl IndexMut<usize> for RegisterFile {
+    #[inline]
+    fn index_mut(&mut self, number: usize) -> &mut Self::Output {
+        &mut self.x[number]
+    }
+}
+
+impl<'a> IntoIterator for &'a RegisterFile {
+    type Item = &'a Register<u64>;
+    type IntoIter = core::slice::Iter<'a, Register<u64>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut RegisterFile {
+    type Item = &'a mut Register<u64>;
+    type IntoIter = core::slice::IterMut<'a, Register<u64>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl Debug for RegisterFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let mut debug = f.debug_struct("RegisterFile");
+        for (name, value) in Self::NAMES.iter().zip(self) {
+            debug.field(name, &format_args!("{:#x}", u64::from(*value)));
+        }
+
+        debug.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn index() {
+        let mut regs = RegisterFile::default();
+        regs[8] = Register::from(93u64);
+        *regs.gpr_mut(30).unwrap() = Register::from(0x40_0000u64);
+
+        assert_eq!(regs.x[8], Register::from(93u64));
+        assert_eq!(regs.gpr(30), Some(Register::from(0x40_0000u64)));
+        assert_eq!(regs.gpr(31), None);
+        assert!(regs.gpr_mut(31).is_none());
+    }
+
+    #[test]
+    fn iter() {
+        let mut regs = RegisterFile::default();
+        for (i, reg) in (&mut regs).into_iter().enumerate() {
+            *reg = Register::from(i as u64);
+        }
+
+        assert_eq!(regs.x[30], Register::from(30u64));
+        assert_eq!(regs.sp, Register::from(31u64));
+        assert_eq!(regs.pstate, Register::from(33u64));
+        assert_eq!(regs.iter().count(), RegisterFile::LEN);
+    }
+
+    #[test]
+    fn debug() {
+        let regs = RegisterFile {
+            pc: Register::from(0xffff_0000_0000_1000u64),
+            ..Default::default()
+        };
+
+        let debug = format!("{:?}", regs);
+        assert!(debug.starts_with("RegisterFile { x0: 0x0, x1: 0x0,"));
+        assert!(debug.ends_with("pc: 0xffff000000001000, pstate: 0x0 }"));
+    }
+}
@@ -0,0 +1,9 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Primitives specific to the AArch64 architecture
+//!
+//! These are plain data definitions and do not require running on AArch64.
+
+pub mod abi;
+pub mod context;
+pub mod paging;
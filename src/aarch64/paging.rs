@@ -0,0 +1,700 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! AArch64 stage-1 translation tables
+//!
+//! This module describes the VMSAv8-64 descriptor format for the 4 KiB,
+//! 16 KiB and 64 KiB translation granules and provides a software walker.
+//! The granule is selected by the page type (`Page4K`, `Page16K` or
+//! `Page64K`) used for the tables, which are reached through the `Frames`
+//! trait. Output addresses are limited to 48 bits.
+
+use crate::{Address, Frame, Frames, Page4K, PageSize, SpaceAddress, Translate, Virtual};
+
+use core::marker::PhantomData;
+
+/// The size of the virtual address space of a translation table
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum VaBits {
+    /// 39-bit virtual addresses (`TnSZ = 25`)
+    Bits39,
+
+    /// 48-bit virtual addresses (`TnSZ = 16`)
+    Bits48,
+}
+
+impl VaBits {
+    /// Returns the number of significant bits in a virtual address
+    #[inline]
+    pub const fn bits(self) -> u32 {
+        match self {
+            Self::Bits39 => 39,
+            Self::Bits48 => 48,
+        }
+    }
+}
+
+flags! {
+    /// The attributes of a descriptor
+    ///
+    /// This excludes the memory attribute index and the shareability, which
+    /// are multi-bit fields (see `Descriptor`).
+    pub struct Attributes {
+        /// The descriptor is valid
+        VALID = 0;
+
+        /// The descriptor references a table, or a page at level 3
+        TABLE = 1;
+
+        /// The mapped memory is in the non-secure address space
+        NON_SECURE = 5;
+
+        /// The mapped memory is accessible from EL0 (`AP[1]`)
+        USER = 6;
+
+        /// The mapped memory is read-only (`AP[2]`)
+        READ_ONLY = 7;
+
+        /// The descriptor has been used for a translation (`AF`)
+        ACCESSED = 10;
+
+        /// The translation is specific to an address space identifier (`nG`)
+        NOT_GLOBAL = 11;
+
+        /// The dirty state is managed by hardware (`DBM`)
+        DIRTY_BIT_MODIFIER = 51;
+
+        /// The descriptor is one of a contiguous set of descriptors
+        CONTIGUOUS = 52;
+
+        /// The mapped memory is not executable at EL1 (`PXN`)
+        PRIVILEGED_NO_EXECUTE = 53;
+
+        /// The mapped memory is not executable at EL0 (`UXN`)
+        USER_NO_EXECUTE = 54;
+
+        /// Memory below this table is not executable at EL1 (`PXNTable`)
+        TABLE_PRIVILEGED_NO_EXECUTE = 59;
+
+        /// Memory below this table is not executable at EL0 (`UXNTable`)
+        TABLE_USER_NO_EXECUTE = 60;
+
+        /// Memory below this table is not accessible from EL0 (`APTable[0]`)
+        TABLE_NO_USER = 61;
+
+        /// Memory below this table is read-only (`APTable[1]`)
+        TABLE_READ_ONLY = 62;
+
+        /// Memory below this table is in the non-secure address space (`NSTable`)
+        TABLE_NON_SECURE = 63;
+    }
+}
+
+impl Attributes {
+    const MASK: u64 =
+        !(Descriptor::ADDRESS_MASK | Descriptor::ATTR_INDEX_MASK | Descriptor::SHAREABILITY_MASK);
+
+    /// Creates attributes from raw bits, discarding the bits of other fields
+    #[inline]
+    pub const fn from_bits_truncate(bits: u64) -> Self {
+        Self(bits & Self::MASK)
+    }
+}
+
+/// The shareability of the mapped memory
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Shareability {
+    /// Not shareable
+    Non = 0,
+
+    /// Outer shareable
+    Outer = 2,
+
+    /// Inner shareable
+    Inner = 3,
+}
+
+/// A translation table descriptor
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct Descriptor(u64);
+
+impl core::fmt::Debug for Descriptor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Descriptor")
+            .field(&Address::<u64, ()>::from(self.0 & Self::ADDRESS_MASK))
+            .field(&self.attributes())
+            .field(&self.attr_index())
+            .finish()
+    }
+}
+
+impl Descriptor {
+    /// The mask selecting the output address bits of a descriptor
+    ///
+    /// With the 16 KiB and 64 KiB granules, the lowest bits of this field
+    /// are ignored (see `Descriptor::address`).
+    pub const ADDRESS_MASK: u64 = 0x0000_ffff_ffff_f000;
+
+    /// The mask selecting the memory attribute index (`AttrIndx`)
+    pub const ATTR_INDEX_MASK: u64 = 0b111 << 2;
+
+    /// The mask selecting the shareability (`SH`)
+    pub const SHAREABILITY_MASK: u64 = 0b11 << 8;
+
+    /// An invalid (all zero) descriptor
+    pub const INVALID: Self = Self(0);
+
+    /// Creates a table descriptor referencing the table at `addr`
+    #[inline]
+    pub const fn table<P>(addr: Address<u64, P>, attrs: Attributes) -> Self {
+        Self((addr.0 & Self::ADDRESS_MASK) | attrs.0 | Attributes::VALID.0 | Attributes::TABLE.0)
+    }
+
+    /// Creates a block descriptor mapping the block at `addr`
+    ///
+    /// Blocks may only be used at certain levels (see `Level::can_map`) and
+    /// `addr` must be aligned to the block size.
+    #[inline]
+    pub const fn block<P>(addr: Address<u64, P>, attrs: Attributes) -> Self {
+        Self(((addr.0 & Self::ADDRESS_MASK) | attrs.0 | Attributes::VALID.0) & !Attributes::TABLE.0)
+    }
+
+    /// Creates a level 3 descriptor mapping the page at `addr`
+    #[inline]
+    pub const fn page<P>(addr: Address<u64, P>, attrs: Attributes) -> Self {
+        Self::table(addr, attrs)
+    }
+
+    /// Creates a descriptor from its raw value
+    #[inline]
+    pub const fn from_raw(value: u64) -> Self {
+        Self(value)
+    }
+
+    /// Returns the raw value of the descriptor
+    #[inline]
+    pub const fn raw(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the output address of the descriptor for the granule `P`
+    #[inline]
+    pub fn address<P: PageSize>(self) -> Address<u64, P> {
+        Address(self.0 & Self::ADDRESS_MASK & !(P::MASK as u64), PhantomData)
+    }
+
+    /// Returns the attributes of the descriptor
+    #[inline]
+    pub const fn attributes(self) -> Attributes {
+        Attributes::from_bits_truncate(self.0)
+    }
+
+    /// Returns the memory attribute index into `MAIR_ELx`
+    #[inline]
+    pub const fn attr_index(self) -> u8 {
+        ((self.0 & Self::ATTR_INDEX_MASK) >> 2) as u8
+    }
+
+    /// Returns the descriptor with the memory attribute index replaced
+    ///
+    /// Only the lowest three bits of `index` are used.
+    #[inline]
+    pub const fn with_attr_index(self, index: u8) -> Self {
+        Self((self.0 & !Self::ATTR_INDEX_MASK) | ((index as u64) << 2 & Self::ATTR_INDEX_MASK))
+    }
+
+    /// Returns the shareability, or `None` if the reserved encoding is used
+    #[inline]
+    pub const fn shareability(self) -> Option<Shareability> {
+        match (self.0 & Self::SHAREABILITY_MASK) >> 8 {
+            0 => Some(Shareability::Non),
+            2 => Some(Shareability::Outer),
+            3 => Some(Shareability::Inner),
+            _ => None,
+        }
+    }
+
+    /// Returns the descriptor with the shareability replaced
+    #[inline]
+    pub const fn with_shareability(self, shareability: Shareability) -> Self {
+        Self((self.0 & !Self::SHAREABILITY_MASK) | (shareability as u64) << 8)
+    }
+
+    /// Returns `true` if the descriptor is valid
+    #[inline]
+    pub const fn is_valid(self) -> bool {
+        self.attributes().contains(Attributes::VALID)
+    }
+
+    /// Returns `true` if the descriptor references a table, or a page at level 3
+    #[inline]
+    pub const fn is_table(self) -> bool {
+        self.attributes().contains(Attributes::TABLE)
+    }
+
+    /// Interprets a frame of the granule `P` as a translation table
+    #[inline]
+    pub fn slice<P: Frame>(frame: &P) -> &[Self] {
+        let bytes = frame.as_ref();
+
+        // SAFETY: page types are aligned to at least 4 KiB, their size is
+        // a multiple of the size of a descriptor and every bit pattern is a
+        // valid descriptor.
+        unsafe { core::slice::from_raw_parts(bytes.as_ptr() as *const Self, bytes.len() / 8) }
+    }
+
+    /// Interprets a frame of the granule `P` as a mutable translation table
+    #[inline]
+    pub fn slice_mut<P: Frame>(frame: &mut P) -> &mut [Self] {
+        let bytes = frame.as_mut();
+
+        // SAFETY: as above, and every bit pattern is also valid for bytes.
+        unsafe { core::slice::from_raw_parts_mut(bytes.as_mut_ptr() as *mut Self, bytes.len() / 8) }
+    }
+}
+
+/// A level of lookup
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// Level 0, only used for large virtual address spaces
+    L0 = 0,
+
+    /// Level 1
+    L1 = 1,
+
+    /// Level 2
+    L2 = 2,
+
+    /// Level 3, whose descriptors map pages of the granule size
+    L3 = 3,
+}
+
+impl Level {
+    /// Returns the initial level of lookup for the granule `P`
+    #[inline]
+    pub fn root<P: PageSize>(va: VaBits) -> Self {
+        let stride = P::SHIFT - 3;
+        let levels = (va.bits() - P::SHIFT + stride - 1) / stride;
+
+        match levels {
+            4 => Self::L0,
+            3 => Self::L1,
+            _ => Self::L2,
+        }
+    }
+
+    /// Returns the next level of lookup
+    #[inline]
+    pub const fn next(self) -> Option<Self> {
+        match self {
+            Self::L0 => Some(Self::L1),
+            Self::L1 => Some(Self::L2),
+            Self::L2 => Some(Self::L3),
+            Self::L3 => None,
+        }
+    }
+
+    /// Returns the position of the lowest virtual address bit indexing this level
+    #[inline]
+    pub fn shift<P: PageSize>(self) -> u32 {
+        P::SHIFT + (P::SHIFT - 3) * (3 - self as u32)
+    }
+
+    /// Returns the size of the memory region covered by one descriptor
+    #[inline]
+    pub fn size<P: PageSize>(self) -> u64 {
+        1 << self.shift::<P>()
+    }
+
+    /// Returns `true` if descriptors at this level can map memory
+    ///
+    /// Level 3 maps pages. Blocks can be mapped at level 2 and, with the
+    /// 4 KiB granule, at level 1.
+    #[inline]
+    pub fn can_map<P: PageSize>(self) -> bool {
+        match self {
+            Self::L3 | Self::L2 => true,
+            Self::L1 => P::SHIFT == 12,
+            Self::L0 => false,
+        }
+    }
+
+    /// Returns the index into a table at this level for a virtual address
+    ///
+    /// Note that the bits above the virtual address size must be cleared
+    /// for the initial level of lookup.
+    #[inline]
+    pub fn index<P: PageSize, U>(self, addr: Address<u64, U>) -> usize {
+        ((addr.0 >> self.shift::<P>()) as usize) % (P::SIZE / 8)
+    }
+}
+
+/// The result of translating a virtual address
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Translation {
+    /// The output address
+    pub address: Address<u64, ()>,
+
+    /// The level of the descriptor which mapped the address
+    ///
+    /// This determines the size of the mapped memory (see `Level::size`).
+    pub level: Level,
+
+    /// The descriptor which mapped the address
+    pub descriptor: Descriptor,
+
+    /// The effective attributes of the mapping
+    ///
+    /// These are the attributes of the mapping descriptor with the
+    /// hierarchical restrictions of all table descriptors applied.
+    pub attributes: Attributes,
+}
+
+/// An error encountered while walking the translation tables
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WalkError {
+    /// The virtual address is outside of both halves of the address space
+    OutOfRange(Address<u64, ()>),
+
+    /// The descriptor at the specified level is invalid
+    Invalid(Level),
+
+    /// The descriptor at the specified level uses a reserved encoding
+    Reserved(Level),
+
+    /// A table is located at a physical address which is not accessible
+    MissingTable(Address<u64, ()>),
+}
+
+/// A software translation table walker
+///
+/// The walker translates addresses through one translation table, which
+/// may be the table referenced by either `TTBR0_EL1` or `TTBR1_EL1`. It
+/// accepts addresses from both halves of the address space, so picking the
+/// appropriate table is up to the caller.
+pub struct Walker<'a, M: ?Sized, P = Page4K> {
+    va: VaBits,
+    root: Address<u64, P>,
+    memory: &'a M,
+}
+
+impl<'a, M: Frames<P> + ?Sized, P: Frame> Walker<'a, M, P> {
+    /// Creates a walker for the table at `root`
+    #[inline]
+    pub fn new(va: VaBits, root: Address<u64, P>, memory: &'a M) -> Self {
+        Self { va, root, memory }
+    }
+
+    /// Returns the descriptor mapping `addr` along with its level
+    pub fn entry<U>(&self, addr: Address<u64, U>) -> Result<(Descriptor, Level), WalkError> {
+        self.descend(Address(addr.0, PhantomData), |_| ())
+    }
+
+    /// Translates a virtual address
+    pub fn walk<U>(&self, addr: Address<u64, U>) -> Result<Translation, WalkError> {
+        let addr: Address<u64, ()> = Address(addr.0, PhantomData);
+        let mut restrictions = Attributes::empty();
+
+        let (descriptor, level) = self.descend(addr, |table| {
+            restrictions |= table.attributes();
+        })?;
+
+        let mut attributes = descriptor.attributes();
+        if restrictions.contains(Attributes::TABLE_PRIVILEGED_NO_EXECUTE) {
+            attributes |= Attributes::PRIVILEGED_NO_EXECUTE;
+        }
+        if restrictions.contains(Attributes::TABLE_USER_NO_EXECUTE) {
+            attributes |= Attributes::USER_NO_EXECUTE;
+        }
+        if restrictions.contains(Attributes::TABLE_NO_USER) {
+            attributes = attributes.difference(Attributes::USER);
+        }
+        if restrictions.contains(Attributes::TABLE_READ_ONLY) {
+            attributes |= Attributes::READ_ONLY;
+        }
+
+        let mask = level.size::<P>() - 1;
+        let base = descriptor.0 & Descriptor::ADDRESS_MASK & !mask;
+
+        Ok(Translation {
+            address: Address(base | (addr.0 & mask), PhantomData),
+            level,
+            descriptor,
+            attributes,
+        })
+    }
+
+    /// Walks down the tables, calling `visit` for each table descriptor
+    fn descend(
+        &self,
+        addr: Address<u64, ()>,
+        mut visit: impl FnMut(Descriptor),
+    ) -> Result<(Descriptor, Level), WalkError> {
+        let bits = self.va.bits();
+        let upper = addr.0 >> bits;
+        if upper != 0 && upper != u64::MAX >> bits {
+            return Err(WalkError::OutOfRange(addr));
+        }
+
+        let offset: Address<u64, ()> = Address(addr.0 & ((1 << bits) - 1), PhantomData);
+        let mut table = self.root;
+        let mut level = Level::root::<P>(self.va);
+
+        loop {
+            let frame = self
+                .memory
+                .frame(table)
+                .ok_or(WalkError::MissingTable(Address(table.0, PhantomData)))?;
+            let descriptor = Descriptor::slice(frame)[level.index::<P, ()>(offset)];
+
+            if !descriptor.is_valid() {
+                return Err(WalkError::Invalid(level));
+            }
+
+            match level.next() {
+                Some(next) if descriptor.is_table() => {
+                    visit(descriptor);
+                    table = descriptor.address();
+                    level = next;
+                }
+
+                Some(..) if !level.can_map::<P>() => return Err(WalkError::Reserved(level)),
+                None if !descriptor.is_table() => return Err(WalkError::Reserved(level)),
+                _ => return Ok((descriptor, level)),
+            }
+        }
+    }
+}
+
+impl<'a, M: Frames<P> + ?Sized, P: Frame, D> Translate<Virtual, D, u64> for Walker<'a, M, P> {
+    #[inline]
+    fn translate(&self, addr: SpaceAddress<Virtual, u64, ()>) -> Option<SpaceAddress<D, u64, ()>> {
+        let translation = self.walk(addr.address()).ok()?;
+        Some(SpaceAddress::new(translation.address))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use crate::testing::{frame, set};
+    use crate::{Contiguous, Page16K, Page64K};
+    use std::vec::Vec;
+
+    fn memory<P: Frame + Default>(count: usize) -> Vec<P> {
+        (0..count).map(|_| P::default()).collect()
+    }
+
+    #[test]
+    fn levels() {
+        let roots = [
+            (Level::root::<Page4K>(VaBits::Bits39), Level::L1),
+            (Level::root::<Page4K>(VaBits::Bits48), Level::L0),
+            (Level::root::<Page16K>(VaBits::Bits39), Level::L1),
+            (Level::root::<Page16K>(VaBits::Bits48), Level::L0),
+            (Level::root::<Page64K>(VaBits::Bits39), Level::L2),
+            (Level::root::<Page64K>(VaBits::Bits48), Level::L1),
+        ];
+
+        for (root, expected) in roots {
+            assert_eq!(root, expected);
+        }
+
+        assert_eq!(Level::L1.size::<Page4K>(), 1 << 30);
+        assert_eq!(Level::L2.size::<Page4K>(), 1 << 21);
+        assert_eq!(Level::L2.size::<Page16K>(), 1 << 25);
+        assert_eq!(Level::L2.size::<Page64K>(), 1 << 29);
+        assert_eq!(Level::L0.shift::<Page16K>(), 47);
+        assert!(Level::L1.can_map::<Page4K>());
+        assert!(!Level::L1.can_map::<Page16K>());
+    }
+
+    #[test]
+    fn descriptor() {
+        let page: Address<u64, Page4K> = Address::from(0x1234_5000u64).lower();
+        let d = Descriptor::page(page, Attributes::READ_ONLY | Attributes::ACCESSED)
+            .with_attr_index(5)
+            .with_shareability(Shareability::Inner);
+
+        assert!(d.is_valid());
+        assert!(d.is_table());
+        assert_eq!(d.address::<Page4K>(), page);
+        assert_eq!(d.address::<Page64K>().raw(), 0x1234_0000);
+        assert_eq!(d.attr_index(), 5);
+        assert_eq!(d.shareability(), Some(Shareability::Inner));
+        assert_eq!(
+            d.attributes(),
+            Attributes::VALID | Attributes::TABLE | Attributes::READ_ONLY | Attributes::ACCESSED
+        );
+
+        let d = Descriptor::block(page, Attributes::TABLE);
+        assert!(d.is_valid());
+        assert!(!d.is_table());
+    }
+
+    #[test]
+    fn walk_4k() {
+        let mut pages = memory::<Page4K>(4);
+
+        // L0[1] -> L1 (read-only below), L1[2] -> L2, L1[3] -> 1 GiB block
+        set(
+            &mut pages,
+            0,
+            1,
+            Descriptor::table(frame::<Page4K>(1), Attributes::empty()),
+        );
+        set(
+            &mut pages,
+            1,
+            2,
+            Descriptor::table(frame::<Page4K>(2), Attributes::TABLE_READ_ONLY),
+        );
+        set(
+            &mut pages,
+            1,
+            3,
+            Descriptor::block(
+                Address::from(0xc000_0000u64).lower::<Page4K>(),
+                Attributes::USER,
+            ),
+        );
+
+        // L2[4] -> L3, L3[5] -> page
+        set(
+            &mut pages,
+            2,
+            4,
+            Descriptor::table(frame::<Page4K>(3), Attributes::empty()),
+        );
+        set(
+            &mut pages,
+            3,
+            5,
+            Descriptor::page(Address::from(0x7000u64).lower::<Page4K>(), Attributes::USER),
+        );
+
+        let memory = Contiguous::new(frame(0), pages);
+        let walker = Walker::new(VaBits::Bits48, frame(0), &memory);
+
+        let va = 1 << 39 | 2 << 30 | 4 << 21 | 5 << 12 | 0x123;
+        let t = walker.walk(Address::from(va)).unwrap();
+        assert_eq!(t.address.raw(), 0x7123);
+        assert_eq!(t.level, Level::L3);
+        assert!(t
+            .attributes
+            .contains(Attributes::READ_ONLY | Attributes::USER));
+
+        let t = walker
+            .walk(Address::from(1u64 << 39 | 3 << 30 | 0x1234))
+            .unwrap();
+        assert_eq!(t.address.raw(), 0xc000_1234);
+        assert_eq!(t.level, Level::L1);
+        assert!(!t.attributes.contains(Attributes::READ_ONLY));
+
+        assert_eq!(
+            walker.walk(Address::from(va + 0x1000)),
+            Err(WalkError::Invalid(Level::L3))
+        );
+        assert_eq!(
+            walker.walk(Address::from(1u64 << 48)),
+            Err(WalkError::OutOfRange(Address::from(1u64 << 48)))
+        );
+    }
+
+    #[test]
+    fn walk_16k_upper() {
+        let mut pages = memory::<Page16K>(3);
+
+        // The upper half of a 39-bit address space starts at level 1
+        // with 8 entries; L1[7] -> L2, L2[0x7ff] -> 32 MiB block
+        set(
+            &mut pages,
+            0,
+            7,
+            Descriptor::table(frame::<Page16K>(1), Attributes::empty()),
+        );
+        set(
+            &mut pages,
+            1,
+            0x7ff,
+            Descriptor::block(
+                Address::from(0x200_0000u64).lower::<Page16K>(),
+                Attributes::empty(),
+            ),
+        );
+
+        // Blocks are reserved at level 1 with this granule
+        set(
+            &mut pages,
+            0,
+            6,
+            Descriptor::block(Address::<u64, Page16K>::NULL, Attributes::empty()),
+        );
+
+        let memory = Contiguous::new(frame(0), pages);
+        let walker = Walker::new(VaBits::Bits39, frame::<Page16K>(0), &memory);
+
+        let t = walker
+            .walk(Address::from(0xffff_ffff_ffff_fff0u64))
+            .unwrap();
+        assert_eq!(t.address.raw(), 0x3ff_fff0);
+        assert_eq!(t.level, Level::L2);
+
+        assert_eq!(
+            walker.walk(Address::from(0xffff_ffe0_0000_0000u64)),
+            Err(WalkError::Reserved(Level::L1))
+        );
+    }
+
+    #[test]
+    fn walk_64k() {
+        let mut pages = memory::<Page64K>(3);
+
+        // L1[1] -> L2 (no user access below), L2[2] -> L3, L3[3] -> page
+        set(
+            &mut pages,
+            0,
+            1,
+            Descriptor::table(frame::<Page64K>(1), Attributes::TABLE_NO_USER),
+        );
+        set(
+            &mut pages,
+            1,
+            2,
+            Descriptor::table(frame::<Page64K>(2), Attributes::empty()),
+        );
+        set(
+            &mut pages,
+            2,
+            3,
+            Descriptor::page(
+                Address::from(0x5_0000u64).lower::<Page64K>(),
+                Attributes::USER,
+            ),
+        );
+
+        // Level 3 descriptors without the table bit are reserved
+        set(
+            &mut pages,
+            2,
+            4,
+            Descriptor::block(Address::<u64, Page64K>::NULL, Attributes::empty()),
+        );
+
+        let memory = Contiguous::new(frame(0), pages);
+        let walker = Walker::new(VaBits::Bits48, frame::<Page64K>(0), &memory);
+
+        let va = 1u64 << 42 | 2 << 29 | 3 << 16 | 0xabcd;
+        let t = walker.walk(Address::from(va)).unwrap();
+        assert_eq!(t.address.raw(), 0x5_abcd);
+        assert_eq!(t.level, Level::L3);
+        assert!(!t.attributes.contains(Attributes::USER));
+
+        assert_eq!(
+            walker.walk(Address::from(va + 0x1_0000)),
+            Err(WalkError::Reserved(Level::L3))
+        );
+    }
+}
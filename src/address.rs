@@ -4,7 +4,7 @@ use super::*;
 use core::cmp::Ordering;
 use core::marker::PhantomData;
 use core::mem::{align_of, size_of};
-use core::{ops::*, panic};
+use core::ops::*;
 
 /// An address
 ///
@@ -18,9 +18,27 @@ use core::{ops::*, panic};
 /// Unlike the naked underlying types, you can infallibly convert between,
 /// for example, an `Address<usize, ()>` and an `Address<u64, ()>` wherever
 /// such a conversion is lossless given the target CPU architecture.
-#[derive(Copy, Clone, Default)]
 #[repr(transparent)]
-pub struct Address<T, U>(T, PhantomData<U>);
+pub struct Address<T, U>(pub(crate) T, pub(crate) PhantomData<U>);
+
+// These are implemented by hand, since the derived versions would
+// needlessly require `U` to implement the trait as well.
+
+impl<T: Clone, U> Clone for Address<T, U> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl<T: Copy, U> Copy for Address<T, U> {}
+
+impl<T: Default, U> Default for Address<T, U> {
+    #[inline]
+    fn default() -> Self {
+        Self(T::default(), PhantomData)
+    }
+}
 
 impl<T: core::fmt::Binary, U> core::fmt::Binary for Address<T, U> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
@@ -99,17 +117,184 @@ impl<T: Zero, U> Address<T, U> {
 impl<U> Address<usize, U> {
     /// Creates a new address
     ///
-    /// Panics if the value is not properly aligned.
+    /// Panics if the value is not properly aligned. For other backing types,
+    /// or to handle misaligned values gracefully, use `Address::try_new`.
     #[inline]
     pub const fn new(value: usize) -> Self {
-        if value % align_of::<U>() != 0 {
-            panic!("unaligned address value");
-        }
+        assert!(value % align_of::<U>() == 0, "unaligned address value");
 
         Self(value, PhantomData)
     }
 }
 
+macro_rules! implnew {
+    ($($num:ident)+) => {
+        $(
+            impl<U> Address<$num, U> {
+                /// Creates a new address, checking its alignment
+                #[inline]
+                pub const fn try_new(value: $num) -> Result<Self, AddressError<$num>> {
+                    if value % align_of::<U>() as $num != 0 {
+                        return Err(AddressError::Misaligned {
+                            value,
+                            align: align_of::<U>(),
+                        });
+                    }
+
+                    Ok(Self(value, PhantomData))
+                }
+
+                /// Returns `true` if the address is aligned for the type `V`
+                #[inline]
+                pub const fn is_aligned_to<V>(self) -> bool {
+                    self.0 % align_of::<V>() as $num == 0
+                }
+
+                /// Returns the number of bytes to the next address aligned for the type `V`
+                ///
+                /// This is zero if the address is already aligned.
+                #[inline]
+                pub const fn align_offset<V>(self) -> Offset<$num, ()> {
+                    let align = align_of::<V>() as $num;
+                    Offset::from_items((align - self.0 % align) % align)
+                }
+            }
+        )+
+    };
+}
+
+implnew! { u32 u64 usize }
+
+macro_rules! implarith {
+    ($($num:ident)+) => {
+        $(
+            impl<U> Address<$num, U> {
+                /// The highest address that is properly aligned for `U`
+                #[inline]
+                const fn max() -> Self {
+                    let align = align_of::<U>() as $num;
+                    Self($num::MAX - $num::MAX % align, PhantomData)
+                }
+
+                /// Adds an offset, returning `None` on overflow
+                #[inline]
+                pub const fn checked_add(self, rhs: Offset<$num, U>) -> Option<Self> {
+                    let bytes = match rhs.checked_bytes() {
+                        Some(bytes) => bytes,
+                        None => return None,
+                    };
+
+                    match self.0.checked_add(bytes) {
+                        Some(value) => Some(Self(value, PhantomData)),
+                        None => None,
+                    }
+                }
+
+                /// Subtracts an offset, returning `None` on overflow
+                #[inline]
+                pub const fn checked_sub(self, rhs: Offset<$num, U>) -> Option<Self> {
+                    let bytes = match rhs.checked_bytes() {
+                        Some(bytes) => bytes,
+                        None => return None,
+                    };
+
+                    match self.0.checked_sub(bytes) {
+                        Some(value) => Some(Self(value, PhantomData)),
+                        None => None,
+                    }
+                }
+
+                /// Adds an offset, wrapping around the address space on overflow
+                #[inline]
+                pub const fn wrapping_add(self, rhs: Offset<$num, U>) -> Self {
+                    Self(self.0.wrapping_add(rhs.wrapping_bytes()), PhantomData)
+                }
+
+                /// Subtracts an offset, wrapping around the address space on overflow
+                #[inline]
+                pub const fn wrapping_sub(self, rhs: Offset<$num, U>) -> Self {
+                    Self(self.0.wrapping_sub(rhs.wrapping_bytes()), PhantomData)
+                }
+
+                /// Adds an offset, saturating at the highest aligned address on overflow
+                #[inline]
+                pub const fn saturating_add(self, rhs: Offset<$num, U>) -> Self {
+                    match self.checked_add(rhs) {
+                        Some(addr) => addr,
+                        None => Self::max(),
+                    }
+                }
+
+                /// Subtracts an offset, saturating at the NULL address on overflow
+                #[inline]
+                pub const fn saturating_sub(self, rhs: Offset<$num, U>) -> Self {
+                    match self.checked_sub(rhs) {
+                        Some(addr) => addr,
+                        None => Self(0, PhantomData),
+                    }
+                }
+
+                /// Adds an offset along with a boolean indicating overflow
+                ///
+                /// On overflow, the wrapped address is returned.
+                #[inline]
+                pub const fn overflowing_add(self, rhs: Offset<$num, U>) -> (Self, bool) {
+                    (self.wrapping_add(rhs), self.checked_add(rhs).is_none())
+                }
+
+                /// Subtracts an offset along with a boolean indicating overflow
+                ///
+                /// On overflow, the wrapped address is returned.
+                #[inline]
+                pub const fn overflowing_sub(self, rhs: Offset<$num, U>) -> (Self, bool) {
+                    (self.wrapping_sub(rhs), self.checked_sub(rhs).is_none())
+                }
+
+                /// Calculates the offset from `origin` to `self`
+                ///
+                /// Returns `None` if `origin` is above `self`.
+                #[inline]
+                pub const fn checked_offset_from(self, origin: Self) -> Option<Offset<$num, U>> {
+                    match self.0.checked_sub(origin.0) {
+                        Some(bytes) => Some(Self::items(bytes)),
+                        None => None,
+                    }
+                }
+
+                /// Calculates the offset from `origin` to `self`, wrapping around the address space
+                #[inline]
+                pub const fn wrapping_offset_from(self, origin: Self) -> Offset<$num, U> {
+                    Self::items(self.0.wrapping_sub(origin.0))
+                }
+
+                /// Calculates the offset from `origin` to `self`, saturating at zero
+                #[inline]
+                pub const fn saturating_offset_from(self, origin: Self) -> Offset<$num, U> {
+                    Self::items(self.0.saturating_sub(origin.0))
+                }
+
+                /// Calculates the offset from `origin` to `self` along with a boolean indicating overflow
+                #[inline]
+                pub const fn overflowing_offset_from(self, origin: Self) -> (Offset<$num, U>, bool) {
+                    let (bytes, overflow) = self.0.overflowing_sub(origin.0);
+                    (Self::items(bytes), overflow)
+                }
+
+                /// Converts a number of bytes into a whole number of items
+                #[inline]
+                const fn items(bytes: $num) -> Offset<$num, U> {
+                    match Offset::<$num, U>::from_items(1).checked_bytes() {
+                        Some(stride) => Offset::from_items(bytes / stride),
+                        None => Offset::from_items(0),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+implarith! { u32 u64 usize }
+
 impl<T, U> Address<T, U> {
     /// Creates a new `Address` from a raw inner type without checking
     ///
@@ -118,8 +303,9 @@ impl<T, U> Address<T, U> {
     /// This function is unsafe because it does not enforce the main constraint
     /// of this type that the address stored is properly aligned to the type.
     ///
-    /// For a safe version of this constructor, first create an `Address<T, ()>`
-    /// from the raw value and then align to the type you want.
+    /// For a safe version of this constructor, use `Address::try_new` or
+    /// first create an `Address<T, ()>` from the raw value and then align to
+    /// the type you want.
     #[inline]
     pub const unsafe fn unchecked(value: T) -> Self {
         Self(value, PhantomData)
@@ -155,9 +341,25 @@ where
     pub fn as_mut_ptr(self) -> *mut U {
         self.into().0 as *mut U
     }
-}
 
-pub struct AlignmentError;
+    /// Reads the value at the address without merging or eliding the access
+    ///
+    /// # Safety
+    /// The address must be valid for reads of `U`.
+    #[inline]
+    pub unsafe fn read_volatile(self) -> U {
+        self.as_ptr().read_volatile()
+    }
+
+    /// Writes the value at the address without merging or eliding the access
+    ///
+    /// # Safety
+    /// The address must be valid for writes of `U`.
+    #[inline]
+    pub unsafe fn write_volatile(self, value: U) {
+        self.as_mut_ptr().write_volatile(value)
+    }
+}
 
 impl<T, U> Address<T, U>
 where
@@ -168,11 +370,14 @@ where
     ///
     /// Succeeds only, if they have compatible alignment
     #[inline]
-    pub fn try_cast<V>(self) -> Result<Address<T, V>, AlignmentError> {
+    pub fn try_cast<V>(self) -> Result<Address<T, V>, AddressError<T>> {
         let addr = self.into();
 
         if addr.0 % align_of::<V>() != 0 {
-            return Err(AlignmentError);
+            return Err(AddressError::Misaligned {
+                value: Self::from(addr).0,
+                align: align_of::<V>(),
+            });
         }
 
         Ok(Address(Self::from(addr).0, PhantomData))
@@ -201,6 +406,27 @@ where
         let align: T = Offset::from_items(align_of::<V>()).into().items();
         Address(self.0 / align * align, PhantomData)
     }
+
+    /// Aligns the address up to a multiple of the page size `P`
+    ///
+    /// Unlike `raise()`, this keeps the item type and also supports page
+    /// sizes which no Rust type is aligned to, like `Page1G`. The result is
+    /// still aligned for `U`, since both alignments are powers of two.
+    #[inline]
+    pub fn align_up<P: PageSize>(self) -> Self {
+        let size: T = Offset::from_items(P::SIZE).into().items();
+        Address((self.0 + size - T::ONE) / size * size, PhantomData)
+    }
+
+    /// Aligns the address down to a multiple of the page size `P`
+    ///
+    /// Unlike `lower()`, this keeps the item type and also supports page
+    /// sizes which no Rust type is aligned to, like `Page1G`.
+    #[inline]
+    pub fn align_down<P: PageSize>(self) -> Self {
+        let size: T = Offset::from_items(P::SIZE).into().items();
+        Address(self.0 / size * size, PhantomData)
+    }
 }
 
 /// Convert a raw address value to an untyped `Address`
@@ -279,6 +505,13 @@ impl<U> From<Address<usize, U>> for Address<u64, U> {
     }
 }
 
+impl<U> From<Address<u32, U>> for Address<u64, U> {
+    #[inline]
+    fn from(value: Address<u32, U>) -> Self {
+        Self(value.0 as _, PhantomData)
+    }
+}
+
 #[cfg(any(target_pointer_width = "32", target_pointer_width = "64"))]
 impl<U> From<Address<u32, U>> for Address<usize, U> {
     #[inline]
@@ -295,6 +528,35 @@ impl<U> From<Address<usize, U>> for Address<u32, U> {
     }
 }
 
+macro_rules! impltryfrom {
+    ($($(#[$attr:meta])? $f:ident => $t:ident),* $(,)?) => {
+        $(
+            $(#[$attr])?
+            impl<U> TryFrom<Address<$f, U>> for Address<$t, U> {
+                type Error = AddressError<$f>;
+
+                #[inline]
+                fn try_from(value: Address<$f, U>) -> Result<Self, Self::Error> {
+                    match $t::try_from(value.0) {
+                        Ok(v) => Ok(Self(v, PhantomData)),
+                        Err(_) => Err(AddressError::OutOfRange { value: value.0 }),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impltryfrom! {
+    u64 => u32,
+
+    #[cfg(target_pointer_width = "32")]
+    u64 => usize,
+
+    #[cfg(target_pointer_width = "64")]
+    usize => u32,
+}
+
 impl<T, U> Add<Offset<T, U>> for Address<T, U>
 where
     Offset<usize, ()>: Into<Offset<T, ()>>,
@@ -377,6 +639,159 @@ mod test {
         assert_eq!(Address::from(9usize).lower::<u64>().raw(), 8);
         assert_eq!(Address::from(7usize).raise::<u32>().raw(), 8);
         assert_eq!(Address::from(7usize).lower::<u32>().raw(), 4);
+
+        assert_eq!(Address::from(0x1234u64).raise::<Page>().raw(), 0x2000);
+        assert_eq!(
+            Address::from(0x20_1234u64).lower::<Page2M>().raw(),
+            0x20_0000
+        );
+        assert_eq!(
+            Address::from(0x20_1234u64).raise::<Page2M>().raw(),
+            0x40_0000
+        );
+        assert_eq!(Address::from(0x4001u64).raise::<Page16K>().raw(), 0x8000);
+        assert_eq!(
+            Address::from(0x1_0001u64).lower::<Page64K>().raw(),
+            0x1_0000
+        );
+
+        let addr = Address::<u64, Page>::try_new(0x4020_1000).unwrap();
+        assert_eq!(addr.align_down::<Page1G>().raw(), 0x4000_0000);
+        assert_eq!(addr.align_up::<Page1G>().raw(), 0x8000_0000);
+        assert_eq!(addr.align_down::<Page2M>().raw(), 0x4020_0000);
+        assert_eq!(
+            Address::from(0x4000_0000u64).align_up::<Page1G>().raw(),
+            0x4000_0000
+        );
+    }
+
+    #[test]
+    fn volatile() {
+        let mut value = 0x1234u32;
+        let addr = unsafe { Address::<usize, u32>::unchecked(&mut value as *mut u32 as usize) };
+
+        unsafe {
+            assert_eq!(addr.read_volatile(), 0x1234);
+            addr.write_volatile(0x5678);
+        }
+        assert_eq!(value, 0x5678);
+    }
+
+    #[test]
+    fn checked() {
+        let addr: Address<u64, u64> = Address::from(0xffff_ffff_ffff_fff0u64).raise();
+        let one = Offset::from_items(1);
+        let two = Offset::from_items(2);
+
+        assert_eq!(addr.checked_add(one).unwrap().raw(), 0xffff_ffff_ffff_fff8);
+        assert_eq!(addr.checked_add(two), None);
+        assert_eq!(addr.wrapping_add(two).raw(), 0);
+        assert_eq!(addr.saturating_add(two).raw(), 0xffff_ffff_ffff_fff8);
+        assert_eq!(addr.overflowing_add(two), (Address::NULL, true));
+        assert!(!addr.overflowing_add(one).1);
+
+        let low: Address<u64, u64> = Address::from(8u64).raise();
+        assert_eq!(low.checked_sub(one), Some(Address::NULL));
+        assert_eq!(low.checked_sub(two), None);
+        assert_eq!(low.wrapping_sub(two).raw(), 0xffff_ffff_ffff_fff8);
+        assert_eq!(low.saturating_sub(two), Address::NULL);
+        assert!(low.overflowing_sub(two).1);
+
+        // An offset whose size in bytes does not fit in the address
+        let huge = Offset::from_items(u64::MAX);
+        assert_eq!(low.checked_add(huge), None);
+        assert_eq!(low.saturating_add(huge).raw(), 0xffff_ffff_ffff_fff8);
+    }
+
+    #[test]
+    fn offset_from() {
+        let lo: Address<u64, u32> = Address::from(8u64).raise();
+        let hi: Address<u64, u32> = Address::from(20u64).raise();
+
+        assert_eq!(hi - lo, Offset::from_items(3));
+        assert_eq!(hi.checked_offset_from(lo), Some(Offset::from_items(3)));
+        assert_eq!(lo.checked_offset_from(hi), None);
+        assert_eq!(lo.saturating_offset_from(hi), Offset::from_items(0));
+        assert!(lo.overflowing_offset_from(hi).1);
+        assert_eq!(
+            lo.wrapping_offset_from(hi),
+            Offset::from_items((u64::MAX - 11) / 4)
+        );
+
+        let a = Address::from(3u64);
+        let b = Address::from(10u64);
+        assert_eq!(b - a, Offset::from_items(7));
+        assert_eq!(b.checked_offset_from(a), Some(Offset::from_items(7)));
+    }
+
+    #[test]
+    fn try_new() {
+        assert!(Address::<u32, u32>::try_new(8).is_ok());
+        assert!(Address::<usize, Page>::try_new(0x3000).is_ok());
+        assert_eq!(
+            Address::<u64, Page>::try_new(0x3001),
+            Err(AddressError::Misaligned {
+                value: 0x3001,
+                align: 4096
+            })
+        );
+    }
+
+    #[test]
+    fn try_new_const() {
+        const ADDR: Result<Address<u64, Page>, AddressError<u64>> =
+            Address::<u64, _>::try_new(0x2000);
+        assert_eq!(ADDR.unwrap().raw(), 0x2000);
+    }
+
+    #[test]
+    fn alignment() {
+        let addr = Address::<u32, u16>::try_new(0x1002).unwrap();
+        assert!(addr.is_aligned_to::<u16>());
+        assert!(!addr.is_aligned_to::<u32>());
+        assert!(!Address::<u64, Page>::try_new(0x2000)
+            .unwrap()
+            .is_aligned_to::<Page2M>());
+        assert!(Address::<u64, Page>::try_new(0x20_0000)
+            .unwrap()
+            .is_aligned_to::<Page2M>());
+
+        assert_eq!(addr.align_offset::<u16>(), Offset::from_items(0));
+        assert_eq!(addr.align_offset::<u64>(), Offset::from_items(6));
+        assert_eq!(addr.align_offset::<Page>(), Offset::from_items(0xffe));
+        assert_eq!(
+            Address::<u64, ()>::from(u64::MAX).align_offset::<u32>(),
+            Offset::from_items(1)
+        );
+    }
+
+    #[test]
+    fn try_cast() {
+        let addr = Address::from(0x1002usize);
+        assert_eq!(addr.try_cast::<u16>().unwrap().raw(), 0x1002);
+        assert_eq!(
+            addr.try_cast::<u32>(),
+            Err(AddressError::Misaligned {
+                value: 0x1002,
+                align: 4
+            })
+        );
+    }
+
+    #[test]
+    fn try_from() {
+        let addr = Address::<u64, u32>::try_new(0x1000).unwrap();
+        let small = Address::<u32, u32>::try_from(addr).unwrap();
+        assert_eq!(small.raw(), 0x1000);
+        assert_eq!(Address::<u64, u32>::from(small), addr);
+
+        let addr = Address::<u64, u32>::try_new(0x1_0000_0000).unwrap();
+        assert_eq!(
+            Address::<u32, u32>::try_from(addr),
+            Err(AddressError::OutOfRange {
+                value: 0x1_0000_0000
+            })
+        );
     }
 
     #[test]
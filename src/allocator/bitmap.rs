@@ -0,0 +1,257 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{alignment, get, put, AllocError, Region, Stats};
+use crate::{Address, FrameSource, Offset, Page, Span};
+
+use core::convert::TryFrom;
+
+/// A bitmap frame allocator
+///
+/// This allocator tracks the state of every frame in one bit. It finds the
+/// lowest suitable run of free frames, which makes it slow for large ranges
+/// of memory but also keeps fragmentation low.
+pub struct Bitmap<'a> {
+    region: Region,
+    used: &'a mut [u64],
+    free: usize,
+}
+
+impl<'a> Bitmap<'a> {
+    /// Returns the number of words of bookkeeping memory for `frames` frames
+    #[inline]
+    pub const fn words(frames: usize) -> usize {
+        (frames + 63) / 64
+    }
+
+    /// Creates an allocator for `frames` free frames starting at `base`
+    ///
+    /// The bookkeeping is kept in `memory`, which must have room for at
+    /// least `Bitmap::words(frames)` words.
+    pub fn new(
+        base: Address<u64, Page>,
+        frames: usize,
+        memory: &'a mut [u64],
+    ) -> Result<Self, AllocError> {
+        let region = Region::new(base, frames)?;
+        let used = memory
+            .get_mut(..Self::words(frames))
+            .ok_or(AllocError::TooSmall)?;
+        used.fill(0);
+
+        Ok(Self {
+            region,
+            used,
+            free: frames,
+        })
+    }
+
+    /// Returns the allocator statistics
+    pub fn stats(&self) -> Stats {
+        let mut largest = 0;
+        let mut run = 0;
+
+        for index in 0..self.region.count {
+            run = if get(self.used, index) { 0 } else { run + 1 };
+            largest = largest.max(run);
+        }
+
+        Stats {
+            total: self.region.count,
+            free: self.free,
+            largest,
+        }
+    }
+
+    /// Marks the frames in `span` as used, for example to exclude a hole
+    ///
+    /// All of the frames must be free.
+    pub fn reserve(&mut self, span: Span<u64, Page>) -> Result<(), AllocError> {
+        let (start, end) = self.region.range(span)?;
+
+        if (start..end).any(|index| get(self.used, index)) {
+            return Err(AllocError::InUse);
+        }
+
+        self.mark(start, end, true);
+        Ok(())
+    }
+
+    /// Allocates `count` contiguous frames aligned to `align` frames
+    ///
+    /// The alignment applies to the physical address and must be a power
+    /// of two.
+    pub fn allocate_contiguous(
+        &mut self,
+        count: Offset<u64, Page>,
+        align: Offset<u64, Page>,
+    ) -> Result<Span<u64, Page>, AllocError> {
+        let align = alignment(align)?;
+        let count = usize::try_from(count.items()).map_err(|_| AllocError::OutOfMemory)?;
+        if count > self.region.count {
+            return Err(AllocError::OutOfMemory);
+        }
+
+        let mut start = self.region.align(0, align);
+        while start
+            .checked_add(count)
+            .map_or(false, |end| end <= self.region.count)
+        {
+            match (start..start + count).find(|index| get(self.used, *index)) {
+                Some(used) => start = self.region.align(used + 1, align),
+                None => {
+                    self.mark(start, start + count, true);
+                    return Ok(self.region.span(start, count));
+                }
+            }
+        }
+
+        Err(AllocError::OutOfMemory)
+    }
+
+    /// Frees the frames in `span`
+    ///
+    /// All of the frames must be allocated or reserved.
+    pub fn free(&mut self, span: Span<u64, Page>) -> Result<(), AllocError> {
+        let (start, end) = self.region.range(span)?;
+
+        if (start..end).any(|index| !get(self.used, index)) {
+            return Err(AllocError::NotAllocated);
+        }
+
+        self.mark(start, end, false);
+        Ok(())
+    }
+
+    fn mark(&mut self, start: usize, end: usize, used: bool) {
+        for index in start..end {
+            put(self.used, index, used);
+        }
+
+        if used {
+            self.free -= end - start;
+        } else {
+            self.free += end - start;
+        }
+    }
+}
+
+impl FrameSource for Bitmap<'_> {
+    #[inline]
+    fn allocate(&mut self) -> Option<Address<u64, Page>> {
+        let one = Offset::from_items(1);
+        Some(self.allocate_contiguous(one, one).ok()?.start)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn span(start: u64, count: u64) -> Span<u64, Page> {
+        Span::new(
+            Address::from(start * 0x1000).lower(),
+            Offset::from_items(count),
+        )
+    }
+
+    fn frames(count: u64) -> Offset<u64, Page> {
+        Offset::from_items(count)
+    }
+
+    #[test]
+    fn allocate() {
+        let mut memory = [0; 2];
+        let mut bitmap = Bitmap::new(span(0x10, 0).start, 100, &mut memory).unwrap();
+
+        assert_eq!(
+            bitmap.allocate_contiguous(frames(3), frames(1)),
+            Ok(span(0x10, 3))
+        );
+        assert_eq!(
+            bitmap.allocate_contiguous(frames(2), frames(8)),
+            Ok(span(0x18, 2))
+        );
+        assert_eq!(bitmap.allocate(), Some(span(0x13, 0).start));
+        assert_eq!(
+            bitmap.stats(),
+            Stats {
+                total: 100,
+                free: 94,
+                largest: 100 - 10
+            }
+        );
+
+        bitmap.free(span(0x10, 3)).unwrap();
+        assert_eq!(bitmap.stats().free, 97);
+        assert_eq!(
+            bitmap.allocate_contiguous(frames(4), frames(1)),
+            Ok(span(0x14, 4))
+        );
+
+        assert_eq!(
+            bitmap.allocate_contiguous(frames(91), frames(1)),
+            Err(AllocError::OutOfMemory)
+        );
+        assert_eq!(
+            bitmap.allocate_contiguous(frames(u64::MAX), frames(1)),
+            Err(AllocError::OutOfMemory)
+        );
+        assert_eq!(
+            bitmap.allocate_contiguous(frames(1), frames(1 << 63)),
+            Err(AllocError::OutOfMemory)
+        );
+        assert_eq!(
+            bitmap.allocate_contiguous(frames(1), frames(3)),
+            Err(AllocError::InvalidAlignment)
+        );
+
+        // The first aligned frame is not the first frame
+        let mut memory = [0; 1];
+        let mut bitmap = Bitmap::new(span(0x11, 0).start, 8, &mut memory).unwrap();
+        assert_eq!(
+            bitmap.allocate_contiguous(frames(u64::MAX), frames(2)),
+            Err(AllocError::OutOfMemory)
+        );
+        assert_eq!(
+            bitmap.allocate_contiguous(frames(2), frames(2)),
+            Ok(span(0x12, 2))
+        );
+    }
+
+    #[test]
+    fn reserve() {
+        let mut memory = [0; 1];
+        let mut bitmap = Bitmap::new(span(0, 0).start, 64, &mut memory).unwrap();
+
+        bitmap.reserve(span(0, 1)).unwrap();
+        bitmap.reserve(span(4, 60)).unwrap();
+        assert_eq!(bitmap.reserve(span(3, 2)), Err(AllocError::InUse));
+        assert_eq!(bitmap.reserve(span(63, 2)), Err(AllocError::OutOfRange));
+
+        assert_eq!(
+            bitmap.allocate_contiguous(frames(2), frames(2)),
+            Ok(span(2, 2))
+        );
+        assert_eq!(bitmap.allocate(), Some(span(1, 0).start));
+        assert_eq!(bitmap.allocate(), None);
+
+        assert_eq!(bitmap.free(span(1, 4)), Ok(()));
+        assert_eq!(bitmap.free(span(1, 1)), Err(AllocError::NotAllocated));
+        assert_eq!(bitmap.stats().largest, 4);
+    }
+
+    #[test]
+    fn memory() {
+        let mut memory = [0; 1];
+        assert!(matches!(
+            Bitmap::new(span(0, 0).start, 65, &mut memory),
+            Err(AllocError::TooSmall)
+        ));
+
+        let top = Address::from(0xffff_ffff_ffff_f000u64).lower();
+        assert!(matches!(
+            Bitmap::new(top, 2, &mut memory),
+            Err(AllocError::OutOfRange)
+        ));
+    }
+}
@@ -0,0 +1,387 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{alignment, get, put, AllocError, Region, Stats};
+use crate::{Address, FrameSource, Offset, Page, Span};
+
+use core::convert::TryFrom;
+
+/// The maximum number of block orders
+const ORDERS: usize = usize::BITS as usize;
+
+/// Returns the number of blocks of `order` in `frames` frames
+#[inline]
+const fn blocks(frames: usize, order: usize) -> usize {
+    frames >> order
+}
+
+/// Returns the base-2 logarithm of `value`, rounded down
+#[inline]
+const fn log2(value: usize) -> usize {
+    (usize::BITS - 1 - value.leading_zeros()) as usize
+}
+
+/// A buddy frame allocator
+///
+/// This allocator manages free memory in blocks of 2ⁿ frames, which are
+/// split to satisfy smaller allocations and coalesced with their buddies
+/// when freed. It tracks the free blocks of each order in a bitmap.
+///
+/// Blocks are aligned relative to the base address. Allocations of frame
+/// counts which are not a power of two are carved out of a block and the
+/// remainder is returned to the allocator. An allocation never spans more
+/// than one free block.
+pub struct Buddy<'a> {
+    region: Region,
+    orders: usize,
+    offsets: [usize; ORDERS],
+    free_blocks: &'a mut [u64],
+    free: usize,
+}
+
+impl<'a> Buddy<'a> {
+    /// Returns the number of words of bookkeeping memory for `frames` frames
+    pub const fn words(frames: usize) -> usize {
+        let mut words = 0;
+        let mut order = 0;
+
+        while order < ORDERS && blocks(frames, order) > 0 {
+            words += (blocks(frames, order) + 63) / 64;
+            order += 1;
+        }
+
+        words
+    }
+
+    /// Creates an allocator for `frames` free frames starting at `base`
+    ///
+    /// The bookkeeping is kept in `memory`, which must have room for at
+    /// least `Buddy::words(frames)` words.
+    pub fn new(
+        base: Address<u64, Page>,
+        frames: usize,
+        memory: &'a mut [u64],
+    ) -> Result<Self, AllocError> {
+        let region = Region::new(base, frames)?;
+        let free_blocks = memory
+            .get_mut(..Self::words(frames))
+            .ok_or(AllocError::TooSmall)?;
+        free_blocks.fill(0);
+
+        let mut offsets = [0; ORDERS];
+        let mut words = 0;
+        let mut orders = 0;
+        while orders < ORDERS && blocks(frames, orders) > 0 {
+            offsets[orders] = words * 64;
+            words += (blocks(frames, orders) + 63) / 64;
+            orders += 1;
+        }
+
+        let mut buddy = Self {
+            region,
+            orders,
+            offsets,
+            free_blocks,
+            free: frames,
+        };
+
+        buddy.insert(0, frames);
+        Ok(buddy)
+    }
+
+    /// Returns the allocator statistics
+    pub fn stats(&self) -> Stats {
+        let largest = (0..self.orders)
+            .rev()
+            .find(|order| {
+                (0..blocks(self.region.count, *order)).any(|block| self.is_free(*order, block))
+            })
+            .map(|order| 1 << order)
+            .unwrap_or(0);
+
+        Stats {
+            total: self.region.count,
+            free: self.free,
+            largest,
+        }
+    }
+
+    /// Marks the frames in `span` as used, for example to exclude a hole
+    ///
+    /// All of the frames must be free.
+    pub fn reserve(&mut self, span: Span<u64, Page>) -> Result<(), AllocError> {
+        let (start, end) = self.region.range(span)?;
+
+        let mut index = start;
+        while index < end {
+            match self.containing(index) {
+                Some((order, block)) => index = (block + 1) << order,
+                None => return Err(AllocError::InUse),
+            }
+        }
+
+        self.remove(start, end);
+        self.free -= end - start;
+        Ok(())
+    }
+
+    /// Allocates `count` contiguous frames aligned to `align` frames
+    ///
+    /// The alignment applies to the physical address and must be a power
+    /// of two. The smallest free block which can hold the allocation is used.
+    pub fn allocate_contiguous(
+        &mut self,
+        count: Offset<u64, Page>,
+        align: Offset<u64, Page>,
+    ) -> Result<Span<u64, Page>, AllocError> {
+        let align = alignment(align)?;
+        let count = usize::try_from(count.items()).map_err(|_| AllocError::OutOfMemory)?;
+        if count > self.region.count {
+            return Err(AllocError::OutOfMemory);
+        }
+
+        let order = match count {
+            0 | 1 => 0,
+            count => log2(count - 1) + 1,
+        };
+
+        for order in order..self.orders {
+            for block in 0..blocks(self.region.count, order) {
+                if !self.is_free(order, block) {
+                    continue;
+                }
+
+                let start = self.region.align(block << order, align);
+                let end = start.checked_add(count);
+                if end.map_or(false, |end| end <= (block + 1) << order) {
+                    self.remove(start, start + count);
+                    self.free -= count;
+                    return Ok(self.region.span(start, count));
+                }
+            }
+        }
+
+        Err(AllocError::OutOfMemory)
+    }
+
+    /// Frees the frames in `span`, coalescing free blocks
+    ///
+    /// All of the frames must be allocated or reserved.
+    pub fn free(&mut self, span: Span<u64, Page>) -> Result<(), AllocError> {
+        let (start, end) = self.region.range(span)?;
+
+        if (start..end).any(|index| self.containing(index).is_some()) {
+            return Err(AllocError::NotAllocated);
+        }
+
+        self.insert(start, end);
+        self.free += end - start;
+        Ok(())
+    }
+
+    fn is_free(&self, order: usize, block: usize) -> bool {
+        get(self.free_blocks, self.offsets[order] + block)
+    }
+
+    fn set_free(&mut self, order: usize, block: usize, free: bool) {
+        put(self.free_blocks, self.offsets[order] + block, free);
+    }
+
+    /// Returns the free block containing the frame at `index`
+    fn containing(&self, index: usize) -> Option<(usize, usize)> {
+        (0..self.orders)
+            .map(|order| (order, index >> order))
+            .find(|(order, block)| {
+                *block < blocks(self.region.count, *order) && self.is_free(*order, *block)
+            })
+    }
+
+    /// Adds the frames from `start` to `end` to the free blocks
+    fn insert(&mut self, mut start: usize, end: usize) {
+        while start < end {
+            let mut order = log2(end - start).min(self.orders - 1);
+            if start != 0 {
+                order = order.min(start.trailing_zeros() as usize);
+            }
+
+            self.release(order, start >> order);
+            start += 1 << order;
+        }
+    }
+
+    /// Adds a free block, coalescing it with its buddies
+    fn release(&mut self, mut order: usize, mut block: usize) {
+        while order + 1 < self.orders
+            && (block ^ 1) < blocks(self.region.count, order)
+            && self.is_free(order, block ^ 1)
+        {
+            self.set_free(order, block ^ 1, false);
+            order += 1;
+            block >>= 1;
+        }
+
+        self.set_free(order, block, true);
+    }
+
+    /// Removes the frames from `start` to `end` from the free blocks
+    ///
+    /// The frames must be free. The parts of the affected blocks outside of
+    /// the range are added back.
+    fn remove(&mut self, start: usize, end: usize) {
+        let mut index = start;
+
+        while index < end {
+            let (order, block) = match self.containing(index) {
+                Some(free) => free,
+                None => return,
+            };
+
+            self.set_free(order, block, false);
+
+            let first = block << order;
+            let last = (block + 1) << order;
+            if first < start {
+                self.insert(first, start);
+            }
+            if last > end {
+                self.insert(end, last);
+            }
+
+            index = last;
+        }
+    }
+}
+
+impl FrameSource for Buddy<'_> {
+    #[inline]
+    fn allocate(&mut self) -> Option<Address<u64, Page>> {
+        let one = Offset::from_items(1);
+        Some(self.allocate_contiguous(one, one).ok()?.start)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn span(start: u64, count: u64) -> Span<u64, Page> {
+        Span::new(
+            Address::from(start * 0x1000).lower(),
+            Offset::from_items(count),
+        )
+    }
+
+    fn frames(count: u64) -> Offset<u64, Page> {
+        Offset::from_items(count)
+    }
+
+    #[test]
+    fn words() {
+        assert_eq!(Buddy::words(0), 0);
+        assert_eq!(Buddy::words(1), 1);
+        assert_eq!(Buddy::words(64), 7);
+        assert_eq!(Buddy::words(100), 2 + 6);
+    }
+
+    #[test]
+    fn allocate() {
+        let mut memory = [0; Buddy::words(64)];
+        let mut buddy = Buddy::new(span(0x100, 0).start, 64, &mut memory).unwrap();
+        assert_eq!(buddy.stats().largest, 64);
+
+        // Splitting the 64 frame block into 1 + 1 + 2 + 4 + ... + 32
+        assert_eq!(buddy.allocate(), Some(span(0x100, 0).start));
+        assert_eq!(buddy.stats().largest, 32);
+
+        // Three frames are carved out of a block of four
+        assert_eq!(
+            buddy.allocate_contiguous(frames(3), frames(1)),
+            Ok(span(0x104, 3))
+        );
+        assert_eq!(buddy.allocate(), Some(span(0x101, 0).start));
+        assert_eq!(buddy.allocate(), Some(span(0x107, 0).start));
+
+        assert_eq!(
+            buddy.allocate_contiguous(frames(2), frames(16)),
+            Ok(span(0x110, 2))
+        );
+        assert_eq!(buddy.stats().free, 64 - 8);
+
+        // Freeing everything coalesces back into a single block
+        for span in [span(0x100, 2), span(0x104, 4), span(0x110, 2)] {
+            buddy.free(span).unwrap();
+        }
+        assert_eq!(buddy.free(span(0x100, 1)), Err(AllocError::NotAllocated));
+        assert_eq!(
+            buddy.stats(),
+            Stats {
+                total: 64,
+                free: 64,
+                largest: 64
+            }
+        );
+
+        assert_eq!(
+            buddy.allocate_contiguous(frames(65), frames(1)),
+            Err(AllocError::OutOfMemory)
+        );
+        assert_eq!(
+            buddy.allocate_contiguous(frames(u64::MAX), frames(2)),
+            Err(AllocError::OutOfMemory)
+        );
+        assert_eq!(
+            buddy.allocate_contiguous(frames(1), frames(1 << 63)),
+            Err(AllocError::OutOfMemory)
+        );
+        assert_eq!(
+            buddy.allocate_contiguous(frames(64), frames(1)),
+            Ok(span(0x100, 64))
+        );
+    }
+
+    #[test]
+    fn reserve() {
+        let mut memory = [0; Buddy::words(100)];
+        let mut buddy = Buddy::new(span(1, 0).start, 100, &mut memory).unwrap();
+
+        // A hole in the middle of the 64 frame block
+        buddy.reserve(span(17, 8)).unwrap();
+        assert_eq!(buddy.reserve(span(20, 1)), Err(AllocError::InUse));
+        assert_eq!(buddy.reserve(span(100, 2)), Err(AllocError::OutOfRange));
+        assert_eq!(
+            buddy.stats(),
+            Stats {
+                total: 100,
+                free: 92,
+                largest: 32
+            }
+        );
+
+        // Alignment applies to the physical address, not the block
+        assert_eq!(
+            buddy.allocate_contiguous(frames(4), frames(8)),
+            Ok(span(8, 4))
+        );
+
+        // An allocation never spans more than one block
+        assert_eq!(
+            buddy.allocate_contiguous(frames(32), frames(32)),
+            Err(AllocError::OutOfMemory)
+        );
+
+        buddy.free(span(17, 8)).unwrap();
+        buddy.free(span(8, 4)).unwrap();
+        assert_eq!(buddy.stats().largest, 64);
+        assert_eq!(buddy.stats().free, 100);
+    }
+
+    #[test]
+    fn frame_source() {
+        let mut memory = [0; Buddy::words(3)];
+        let mut buddy = Buddy::new(span(0, 0).start, 3, &mut memory).unwrap();
+
+        assert_eq!(buddy.allocate(), Some(span(2, 0).start));
+        assert_eq!(buddy.allocate(), Some(span(0, 0).start));
+        assert_eq!(buddy.allocate(), Some(span(1, 0).start));
+        assert_eq!(buddy.allocate(), None);
+    }
+}
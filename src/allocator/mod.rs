@@ -0,0 +1,161 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Physical frame allocators
+//!
+//! The allocators in this module hand out frames from a contiguous range of
+//! physical memory, starting at a base address. They keep their bookkeeping
+//! in memory provided by the caller (see `Bitmap::words` and `Buddy::words`)
+//! and never touch the frames they manage, so they can manage memory which
+//! is not mapped.
+//!
+//! Both allocators implement `FrameSource`, which allocates single frames.
+
+mod bitmap;
+mod buddy;
+
+pub use bitmap::Bitmap;
+pub use buddy::Buddy;
+
+use crate::{Address, Offset, Page, PageSize, Span};
+
+use core::convert::TryFrom;
+
+/// An error produced by a frame allocator
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AllocError {
+    /// There are not enough suitable free frames
+    OutOfMemory,
+
+    /// The frames are not managed by the allocator
+    OutOfRange,
+
+    /// Some of the frames to reserve are not free
+    InUse,
+
+    /// Some of the frames to free are not allocated
+    NotAllocated,
+
+    /// The alignment is not a power of two
+    InvalidAlignment,
+
+    /// The memory provided for bookkeeping is too small
+    TooSmall,
+}
+
+impl core::fmt::Display for AllocError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::OutOfMemory => write!(f, "out of memory"),
+            Self::OutOfRange => write!(f, "frames are not managed by the allocator"),
+            Self::InUse => write!(f, "frames are in use"),
+            Self::NotAllocated => write!(f, "frames are not allocated"),
+            Self::InvalidAlignment => write!(f, "alignment is not a power of two"),
+            Self::TooSmall => write!(f, "bookkeeping memory is too small"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for AllocError {}
+
+/// Statistics of a frame allocator
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// The number of frames managed by the allocator
+    pub total: usize,
+
+    /// The number of free frames
+    pub free: usize,
+
+    /// The number of frames in the largest allocation which could succeed
+    pub largest: usize,
+}
+
+impl Stats {
+    /// Returns the number of frames which are allocated or reserved
+    #[inline]
+    pub fn used(&self) -> usize {
+        self.total - self.free
+    }
+}
+
+/// The frames managed by an allocator
+#[derive(Copy, Clone, Debug)]
+struct Region {
+    base: Address<u64, Page>,
+    count: usize,
+}
+
+impl Region {
+    /// Checks that the frames don't extend past the end of the address space
+    fn new(base: Address<u64, Page>, count: usize) -> Result<Self, AllocError> {
+        let items = u64::try_from(count).map_err(|_| AllocError::OutOfRange)?;
+        base.checked_add(Offset::from_items(items))
+            .ok_or(AllocError::OutOfRange)?;
+
+        Ok(Self { base, count })
+    }
+
+    /// Returns the frame number of the first frame
+    fn first(&self) -> u64 {
+        self.base.raw() >> Page::SHIFT
+    }
+
+    /// Returns the index range of the frames in `span`
+    fn range(&self, span: Span<u64, Page>) -> Result<(usize, usize), AllocError> {
+        let start = span
+            .start
+            .checked_offset_from(self.base)
+            .ok_or(AllocError::OutOfRange)?;
+        let start = usize::try_from(start.items()).map_err(|_| AllocError::OutOfRange)?;
+        let count = usize::try_from(span.count.items()).map_err(|_| AllocError::OutOfRange)?;
+
+        match start.checked_add(count) {
+            Some(end) if end <= self.count => Ok((start, end)),
+            _ => Err(AllocError::OutOfRange),
+        }
+    }
+
+    /// Returns the span of `count` frames starting at the index `start`
+    fn span(&self, start: usize, count: usize) -> Span<u64, Page> {
+        Span::new(
+            self.base + Offset::from_items(start as u64),
+            Offset::from_items(count as u64),
+        )
+    }
+
+    /// Returns the first index at or above `start` with the alignment `align`
+    ///
+    /// Returns `usize::MAX` if there is no such index.
+    fn align(&self, start: usize, align: u64) -> usize {
+        let frame = self.first() + start as u64;
+        frame
+            .checked_add(align - 1)
+            .map(|end| end & !(align - 1))
+            .and_then(|aligned| start.checked_add((aligned - frame) as usize))
+            .unwrap_or(usize::MAX)
+    }
+}
+
+/// Returns the alignment in frames, checking that it is a power of two
+fn alignment(align: Offset<u64, Page>) -> Result<u64, AllocError> {
+    match align.items() {
+        align if align.is_power_of_two() => Ok(align),
+        _ => Err(AllocError::InvalidAlignment),
+    }
+}
+
+#[inline]
+fn get(words: &[u64], index: usize) -> bool {
+    words[index / 64] & 1 << (index % 64) != 0
+}
+
+#[inline]
+fn put(words: &mut [u64], index: usize, value: bool) {
+    if value {
+        words[index / 64] |= 1 << (index % 64);
+    } else {
+        words[index / 64] &= !(1 << (index % 64));
+    }
+}
@@ -0,0 +1,175 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Register;
+
+/// An error number returned by a system call
+///
+/// Linux system calls return `-errno` in the return register on failure.
+/// Values from `-4095` to `-1` are errors; all other values are results.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Errno(pub i32);
+
+impl Errno {
+    /// The largest error number which can be returned
+    pub const MAX: i32 = 4095;
+
+    /// Decodes a system call return value
+    #[inline]
+    pub fn decode(value: Register<usize>) -> Result<Register<usize>, Self> {
+        let ret = isize::from(value);
+        match ret < 0 && ret >= -(Self::MAX as isize) {
+            true => Err(Self(-ret as i32)),
+            false => Ok(value),
+        }
+    }
+
+    /// Encodes a system call result as a return value
+    #[inline]
+    pub fn encode(result: Result<Register<usize>, Self>) -> Register<usize> {
+        match result {
+            Ok(value) => value,
+            Err(Self(errno)) => Register::from(-(errno as isize)),
+        }
+    }
+}
+
+impl core::fmt::Display for Errno {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "system call failed with errno {}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Errno {}
+
+/// Packs typed arguments into argument registers
+///
+/// Every value which converts into a `Register<usize>` takes one register:
+/// integers, signed integers (sign-extended), pointers and references.
+/// Slices take two registers, the pointer followed by the length.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Arguments<const N: usize> {
+    regs: [Register<usize>; N],
+    len: usize,
+}
+
+impl<const N: usize> Default for Arguments<N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> Arguments<N> {
+    /// Creates an empty argument list; unused registers are zero
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            regs: [Register::from(0usize); N],
+            len: 0,
+        }
+    }
+
+    /// Returns the number of registers used
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Returns `true` if no register is used
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Appends a value
+    ///
+    /// # Panics
+    /// Panics if all registers are used.
+    #[inline]
+    pub fn arg(mut self, value: impl Into<Register<usize>>) -> Self {
+        assert!(self.len < N, "too many arguments");
+        self.regs[self.len] = value.into();
+        self.len += 1;
+        self
+    }
+
+    /// Appends the pointer and the length of a slice
+    ///
+    /// # Panics
+    /// Panics if fewer than two registers are left.
+    #[inline]
+    pub fn slice<T>(self, slice: &[T]) -> Self {
+        self.arg(slice).arg(slice.len())
+    }
+
+    /// Returns the registers
+    #[inline]
+    pub fn into_inner(self) -> [Register<usize>; N] {
+        self.regs
+    }
+}
+
+impl<const N: usize> From<Arguments<N>> for [Register<usize>; N] {
+    #[inline]
+    fn from(value: Arguments<N>) -> Self {
+        value.into_inner()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn errno() {
+        assert_eq!(
+            Errno::decode(Register::from(0usize)),
+            Ok(Register::from(0usize))
+        );
+        assert_eq!(Errno::decode(Register::from(-2isize)), Err(Errno(2)));
+        assert_eq!(Errno::decode(Register::from(-4095isize)), Err(Errno(4095)));
+
+        let addr = Register::from(-4096isize);
+        assert_eq!(Errno::decode(addr), Ok(addr));
+
+        assert_eq!(Errno::encode(Err(Errno(22))), Register::from(-22isize));
+        assert_eq!(Errno::encode(Ok(addr)), addr);
+    }
+
+    #[test]
+    fn display() {
+        extern crate std;
+        use std::string::ToString;
+
+        assert_eq!(Errno(9).to_string(), "system call failed with errno 9");
+    }
+
+    #[test]
+    fn arguments() {
+        let mut buf = [0u32; 4];
+        let ptr = buf.as_mut_ptr() as usize;
+
+        let args = Arguments::<6>::new()
+            .arg(-100i32)
+            .slice(&buf[..])
+            .arg(&mut buf)
+            .arg(0o644u32);
+        assert_eq!(args.len(), 5);
+
+        let regs = args.into_inner();
+        assert_eq!(isize::from(regs[0]), -100);
+        assert_eq!(usize::from(regs[1]), ptr);
+        assert_eq!(usize::from(regs[2]), 4);
+        assert_eq!(usize::from(regs[3]), ptr);
+        assert_eq!(usize::from(regs[4]), 0o644);
+        assert_eq!(usize::from(regs[5]), 0);
+        assert!(Arguments::<2>::default().is_empty());
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many() {
+        let _ = Arguments::<2>::new().arg(1usize).slice(&[0u8][..]);
+    }
+}
@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use core::marker::PhantomData;
+
+/// An x86_64 paging mode, which determines the width of virtual addresses
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Paging {
+    /// 4-level paging with 48-bit virtual addresses
+    Level4,
+
+    /// 5-level paging (LA57) with 57-bit virtual addresses
+    Level5,
+}
+
+impl Paging {
+    /// Returns the number of significant bits in a virtual address
+    #[inline]
+    pub const fn bits(self) -> u32 {
+        match self {
+            Self::Level4 => 48,
+            Self::Level5 => 57,
+        }
+    }
+}
+
+/// Sign-extends `value` from bit `bits - 1`
+#[inline]
+const fn extend(value: u64, paging: Paging) -> u64 {
+    let shift = 64 - paging.bits();
+    ((value << shift) as i64 >> shift) as u64
+}
+
+impl<U> Address<u64, U> {
+    /// Returns `true` if the address is canonical in the given paging mode
+    ///
+    /// An address is canonical if all bits above the most significant
+    /// implemented bit are copies of that bit.
+    #[inline]
+    pub const fn is_canonical(self, paging: Paging) -> bool {
+        extend(self.0, paging) == self.0
+    }
+
+    /// Converts the address to canonical form by sign-extension
+    ///
+    /// This is useful when the address has been truncated to its
+    /// significant bits, for example when it was read from a page table.
+    #[inline]
+    pub const fn canonicalize(self, paging: Paging) -> Self {
+        Self(extend(self.0, paging), PhantomData)
+    }
+}
+
+impl Address<u64, ()> {
+    /// Creates a new address, checking that it is canonical
+    #[inline]
+    pub const fn try_canonical(value: u64, paging: Paging) -> Result<Self, AddressError<u64>> {
+        let addr = Self(value, PhantomData);
+
+        if !addr.is_canonical(paging) {
+            return Err(AddressError::NonCanonical { value });
+        }
+
+        Ok(addr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn canonical() {
+        let canonical = [
+            (0, Paging::Level4),
+            (0x0000_7fff_ffff_ffff, Paging::Level4),
+            (0xffff_8000_0000_0000, Paging::Level4),
+            (0xffff_ffff_ffff_ffff, Paging::Level4),
+            (0x00ff_ffff_ffff_ffff, Paging::Level5),
+            (0xff00_0000_0000_0000, Paging::Level5),
+            (0x0000_8000_0000_0000, Paging::Level5),
+        ];
+
+        for (value, paging) in canonical {
+            assert!(Address::from(value).is_canonical(paging));
+            assert!(Address::try_canonical(value, paging).is_ok());
+        }
+
+        let noncanonical = [
+            (0x0000_8000_0000_0000, Paging::Level4),
+            (0xffff_7fff_ffff_ffff, Paging::Level4),
+            (0x0100_0000_0000_0000, Paging::Level5),
+            (0xfeff_ffff_ffff_ffff, Paging::Level5),
+        ];
+
+        for (value, paging) in noncanonical {
+            assert!(!Address::from(value).is_canonical(paging));
+            assert_eq!(
+                Address::try_canonical(value, paging),
+                Err(AddressError::NonCanonical { value })
+            );
+        }
+    }
+
+    #[test]
+    fn canonicalize() {
+        let addr: Address<u64, Page> = Address::from(0x0000_8000_0000_1000u64).lower();
+        assert_eq!(
+            addr.canonicalize(Paging::Level4).raw(),
+            0xffff_8000_0000_1000
+        );
+        assert_eq!(
+            addr.canonicalize(Paging::Level5).raw(),
+            0x0000_8000_0000_1000
+        );
+
+        let addr = Address::from(0x0000_7fff_ffff_f000u64);
+        assert_eq!(addr.canonicalize(Paging::Level4), addr);
+    }
+}
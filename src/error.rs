@@ -0,0 +1,75 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use core::fmt::{Display, Formatter, LowerHex, Result};
+
+/// An error produced when creating or converting an `Address`
+///
+/// The offending value is carried in the backing type `T` of the address.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AddressError<T> {
+    /// The value is not properly aligned
+    Misaligned {
+        /// The offending value
+        value: T,
+
+        /// The required alignment in bytes
+        align: usize,
+    },
+
+    /// An arithmetic operation overflowed
+    Overflow,
+
+    /// The value is not a canonical virtual address
+    NonCanonical {
+        /// The offending value
+        value: T,
+    },
+
+    /// The value cannot be represented in the target type
+    OutOfRange {
+        /// The offending value
+        value: T,
+    },
+}
+
+impl<T: LowerHex> Display for AddressError<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        match self {
+            Self::Misaligned { value, align } => {
+                write!(f, "address {:#x} is not aligned to {:#x}", value, align)
+            }
+            Self::Overflow => write!(f, "address arithmetic overflowed"),
+            Self::NonCanonical { value } => write!(f, "address {:#x} is not canonical", value),
+            Self::OutOfRange { value } => write!(f, "address {:#x} is out of range", value),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: LowerHex + core::fmt::Debug> std::error::Error for AddressError<T> {}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use std::string::ToString;
+
+    #[test]
+    fn display() {
+        let err = AddressError::Misaligned {
+            value: 0x1001u64,
+            align: 4096,
+        };
+        assert_eq!(err.to_string(), "address 0x1001 is not aligned to 0x1000");
+
+        let err = AddressError::NonCanonical {
+            value: 0x8000_0000_0000u64,
+        };
+        assert_eq!(err.to_string(), "address 0x800000000000 is not canonical");
+
+        let err = AddressError::<u32>::Overflow;
+        assert_eq!(err.to_string(), "address arithmetic overflowed");
+    }
+}
@@ -0,0 +1,167 @@
+// SPDX-License-Identifier: Apache-2.0
+
+/// Defines a set of single-bit flags in an integer
+///
+/// ```text
+/// flags! {
+///     /// The flags of an entry
+///     pub struct Flags {
+///         /// The entry is present
+///         PRESENT = 0;
+///
+///         /// The entry is writable
+///         WRITABLE = 1;
+///     }
+/// }
+/// ```
+///
+/// The flags are stored in a `u64`, unless another integer type is given
+/// after the name, as in `pub struct Flags(u8) { ... }`.
+///
+/// Every flag becomes a constant of the generated type. The type has the
+/// set operations and operators, and its `Debug` implementation prints the
+/// names of the flags which are set and any other bits in hexadecimal.
+///
+/// The type must also provide a `from_bits_truncate` method, which is used
+/// by `Not` to discard the bits which belong to other fields.
+macro_rules! flags {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident {
+            $(
+                $(#[$fattr:meta])*
+                $flag:ident = $bit:literal;
+            )+
+        }
+    ) => {
+        flags! {
+            $(#[$attr])*
+            $vis struct $name(u64) {
+                $(
+                    $(#[$fattr])*
+                    $flag = $bit;
+                )+
+            }
+        }
+    };
+
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident($bits:ty) {
+            $(
+                $(#[$fattr:meta])*
+                $flag:ident = $bit:literal;
+            )+
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+        #[repr(transparent)]
+        $vis struct $name($bits);
+
+        impl $name {
+            $(
+                $(#[$fattr])*
+                pub const $flag: Self = Self(1 << $bit);
+            )+
+
+            const NAMES: &'static [(Self, &'static str)] = &[
+                $((Self::$flag, stringify!($flag)),)+
+            ];
+
+            /// Returns an empty set of flags
+            #[inline]
+            pub const fn empty() -> Self {
+                Self(0)
+            }
+
+            /// Returns the raw bits
+            #[inline]
+            pub const fn bits(self) -> $bits {
+                self.0
+            }
+
+            /// Returns `true` if all of the flags in `other` are set
+            #[inline]
+            pub const fn contains(self, other: Self) -> bool {
+                self.0 & other.0 == other.0
+            }
+
+            /// Returns the union of both sets of flags
+            #[inline]
+            pub const fn union(self, other: Self) -> Self {
+                Self(self.0 | other.0)
+            }
+
+            /// Returns the flags in `self` which are not in `other`
+            #[inline]
+            pub const fn difference(self, other: Self) -> Self {
+                Self(self.0 & !other.0)
+            }
+        }
+
+        impl ::core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                let mut rest = *self;
+                let mut first = true;
+
+                f.write_str(concat!(stringify!($name), "("))?;
+                for &(flag, name) in Self::NAMES {
+                    if self.contains(flag) {
+                        if !first {
+                            f.write_str(" | ")?;
+                        }
+
+                        f.write_str(name)?;
+                        rest = rest.difference(flag);
+                        first = false;
+                    }
+                }
+
+                if rest != Self::empty() {
+                    if !first {
+                        f.write_str(" | ")?;
+                    }
+
+                    write!(f, "{:#x}", rest.0)?;
+                }
+
+                f.write_str(")")
+            }
+        }
+
+        impl ::core::ops::BitOr for $name {
+            type Output = Self;
+
+            #[inline]
+            fn bitor(self, rhs: Self) -> Self {
+                self.union(rhs)
+            }
+        }
+
+        impl ::core::ops::BitOrAssign for $name {
+            #[inline]
+            fn bitor_assign(&mut self, rhs: Self) {
+                *self = self.union(rhs);
+            }
+        }
+
+        impl ::core::ops::BitAnd for $name {
+            type Output = Self;
+
+            #[inline]
+            fn bitand(self, rhs: Self) -> Self {
+                Self(self.0 & rhs.0)
+            }
+        }
+
+        impl ::core::ops::Not for $name {
+            type Output = Self;
+
+            #[inline]
+            fn not(self) -> Self {
+                Self::from_bits_truncate(!self.0)
+            }
+        }
+    };
+}
@@ -5,6 +5,9 @@
 //!   * Addresses
 //!   * Offsets
 //!   * Pages
+//!
+//! The minimum supported Rust version is 1.57. The `asm` feature needs Rust
+//! 1.59.
 
 #![no_std]
 #![forbid(clippy::expect_used, clippy::panic)]
@@ -19,17 +22,53 @@
 #[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[cfg(feature = "std")]
+extern crate std;
+
 mod address;
+mod arguments;
+mod canonical;
+mod error;
+#[macro_use]
+mod flags;
+mod line;
+mod memory;
 mod offset;
 mod page;
 mod pages;
 mod register;
+mod space;
+mod span;
+mod volatile;
+
+#[cfg(test)]
+mod testing;
+
+pub mod aarch64;
+pub mod allocator;
+pub mod memmap;
+pub mod mmio;
+pub mod riscv64;
+pub mod x86_64;
 
 pub use address::Address;
+pub use arguments::{Arguments, Errno};
+pub use canonical::Paging;
+pub use error::AddressError;
+pub use line::{Addresses, Line};
+pub use memory::{Contiguous, FrameSource, Frames, FramesMut};
 pub use offset::Offset;
-pub use page::Page;
+pub use page::{Frame, Page, Page16K, Page1G, Page2M, Page4K, Page64K, PageSize};
 pub use pages::Pages;
 pub use register::Register;
+pub use space::{
+    GuestPhysAddr, GuestPhysical, HostPhysAddr, HostPhysical, Identity, Space, SpaceAddress,
+    Translate, VirtAddr, Virtual,
+};
+pub use span::Span;
+pub use volatile::{
+    Access, ReadOnly, ReadWrite, Readable, Volatile, VolatileRef, Writable, WriteOnly,
+};
 
 /// Defines the additive identity value
 pub trait Zero: Copy {
@@ -0,0 +1,272 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use core::ops::{Add, Range, Sub};
+
+/// A half-open range of addresses: `start..end`
+///
+/// This is the address equivalent of `core::ops::Range`. Like `Address`, it
+/// carries the type `U` of the items it contains, so both of its bounds are
+/// always properly aligned for `U`. A `Line` can be losslessly converted to
+/// a `Span`, which describes the same region as a start and a count. The
+/// conversion back fails if the span reaches the top of the address space.
+pub struct Line<T, U> {
+    /// The first address in the range
+    pub start: Address<T, U>,
+
+    /// The first address past the end of the range
+    pub end: Address<T, U>,
+}
+
+impl<T: Clone, U> Clone for Line<T, U> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::new(self.start.clone(), self.end.clone())
+    }
+}
+
+impl<T: Copy, U> Copy for Line<T, U> {}
+
+impl<T: core::fmt::LowerHex, U> core::fmt::Debug for Line<T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:?}..{:?}", self.start, self.end)
+    }
+}
+
+impl<T: PartialEq, U> PartialEq for Line<T, U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.start == other.start && self.end == other.end
+    }
+}
+
+impl<T: Eq, U> Eq for Line<T, U> {}
+
+impl<T, U> Line<T, U> {
+    /// Creates a new range from its bounds
+    #[inline]
+    pub const fn new(start: Address<T, U>, end: Address<T, U>) -> Self {
+        Self { start, end }
+    }
+}
+
+impl<T: Copy + Ord, U> Line<T, U> {
+    /// Returns `true` if the range contains no addresses
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.start >= self.end
+    }
+
+    /// Returns `true` if `addr` lies within the range
+    #[inline]
+    pub fn contains(&self, addr: &Address<T, U>) -> bool {
+        self.start <= *addr && *addr < self.end
+    }
+
+    /// Returns `true` if the two ranges share at least one address
+    #[inline]
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Returns the range of addresses common to both ranges, if any
+    #[inline]
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let line = Self::new(self.start.max(other.start), self.end.min(other.end));
+
+        if line.is_empty() {
+            return None;
+        }
+
+        Some(line)
+    }
+
+    /// Splits the range in two at the specified address
+    ///
+    /// The first range covers `start..at` and the second covers `at..end`.
+    /// Returns `None` if `at` lies outside of `start..=end`.
+    #[inline]
+    pub fn split_at(self, at: Address<T, U>) -> Option<(Self, Self)> {
+        if at < self.start || at > self.end {
+            return None;
+        }
+
+        Some((Self::new(self.start, at), Self::new(at, self.end)))
+    }
+}
+
+impl<T: Copy + Ord + Zero, U> Line<T, U>
+where
+    Address<T, U>: Sub<Output = Offset<T, U>>,
+{
+    /// Returns the number of whole items in the range
+    #[inline]
+    pub fn count(&self) -> Offset<T, U> {
+        if self.is_empty() {
+            return Offset::from_items(T::ZERO);
+        }
+
+        self.end - self.start
+    }
+
+    /// Returns an iterator over each address in the range
+    ///
+    /// Only addresses of items which fit entirely within the range are
+    /// yielded.
+    #[inline]
+    pub fn iter(&self) -> Addresses<T, U> {
+        Addresses(Span::from(*self))
+    }
+}
+
+impl<T: Copy + Ord + Zero + One, U> IntoIterator for Line<T, U>
+where
+    Address<T, U>: Add<Offset<T, U>, Output = Address<T, U>>,
+    Address<T, U>: Sub<Output = Offset<T, U>>,
+    Offset<T, U>: Sub<Output = Offset<T, U>>,
+{
+    type Item = Address<T, U>;
+    type IntoIter = Addresses<T, U>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T, U> From<Range<Address<T, U>>> for Line<T, U> {
+    #[inline]
+    fn from(value: Range<Address<T, U>>) -> Self {
+        Self::new(value.start, value.end)
+    }
+}
+
+impl<T, U> From<Line<T, U>> for Range<Address<T, U>> {
+    #[inline]
+    fn from(value: Line<T, U>) -> Self {
+        value.start..value.end
+    }
+}
+
+/// An iterator over the addresses in a `Line` or a `Span`
+///
+/// This type is created by `Line::iter()` and `Span::iter()`.
+pub struct Addresses<T, U>(pub(crate) Span<T, U>);
+
+impl<T: Clone, U> Clone for Addresses<T, U> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone())
+    }
+}
+
+impl<T: core::fmt::LowerHex + core::fmt::Debug, U> core::fmt::Debug for Addresses<T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Addresses").field(&self.0).finish()
+    }
+}
+
+impl<T: Copy + Ord + One, U> Iterator for Addresses<T, U>
+where
+    Address<T, U>: Add<Offset<T, U>, Output = Address<T, U>>,
+    Offset<T, U>: Sub<Output = Offset<T, U>>,
+{
+    type Item = Address<T, U>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let one = Offset::from_items(T::ONE);
+
+        if self.0.count < one {
+            return None;
+        }
+
+        // The start only moves while items remain, so that the last item
+        // of the address space does not overflow it
+        let addr = self.0.start;
+        self.0.count = self.0.count - one;
+        if self.0.count >= one {
+            self.0.start = addr + one;
+        }
+
+        Some(addr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use std::vec::Vec;
+
+    fn line(start: u64, end: u64) -> Line<u64, u32> {
+        Line::new(Address::from(start).raise(), Address::from(end).raise())
+    }
+
+    #[test]
+    fn empty() {
+        assert!(line(8, 8).is_empty());
+        assert!(line(8, 4).is_empty());
+        assert!(!line(4, 8).is_empty());
+        assert_eq!(line(8, 4).count(), Offset::from_items(0));
+        assert_eq!(line(4, 16).count(), Offset::from_items(3));
+    }
+
+    #[test]
+    fn contains() {
+        let l = line(4, 12);
+        assert!(!l.contains(&Address::from(0u64).raise()));
+        assert!(l.contains(&Address::from(4u64).raise()));
+        assert!(l.contains(&Address::from(8u64).raise()));
+        assert!(!l.contains(&Address::from(12u64).raise()));
+    }
+
+    #[test]
+    fn overlaps() {
+        assert!(line(4, 12).overlaps(&line(8, 16)));
+        assert!(line(8, 16).overlaps(&line(4, 12)));
+        assert!(line(0, 16).overlaps(&line(4, 8)));
+        assert!(!line(4, 8).overlaps(&line(8, 12)));
+        assert!(!line(4, 8).overlaps(&line(8, 8)));
+        assert!(!line(4, 12).overlaps(&line(8, 8)));
+    }
+
+    #[test]
+    fn intersection() {
+        assert_eq!(line(4, 12).intersection(&line(8, 16)), Some(line(8, 12)));
+        assert_eq!(line(0, 16).intersection(&line(4, 8)), Some(line(4, 8)));
+        assert_eq!(line(4, 8).intersection(&line(8, 12)), None);
+    }
+
+    #[test]
+    fn split_at() {
+        let l = line(4, 12);
+        assert_eq!(
+            l.split_at(Address::from(8u64).raise()),
+            Some((line(4, 8), line(8, 12)))
+        );
+        assert_eq!(
+            l.split_at(Address::from(12u64).raise()),
+            Some((line(4, 12), line(12, 12)))
+        );
+        assert_eq!(l.split_at(Address::from(16u64).raise()), None);
+    }
+
+    #[test]
+    fn iter() {
+        let addrs: Vec<u64> = line(4, 16).into_iter().map(|a| a.raw()).collect();
+        assert_eq!(addrs, [4, 8, 12]);
+
+        // Items that do not fit entirely are not yielded
+        let line: Line<u64, [u32; 3]> =
+            Line::new(Address::from(0u64).raise(), Address::from(16u64).raise());
+        let addrs: Vec<u64> = line.iter().map(|a| a.raw()).collect();
+        assert_eq!(addrs, [0]);
+    }
+
+    #[test]
+    fn range() {
+        let range: Range<Address<u64, u32>> = line(4, 12).into();
+        assert_eq!(Line::from(range), line(4, 12));
+    }
+}
@@ -0,0 +1,266 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! E820 memory maps
+//!
+//! The BIOS reports the memory map as a table of 20-byte entries, which the
+//! Linux boot protocol passes on in the `boot_params` structure ("zero
+//! page").
+
+use super::{read_u32, read_u64, ParseError, Region};
+use crate::{Address, Line};
+
+/// The offset of the entry count (`e820_entries`) in `boot_params`
+pub const ENTRIES_OFFSET: usize = 0x1e8;
+
+/// The offset of the table (`e820_table`) in `boot_params`
+pub const TABLE_OFFSET: usize = 0x2d0;
+
+/// The maximum number of entries in `boot_params`
+pub const MAX_ENTRIES: usize = 128;
+
+/// The size of an entry in bytes
+pub const ENTRY_SIZE: usize = 20;
+
+/// The type of an E820 entry
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// Usable RAM
+    Ram,
+
+    /// Reserved by the firmware
+    Reserved,
+
+    /// ACPI tables, usable after they have been read
+    Acpi,
+
+    /// ACPI non-volatile storage
+    Nvs,
+
+    /// Memory with detected errors
+    Unusable,
+
+    /// Disabled memory
+    Disabled,
+
+    /// Persistent memory
+    Persistent,
+
+    /// Any other type
+    Other(u32),
+}
+
+impl From<u32> for Kind {
+    #[inline]
+    fn from(value: u32) -> Self {
+        match value {
+            1 => Self::Ram,
+            2 => Self::Reserved,
+            3 => Self::Acpi,
+            4 => Self::Nvs,
+            5 => Self::Unusable,
+            6 => Self::Disabled,
+            7 => Self::Persistent,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl From<Kind> for u32 {
+    #[inline]
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::Ram => 1,
+            Kind::Reserved => 2,
+            Kind::Acpi => 3,
+            Kind::Nvs => 4,
+            Kind::Unusable => 5,
+            Kind::Disabled => 6,
+            Kind::Persistent => 7,
+            Kind::Other(other) => other,
+        }
+    }
+}
+
+/// An E820 table
+///
+/// Entries are not sorted and may overlap. Their ends are clamped to the
+/// top of the address space.
+#[derive(Copy, Clone, Debug)]
+pub struct Table<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Table<'a> {
+    /// Parses a table of packed entries
+    pub fn new(bytes: &'a [u8]) -> Result<Self, ParseError> {
+        if bytes.len() % ENTRY_SIZE != 0 {
+            return Err(ParseError::Truncated);
+        }
+
+        Ok(Self { bytes })
+    }
+
+    /// Parses the table in a `boot_params` structure
+    pub fn from_boot_params(boot_params: &'a [u8]) -> Result<Self, ParseError> {
+        let count = *boot_params
+            .get(ENTRIES_OFFSET)
+            .ok_or(ParseError::Truncated)? as usize;
+        if count > MAX_ENTRIES {
+            return Err(ParseError::TooManyEntries);
+        }
+
+        boot_params
+            .get(TABLE_OFFSET..TABLE_OFFSET + count * ENTRY_SIZE)
+            .ok_or(ParseError::Truncated)
+            .and_then(Self::new)
+    }
+
+    /// Returns the number of entries
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len() / ENTRY_SIZE
+    }
+
+    /// Returns `true` if the table has no entries
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Returns an iterator over the entries as regions
+    #[inline]
+    pub fn iter(&self) -> Entries<'a> {
+        Entries {
+            chunks: self.bytes.chunks_exact(ENTRY_SIZE),
+        }
+    }
+}
+
+impl<'a> IntoIterator for Table<'a> {
+    type Item = Region<(), Kind>;
+    type IntoIter = Entries<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of an E820 table
+#[derive(Clone, Debug)]
+pub struct Entries<'a> {
+    chunks: core::slice::ChunksExact<'a, u8>,
+}
+
+impl Iterator for Entries<'_> {
+    type Item = Region<(), Kind>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let entry = self.chunks.next()?;
+        let start = read_u64(entry, 0);
+        let end = start.saturating_add(read_u64(entry, 8));
+        let kind = Kind::from(read_u32(entry, 16));
+
+        Some(Region::new(
+            Line::new(Address::from(start), Address::from(end)),
+            kind,
+        ))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Entries<'_> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::memmap::FixedRegionSet;
+    use crate::Page;
+
+    /// A hand-built zero page, modelled on a QEMU q35 guest with 2 GiB of RAM
+    ///
+    /// Only `e820_entries` and the E820 table are filled in.
+    const BOOT_PARAMS: &[u8] = include_bytes!("fixtures/boot_params.bin");
+
+    fn region(start: u64, end: u64, kind: Kind) -> Region<(), Kind> {
+        Region::new(Line::new(Address::from(start), Address::from(end)), kind)
+    }
+
+    #[test]
+    fn parse() {
+        let table = Table::from_boot_params(BOOT_PARAMS).unwrap();
+        assert_eq!(table.len(), 9);
+        assert_eq!(table.iter().len(), 9);
+
+        let mut entries = table.iter();
+        assert_eq!(entries.next(), Some(region(0, 0x9_fc00, Kind::Ram)));
+        assert_eq!(
+            entries.next(),
+            Some(region(0x9_fc00, 0xa_0000, Kind::Reserved))
+        );
+        assert_eq!(
+            entries.nth(1),
+            Some(region(0x10_0000, 0x7ffe_0000, Kind::Ram))
+        );
+        assert_eq!(
+            entries.last(),
+            Some(region(0xfffc_0000, 0x1_0000_0000, Kind::Reserved))
+        );
+    }
+
+    #[test]
+    fn region_set() {
+        let table = Table::from_boot_params(BOOT_PARAMS).unwrap();
+        let mut set = FixedRegionSet::<Page, Kind, 16>::new();
+        set.extend(table.iter().filter_map(Region::align_inward))
+            .unwrap();
+
+        let ram = |kind: &Kind| *kind == Kind::Ram;
+        let total: u64 = set
+            .iter()
+            .filter(|region| ram(&region.tag))
+            .map(|region| region.line.count().items())
+            .sum();
+        assert_eq!(total, 0x9f + 0x7fee0);
+
+        // The partial page below 640 KiB is dropped from RAM and reserved
+        let page = |addr: u64| Address::from(addr).lower::<Page>();
+        assert_eq!(set.regions()[0].line, Line::new(page(0), page(0x9_f000)));
+        assert!(set.get(page(0x9_f000)).is_none());
+        assert_eq!(set.get(page(0xf_0000)).unwrap().tag, Kind::Reserved);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Table::from_boot_params(&BOOT_PARAMS[..0x300]).unwrap_err(),
+            ParseError::Truncated
+        );
+        assert_eq!(
+            Table::new(&BOOT_PARAMS[..ENTRY_SIZE + 1]).unwrap_err(),
+            ParseError::Truncated
+        );
+
+        let mut boot_params = [0; 0x1000];
+        boot_params[ENTRIES_OFFSET] = 129;
+        assert_eq!(
+            Table::from_boot_params(&boot_params).unwrap_err(),
+            ParseError::TooManyEntries
+        );
+
+        // Entries reaching past the top of the address space are clamped
+        let mut entry = [0xff; ENTRY_SIZE];
+        entry[16..].copy_from_slice(&9u32.to_le_bytes());
+        let table = Table::new(&entry).unwrap();
+        assert_eq!(
+            table.iter().next(),
+            Some(region(u64::MAX, u64::MAX, Kind::Other(9)))
+        );
+        assert_eq!(u32::from(Kind::Other(9)), 9);
+        assert_eq!(u32::from(Kind::Persistent), 7);
+    }
+}
@@ -0,0 +1,666 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Memory maps
+//!
+//! A memory map is a `RegionSet`: a sorted set of non-overlapping address
+//! ranges, each of which carries a caller-defined tag (for example RAM,
+//! reserved or MMIO). Adjacent ranges with equal tags are coalesced. The set
+//! keeps its regions in a `Storage`, which is either a fixed-capacity array
+//! (`Fixed`) or, with the `alloc` feature, a `Vec`.
+//!
+//! The `e820` and `uefi` modules parse the memory maps handed over by the
+//! firmware into untyped regions. Use `Region::align_inward` to turn them
+//! into page-aligned regions before feeding them to a set.
+
+pub mod e820;
+pub mod uefi;
+
+use crate::{Address, Line, Offset};
+
+use core::marker::PhantomData;
+use core::mem::MaybeUninit;
+use core::ops::Range;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+/// An error produced when a storage has no room for more regions
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CapacityError;
+
+impl core::fmt::Display for CapacityError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "storage capacity exceeded")
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CapacityError {}
+
+/// An error produced when parsing a firmware memory map
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The input ends before the last entry
+    Truncated,
+
+    /// The number of entries exceeds the format limit
+    TooManyEntries,
+
+    /// The entry size is too small or misaligned
+    InvalidEntrySize,
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "memory map truncated"),
+            Self::TooManyEntries => write!(f, "too many memory map entries"),
+            Self::InvalidEntrySize => write!(f, "invalid memory map entry size"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// Reads a little-endian `u32` at `offset`
+#[inline]
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    let mut raw = [0; 4];
+    raw.copy_from_slice(&bytes[offset..offset + 4]);
+    u32::from_le_bytes(raw)
+}
+
+/// Reads a little-endian `u64` at `offset`
+#[inline]
+fn read_u64(bytes: &[u8], offset: usize) -> u64 {
+    let mut raw = [0; 8];
+    raw.copy_from_slice(&bytes[offset..offset + 8]);
+    u64::from_le_bytes(raw)
+}
+
+/// A sequence of items with insertion and removal
+pub trait Storage<T> {
+    /// Returns the items
+    fn as_slice(&self) -> &[T];
+
+    /// Replaces the items in `range` by `items`
+    ///
+    /// If there is not enough room, the storage is not modified.
+    fn replace(&mut self, range: Range<usize>, items: &[T]) -> Result<(), CapacityError>;
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Clone> Storage<T> for Vec<T> {
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        self
+    }
+
+    #[inline]
+    fn replace(&mut self, range: Range<usize>, items: &[T]) -> Result<(), CapacityError> {
+        self.splice(range, items.iter().cloned());
+        Ok(())
+    }
+}
+
+/// A fixed-capacity storage for up to `N` items
+pub struct Fixed<T, const N: usize> {
+    items: [MaybeUninit<T>; N],
+    len: usize,
+}
+
+impl<T: Copy, const N: usize> Clone for Fixed<T, N> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            items: self.items,
+            len: self.len,
+        }
+    }
+}
+
+impl<T: Copy, const N: usize> Default for Fixed<T, N> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Copy + core::fmt::Debug, const N: usize> core::fmt::Debug for Fixed<T, N> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.as_slice()).finish()
+    }
+}
+
+impl<T: Copy, const N: usize> Fixed<T, N> {
+    /// The maximum number of items
+    pub const CAPACITY: usize = N;
+
+    /// Creates an empty storage
+    #[inline]
+    pub fn new() -> Self {
+        Self {
+            items: [MaybeUninit::uninit(); N],
+            len: 0,
+        }
+    }
+}
+
+impl<T: Copy, const N: usize> Storage<T> for Fixed<T, N> {
+    #[inline]
+    fn as_slice(&self) -> &[T] {
+        // SAFETY: the first `len` items are always initialized.
+        unsafe { core::slice::from_raw_parts(self.items.as_ptr() as *const T, self.len) }
+    }
+
+    fn replace(&mut self, range: Range<usize>, items: &[T]) -> Result<(), CapacityError> {
+        let len = self.len - range.len() + items.len();
+        if len > N {
+            return Err(CapacityError);
+        }
+
+        let end = range.start + items.len();
+        self.items.copy_within(range.end..self.len, end);
+        for (slot, item) in self.items[range.start..end].iter_mut().zip(items) {
+            *slot = MaybeUninit::new(*item);
+        }
+
+        self.len = len;
+        Ok(())
+    }
+}
+
+/// A range of addresses with a tag
+pub struct Region<U, K> {
+    /// The addresses in the region
+    pub line: Line<u64, U>,
+
+    /// The tag of the region
+    pub tag: K,
+}
+
+impl<U, K: Clone> Clone for Region<U, K> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::new(self.line, self.tag.clone())
+    }
+}
+
+impl<U, K: Copy> Copy for Region<U, K> {}
+
+impl<U, K: core::fmt::Debug> core::fmt::Debug for Region<U, K> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Region")
+            .field("line", &self.line)
+            .field("tag", &self.tag)
+            .finish()
+    }
+}
+
+impl<U, K: PartialEq> PartialEq for Region<U, K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.line == other.line && self.tag == other.tag
+    }
+}
+
+impl<U, K: Eq> Eq for Region<U, K> {}
+
+impl<U, K> Region<U, K> {
+    /// Creates a new region
+    #[inline]
+    pub const fn new(line: Line<u64, U>, tag: K) -> Self {
+        Self { line, tag }
+    }
+
+    #[inline]
+    fn raw(start: u64, end: u64, tag: K) -> Self {
+        Self::new(
+            Line::new(Address(start, PhantomData), Address(end, PhantomData)),
+            tag,
+        )
+    }
+}
+
+impl<K> Region<(), K> {
+    /// Shrinks the region to the largest range aligned to `V`
+    ///
+    /// The start is raised and the end is lowered, so the result never
+    /// covers bytes outside of the region. Returns `None` if no aligned
+    /// item fits.
+    pub fn align_inward<V>(self) -> Option<Region<V, K>> {
+        let end = self.line.end.lower::<V>();
+        if self.line.start.0 >= end.0 {
+            return None;
+        }
+
+        let start = self.line.start.raise::<V>();
+        if start.0 >= end.0 {
+            return None;
+        }
+
+        Some(Region::new(Line::new(start, end), self.tag))
+    }
+}
+
+/// A sorted set of non-overlapping, tagged address ranges
+///
+/// Operations which would need more room than the storage has fail with
+/// `CapacityError`. Single-range operations leave the set untouched on
+/// failure; operations involving another set may fail halfway.
+pub struct RegionSet<U, K, S> {
+    storage: S,
+    phantom: PhantomData<Region<U, K>>,
+}
+
+/// A region set with a fixed capacity of `N` regions
+pub type FixedRegionSet<U, K, const N: usize> = RegionSet<U, K, Fixed<Region<U, K>, N>>;
+
+/// A region set backed by a `Vec`
+#[cfg(feature = "alloc")]
+pub type VecRegionSet<U, K> = RegionSet<U, K, Vec<Region<U, K>>>;
+
+impl<U, K, S: Clone> Clone for RegionSet<U, K, S> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            storage: self.storage.clone(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<U, K, S: Default> Default for RegionSet<U, K, S> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            storage: S::default(),
+            phantom: PhantomData,
+        }
+    }
+}
+
+impl<U, K: core::fmt::Debug, S: Storage<Region<U, K>>> core::fmt::Debug for RegionSet<U, K, S> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list().entries(self.storage.as_slice()).finish()
+    }
+}
+
+impl<U, K, S: Default> RegionSet<U, K, S> {
+    /// Creates an empty set
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<U, K: Copy + PartialEq, S: Storage<Region<U, K>>> RegionSet<U, K, S> {
+    /// Returns the regions in ascending order
+    #[inline]
+    pub fn regions(&self) -> &[Region<U, K>] {
+        self.storage.as_slice()
+    }
+
+    /// Returns an iterator over the regions in ascending order
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, Region<U, K>> {
+        self.regions().iter()
+    }
+
+    /// Returns the number of regions
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.regions().len()
+    }
+
+    /// Returns `true` if the set contains no regions
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.regions().is_empty()
+    }
+
+    /// Returns the region containing `addr`, if any
+    pub fn get(&self, addr: Address<u64, U>) -> Option<&Region<U, K>> {
+        let regions = self.regions();
+        let index = regions.partition_point(|r| r.line.end.0 <= addr.0);
+        regions.get(index).filter(|r| r.line.start.0 <= addr.0)
+    }
+
+    /// Inserts a region, replacing any overlapping parts of other regions
+    pub fn insert(&mut self, line: Line<u64, U>, tag: K) -> Result<(), CapacityError> {
+        self.apply(line.start.0, line.end.0, Some(tag))
+    }
+
+    /// Inserts regions in order, later ones replacing earlier ones
+    pub fn extend(
+        &mut self,
+        regions: impl IntoIterator<Item = Region<U, K>>,
+    ) -> Result<(), CapacityError> {
+        for region in regions {
+            self.insert(region.line, region.tag)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes all addresses in `line` from the set
+    pub fn remove(&mut self, line: Line<u64, U>) -> Result<(), CapacityError> {
+        self.apply(line.start.0, line.end.0, None)
+    }
+
+    /// Removes all addresses in `other` from the set, whatever their tags
+    pub fn subtract<L, T: Storage<Region<U, L>>>(
+        &mut self,
+        other: &RegionSet<U, L, T>,
+    ) -> Result<(), CapacityError> {
+        for region in other.storage.as_slice() {
+            self.remove(region.line)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes all addresses which are not in `other` from the set
+    ///
+    /// The remaining regions keep their tags.
+    pub fn intersect<L, T: Storage<Region<U, L>>>(
+        &mut self,
+        other: &RegionSet<U, L, T>,
+    ) -> Result<(), CapacityError> {
+        let mut start = 0;
+
+        for region in other.storage.as_slice() {
+            self.apply(start, region.line.start.0, None)?;
+            start = region.line.end.0;
+        }
+
+        self.apply(start, u64::MAX, None)
+    }
+
+    /// Finds the lowest address where `count` items aligned to `align` items fit
+    ///
+    /// Only regions whose tag is accepted by `filter` are considered. The
+    /// alignment applies to the address and must be a power of two.
+    pub fn find_first_fit(
+        &self,
+        count: Offset<u64, U>,
+        align: Offset<u64, U>,
+        filter: impl Fn(&K) -> bool,
+    ) -> Option<Address<u64, U>> {
+        let bytes = count.checked_bytes()?;
+        let align = align.checked_bytes()?;
+        if !align.is_power_of_two() {
+            return None;
+        }
+
+        self.iter()
+            .filter(|region| filter(&region.tag))
+            .find_map(|region| {
+                let start = region.line.start.0.checked_add(align - 1)? & !(align - 1);
+                match start.checked_add(bytes) {
+                    Some(end) if end <= region.line.end.0 => Some(Address(start, PhantomData)),
+                    _ => None,
+                }
+            })
+    }
+
+    /// Sets the range from `start` to `end` to `tag`, or removes it
+    fn apply(&mut self, start: u64, end: u64, tag: Option<K>) -> Result<(), CapacityError> {
+        if start >= end {
+            return Ok(());
+        }
+
+        let regions = self.storage.as_slice();
+        let mut lo = regions.partition_point(|r| r.line.end.0 <= start);
+        let mut hi = regions.partition_point(|r| r.line.start.0 < end);
+        let mut pieces = [None; 3];
+        let (mut new_start, mut new_end) = (start, end);
+
+        // Keep the parts of partially covered regions, unless they merge
+        if lo < hi && regions[lo].line.start.0 < start {
+            let r = regions[lo];
+            if tag == Some(r.tag) {
+                new_start = r.line.start.0;
+            } else {
+                pieces[0] = Some(Region::raw(r.line.start.0, start, r.tag));
+            }
+        }
+
+        if lo < hi && regions[hi - 1].line.end.0 > end {
+            let r = regions[hi - 1];
+            if tag == Some(r.tag) {
+                new_end = r.line.end.0;
+            } else {
+                pieces[2] = Some(Region::raw(end, r.line.end.0, r.tag));
+            }
+        }
+
+        // Coalesce with adjacent regions of the same tag
+        if let Some(tag) = tag {
+            if pieces[0].is_none() && lo > 0 {
+                let r = regions[lo - 1];
+                if r.line.end.0 == new_start && r.tag == tag {
+                    new_start = r.line.start.0;
+                    lo -= 1;
+                }
+            }
+
+            if pieces[2].is_none() && hi < regions.len() {
+                let r = regions[hi];
+                if r.line.start.0 == new_end && r.tag == tag {
+                    new_end = r.line.end.0;
+                    hi += 1;
+                }
+            }
+
+            pieces[1] = Some(Region::raw(new_start, new_end, tag));
+        }
+
+        let mut items = match pieces.iter().flatten().next() {
+            Some(piece) => [*piece; 3],
+            None => return self.storage.replace(lo..hi, &[]),
+        };
+
+        let mut len = 0;
+        for piece in pieces.iter().flatten() {
+            items[len] = *piece;
+            len += 1;
+        }
+
+        self.storage.replace(lo..hi, &items[..len])
+    }
+}
+
+impl<'a, U, K: Copy + PartialEq, S: Storage<Region<U, K>>> IntoIterator for &'a RegionSet<U, K, S> {
+    type Item = &'a Region<U, K>;
+    type IntoIter = core::slice::Iter<'a, Region<U, K>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Page;
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    enum Tag {
+        Ram,
+        Reserved,
+        Mmio,
+    }
+
+    type Set = FixedRegionSet<(), Tag, 8>;
+
+    fn line(start: u64, end: u64) -> Line<u64, ()> {
+        Line::new(Address::from(start), Address::from(end))
+    }
+
+    fn region(start: u64, end: u64, tag: Tag) -> Region<(), Tag> {
+        Region::new(line(start, end), tag)
+    }
+
+    #[test]
+    fn insert() {
+        let mut set = Set::new();
+        set.insert(line(0x1000, 0x2000), Tag::Ram).unwrap();
+        set.insert(line(0x3000, 0x4000), Tag::Ram).unwrap();
+        set.insert(line(0x2000, 0x3000), Tag::Ram).unwrap();
+        assert_eq!(set.regions(), [region(0x1000, 0x4000, Tag::Ram)]);
+
+        // A reserved region in the middle splits the RAM
+        set.insert(line(0x2000, 0x2800), Tag::Reserved).unwrap();
+        assert_eq!(
+            set.regions(),
+            [
+                region(0x1000, 0x2000, Tag::Ram),
+                region(0x2000, 0x2800, Tag::Reserved),
+                region(0x2800, 0x4000, Tag::Ram),
+            ]
+        );
+
+        // Overlapping insertions replace and merge
+        set.insert(line(0x1800, 0x3000), Tag::Ram).unwrap();
+        set.insert(line(0x5000, 0x6000), Tag::Mmio).unwrap();
+        set.insert(line(0x3800, 0x5800), Tag::Mmio).unwrap();
+        assert_eq!(
+            set.regions(),
+            [
+                region(0x1000, 0x3800, Tag::Ram),
+                region(0x3800, 0x6000, Tag::Mmio),
+            ]
+        );
+
+        assert_eq!(set.get(Address::from(0x37ffu64)).unwrap().tag, Tag::Ram);
+        assert_eq!(set.get(Address::from(0x3800u64)).unwrap().tag, Tag::Mmio);
+        assert!(set.get(Address::from(0x6000u64)).is_none());
+        assert!(set.get(Address::from(0x0fffu64)).is_none());
+    }
+
+    #[test]
+    fn remove() {
+        let mut set = Set::new();
+        set.insert(line(0x1000, 0x9000), Tag::Ram).unwrap();
+        set.remove(line(0x2000, 0x3000)).unwrap();
+        set.remove(line(0x8000, 0xa000)).unwrap();
+        set.remove(line(0x4000, 0x4000)).unwrap();
+        assert_eq!(
+            set.regions(),
+            [
+                region(0x1000, 0x2000, Tag::Ram),
+                region(0x3000, 0x8000, Tag::Ram),
+            ]
+        );
+
+        set.remove(line(0, 0x10000)).unwrap();
+        assert!(set.is_empty());
+    }
+
+    #[test]
+    fn subtract_intersect() {
+        // Available RAM minus reserved regions minus the kernel image
+        let mut ram = Set::new();
+        ram.insert(line(0, 0xa_0000), Tag::Ram).unwrap();
+        ram.insert(line(0x10_0000, 0x800_0000), Tag::Ram).unwrap();
+
+        let mut reserved = FixedRegionSet::<(), (), 4>::new();
+        reserved.insert(line(0, 0x1000), ()).unwrap();
+        reserved.insert(line(0x20_0000, 0x40_0000), ()).unwrap();
+
+        ram.subtract(&reserved).unwrap();
+        assert_eq!(
+            ram.regions(),
+            [
+                region(0x1000, 0xa_0000, Tag::Ram),
+                region(0x10_0000, 0x20_0000, Tag::Ram),
+                region(0x40_0000, 0x800_0000, Tag::Ram),
+            ]
+        );
+
+        let mut low = Set::new();
+        low.insert(line(0x8_0000, 0x18_0000), Tag::Mmio).unwrap();
+        ram.intersect(&low).unwrap();
+        assert_eq!(
+            ram.regions(),
+            [
+                region(0x8_0000, 0xa_0000, Tag::Ram),
+                region(0x10_0000, 0x18_0000, Tag::Ram),
+            ]
+        );
+    }
+
+    #[test]
+    fn find_first_fit() {
+        let mut set = FixedRegionSet::<Page, Tag, 4>::new();
+        let page = |n: u64| Address::from(n * 0x1000).lower::<Page>();
+
+        set.insert(Line::new(page(1), page(3)), Tag::Ram).unwrap();
+        set.insert(Line::new(page(3), page(4)), Tag::Mmio).unwrap();
+        set.insert(Line::new(page(5), page(0x300)), Tag::Ram)
+            .unwrap();
+
+        let ram = |tag: &Tag| *tag == Tag::Ram;
+        let fit = |count, align| {
+            set.find_first_fit(Offset::from_items(count), Offset::from_items(align), ram)
+        };
+
+        assert_eq!(fit(2, 1), Some(page(1)));
+        assert_eq!(fit(2, 2), Some(page(6)));
+        assert_eq!(fit(3, 1), Some(page(5)));
+        assert_eq!(fit(1, 0x200), Some(page(0x200)));
+        assert_eq!(fit(0x100, 0x200), Some(page(0x200)));
+        assert_eq!(fit(0x101, 0x200), None);
+        assert_eq!(fit(1, 3), None);
+    }
+
+    #[test]
+    fn align_inward() {
+        let inward = |start, end| region(start, end, Tag::Ram).align_inward::<Page>();
+        let page = |start: u64, end: u64| {
+            let line = Line::new(
+                Address::from(start).lower::<Page>(),
+                Address::from(end).lower::<Page>(),
+            );
+            Some(Region::new(line, Tag::Ram))
+        };
+
+        assert_eq!(inward(0x1000, 0x3000), page(0x1000, 0x3000));
+        assert_eq!(inward(0x0800, 0x9_fc00), page(0x1000, 0x9_f000));
+        assert_eq!(inward(0x9_fc00, 0xa_0000), None);
+        assert_eq!(inward(0x1001, 0x1fff), None);
+        assert_eq!(inward(u64::MAX - 0x10, u64::MAX), None);
+    }
+
+    #[test]
+    fn capacity() {
+        let mut set = FixedRegionSet::<(), Tag, 2>::new();
+        set.insert(line(0x1000, 0x4000), Tag::Ram).unwrap();
+        assert_eq!(
+            set.insert(line(0x2000, 0x3000), Tag::Reserved),
+            Err(CapacityError)
+        );
+        assert_eq!(set.regions(), [region(0x1000, 0x4000, Tag::Ram)]);
+
+        set.insert(line(0x3000, 0x4000), Tag::Reserved).unwrap();
+        assert_eq!(set.len(), 2);
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn vec() {
+        let mut set = VecRegionSet::<(), Tag>::new();
+        for i in 0..32 {
+            let tag = if i % 2 == 0 { Tag::Ram } else { Tag::Reserved };
+            set.insert(line(i * 0x1000, (i + 1) * 0x1000), tag).unwrap();
+        }
+        assert_eq!(set.len(), 32);
+
+        set.insert(line(0, 0x20000), Tag::Ram).unwrap();
+        assert_eq!(set.regions(), [region(0, 0x20000, Tag::Ram)]);
+        assert_eq!((&set).into_iter().count(), 1);
+    }
+}
@@ -0,0 +1,408 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! UEFI memory maps
+//!
+//! `GetMemoryMap()` returns an array of `EFI_MEMORY_DESCRIPTOR`s. The array
+//! stride is the descriptor size reported by the firmware, which may be
+//! larger than the structure defined by the specification.
+
+use super::{read_u32, read_u64, ParseError, Region};
+use crate::{Address, Line, Offset, Page};
+
+/// The size of an `EFI_MEMORY_DESCRIPTOR` (version 1) in bytes
+pub const DESCRIPTOR_SIZE: usize = 40;
+
+/// The size of a UEFI page in bytes
+const PAGE_SIZE: u64 = 0x1000;
+
+/// The type of a UEFI memory descriptor (`EFI_MEMORY_TYPE`)
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Kind {
+    /// Not usable (`EfiReservedMemoryType`)
+    Reserved,
+
+    /// Code of the loaded UEFI application
+    LoaderCode,
+
+    /// Data allocated by the loaded UEFI application
+    LoaderData,
+
+    /// Code of the boot services drivers
+    BootServicesCode,
+
+    /// Data allocated by the boot services drivers
+    BootServicesData,
+
+    /// Code of the runtime services drivers
+    RuntimeServicesCode,
+
+    /// Data allocated by the runtime services drivers
+    RuntimeServicesData,
+
+    /// Free memory
+    Conventional,
+
+    /// Memory with detected errors
+    Unusable,
+
+    /// ACPI tables, usable after they have been read
+    AcpiReclaim,
+
+    /// ACPI non-volatile storage
+    AcpiNvs,
+
+    /// Memory-mapped I/O
+    Mmio,
+
+    /// Memory-mapped I/O port space
+    MmioPortSpace,
+
+    /// Itanium PAL code
+    PalCode,
+
+    /// Persistent memory
+    Persistent,
+
+    /// Memory which must be accepted before use
+    Unaccepted,
+
+    /// Any other type
+    Other(u32),
+}
+
+impl Kind {
+    /// Returns `true` if the memory is free after `ExitBootServices()`
+    #[inline]
+    pub fn is_free_after_boot(&self) -> bool {
+        matches!(
+            self,
+            Self::LoaderCode
+                | Self::LoaderData
+                | Self::BootServicesCode
+                | Self::BootServicesData
+                | Self::Conventional
+        )
+    }
+}
+
+impl From<u32> for Kind {
+    #[inline]
+    fn from(value: u32) -> Self {
+        match value {
+            0 => Self::Reserved,
+            1 => Self::LoaderCode,
+            2 => Self::LoaderData,
+            3 => Self::BootServicesCode,
+            4 => Self::BootServicesData,
+            5 => Self::RuntimeServicesCode,
+            6 => Self::RuntimeServicesData,
+            7 => Self::Conventional,
+            8 => Self::Unusable,
+            9 => Self::AcpiReclaim,
+            10 => Self::AcpiNvs,
+            11 => Self::Mmio,
+            12 => Self::MmioPortSpace,
+            13 => Self::PalCode,
+            14 => Self::Persistent,
+            15 => Self::Unaccepted,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl From<Kind> for u32 {
+    #[inline]
+    fn from(value: Kind) -> Self {
+        match value {
+            Kind::Reserved => 0,
+            Kind::LoaderCode => 1,
+            Kind::LoaderData => 2,
+            Kind::BootServicesCode => 3,
+            Kind::BootServicesData => 4,
+            Kind::RuntimeServicesCode => 5,
+            Kind::RuntimeServicesData => 6,
+            Kind::Conventional => 7,
+            Kind::Unusable => 8,
+            Kind::AcpiReclaim => 9,
+            Kind::AcpiNvs => 10,
+            Kind::Mmio => 11,
+            Kind::MmioPortSpace => 12,
+            Kind::PalCode => 13,
+            Kind::Persistent => 14,
+            Kind::Unaccepted => 15,
+            Kind::Other(other) => other,
+        }
+    }
+}
+
+flags! {
+    /// The attributes of a UEFI memory descriptor
+    pub struct Attributes {
+        /// The memory supports being uncacheable (`EFI_MEMORY_UC`)
+        UC = 0;
+
+        /// The memory supports write combining (`EFI_MEMORY_WC`)
+        WC = 1;
+
+        /// The memory supports write-through caching (`EFI_MEMORY_WT`)
+        WT = 2;
+
+        /// The memory supports write-back caching (`EFI_MEMORY_WB`)
+        WB = 3;
+
+        /// The memory supports write protection (`EFI_MEMORY_WP`)
+        WP = 12;
+
+        /// The memory supports read protection (`EFI_MEMORY_RP`)
+        RP = 13;
+
+        /// The memory supports execute protection (`EFI_MEMORY_XP`)
+        XP = 14;
+
+        /// The memory is non-volatile (`EFI_MEMORY_NV`)
+        NV = 15;
+
+        /// The memory supports read-only protection (`EFI_MEMORY_RO`)
+        RO = 17;
+
+        /// The memory is specific-purpose (`EFI_MEMORY_SP`)
+        SP = 18;
+
+        /// The memory must be mapped by the OS for runtime services (`EFI_MEMORY_RUNTIME`)
+        RUNTIME = 63;
+    }
+}
+
+impl Attributes {
+    /// Creates attributes from raw bits
+    ///
+    /// Every bit is an attribute bit, so unknown bits are kept.
+    #[inline]
+    pub const fn from_bits_truncate(bits: u64) -> Self {
+        Self(bits)
+    }
+}
+
+/// A UEFI memory descriptor
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Descriptor {
+    /// The memory type
+    pub kind: Kind,
+
+    /// The first physical address
+    pub physical_start: Address<u64, Page>,
+
+    /// The first virtual address
+    pub virtual_start: Address<u64, Page>,
+
+    /// The number of 4 KiB pages
+    pub pages: Offset<u64, Page>,
+
+    /// The attributes
+    pub attributes: Attributes,
+}
+
+impl Descriptor {
+    /// Returns the physical addresses of the descriptor as a region
+    ///
+    /// The end is clamped to the top of the address space.
+    pub fn region(&self) -> Region<(), Kind> {
+        let start = self.physical_start.raw();
+        let bytes = self.pages.items().saturating_mul(PAGE_SIZE);
+        let end = start.saturating_add(bytes);
+
+        Region::new(
+            Line::new(Address::from(start), Address::from(end)),
+            self.kind,
+        )
+    }
+}
+
+/// A UEFI memory map
+///
+/// Descriptors are not necessarily sorted.
+#[derive(Copy, Clone, Debug)]
+pub struct Map<'a> {
+    bytes: &'a [u8],
+    descriptor_size: usize,
+}
+
+impl<'a> Map<'a> {
+    /// Parses a memory map with the descriptor size reported by the firmware
+    pub fn new(bytes: &'a [u8], descriptor_size: usize) -> Result<Self, ParseError> {
+        if descriptor_size < DESCRIPTOR_SIZE || descriptor_size % 8 != 0 {
+            return Err(ParseError::InvalidEntrySize);
+        }
+
+        if bytes.len() % descriptor_size != 0 {
+            return Err(ParseError::Truncated);
+        }
+
+        Ok(Self {
+            bytes,
+            descriptor_size,
+        })
+    }
+
+    /// Returns the number of descriptors
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bytes.len() / self.descriptor_size
+    }
+
+    /// Returns `true` if the map has no descriptors
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Returns an iterator over the descriptors
+    #[inline]
+    pub fn iter(&self) -> Descriptors<'a> {
+        Descriptors {
+            chunks: self.bytes.chunks_exact(self.descriptor_size),
+        }
+    }
+
+    /// Returns an iterator over the physical regions of the descriptors
+    #[inline]
+    pub fn regions(&self) -> impl Iterator<Item = Region<(), Kind>> + 'a {
+        self.iter().map(|descriptor| descriptor.region())
+    }
+}
+
+impl<'a> IntoIterator for Map<'a> {
+    type Item = Descriptor;
+    type IntoIter = Descriptors<'a>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// An iterator over the descriptors of a UEFI memory map
+#[derive(Clone, Debug)]
+pub struct Descriptors<'a> {
+    chunks: core::slice::ChunksExact<'a, u8>,
+}
+
+impl Iterator for Descriptors<'_> {
+    type Item = Descriptor;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let descriptor = self.chunks.next()?;
+
+        Some(Descriptor {
+            kind: Kind::from(read_u32(descriptor, 0)),
+            physical_start: Address::from(read_u64(descriptor, 8)).lower(),
+            virtual_start: Address::from(read_u64(descriptor, 16)).lower(),
+            pages: Offset::from_items(read_u64(descriptor, 24)),
+            attributes: Attributes::from_bits_truncate(read_u64(descriptor, 32)),
+        })
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.chunks.size_hint()
+    }
+}
+
+impl ExactSizeIterator for Descriptors<'_> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::memmap::FixedRegionSet;
+
+    /// A hand-built memory map, modelled on an OVMF guest with 2 GiB of RAM
+    const MAP: &[u8] = include_bytes!("fixtures/uefi.bin");
+
+    fn page(addr: u64) -> Address<u64, Page> {
+        Address::from(addr).lower()
+    }
+
+    #[test]
+    fn parse() {
+        let map = Map::new(MAP, 48).unwrap();
+        assert_eq!(map.len(), 14);
+
+        let descriptors = map.iter();
+        assert_eq!(descriptors.len(), 14);
+
+        let runtime = map
+            .iter()
+            .find(|descriptor| descriptor.attributes.contains(Attributes::RUNTIME))
+            .unwrap();
+        assert_eq!(
+            runtime,
+            Descriptor {
+                kind: Kind::RuntimeServicesData,
+                physical_start: page(0x7e20_0000),
+                virtual_start: page(0),
+                pages: Offset::from_items(0x100),
+                attributes: Attributes::RUNTIME
+                    | Attributes::WB
+                    | Attributes::WT
+                    | Attributes::WC
+                    | Attributes::UC,
+            }
+        );
+
+        let mmio = map.regions().last().unwrap();
+        assert_eq!(mmio.tag, Kind::Mmio);
+        assert_eq!(
+            mmio.line,
+            Line::new(Address::from(0xffc0_0000), Address::from(0x1_0000_0000))
+        );
+    }
+
+    #[test]
+    fn region_set() {
+        #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+        enum Tag {
+            Free,
+            Used,
+        }
+
+        let tag = |kind: Kind| match kind.is_free_after_boot() {
+            true => Tag::Free,
+            false => Tag::Used,
+        };
+
+        let map = Map::new(MAP, 48).unwrap();
+        let mut set = FixedRegionSet::<Page, Tag, 16>::new();
+        set.extend(
+            map.regions()
+                .map(|region| Region::new(region.line, tag(region.tag)))
+                .filter_map(Region::align_inward),
+        )
+        .unwrap();
+
+        let free = |start: u64, end: u64| Region::new(Line::new(page(start), page(end)), Tag::Free);
+        let expected = [
+            free(0, 0xa_0000),
+            free(0x10_0000, 0x80_0000),
+            free(0x80_8000, 0x81_0000),
+            free(0x90_0000, 0x7e20_0000),
+            free(0x7e31_0000, 0x7fef_0000),
+        ];
+
+        let mut regions = set.iter().filter(|region| region.tag == Tag::Free);
+        for expected in expected {
+            assert_eq!(regions.next(), Some(&expected));
+        }
+        assert_eq!(regions.next(), None);
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(Map::new(MAP, 36).unwrap_err(), ParseError::InvalidEntrySize);
+        assert_eq!(Map::new(MAP, 44).unwrap_err(), ParseError::InvalidEntrySize);
+        assert_eq!(Map::new(&MAP[..50], 48).unwrap_err(), ParseError::Truncated);
+        assert!(Map::new(&[], 48).unwrap().is_empty());
+
+        assert_eq!(u32::from(Kind::from(15)), 15);
+        assert_eq!(Kind::from(0x7000_0000), Kind::Other(0x7000_0000));
+    }
+}
@@ -0,0 +1,116 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use core::convert::TryFrom;
+
+/// Access to frames of memory by their physical address
+///
+/// This is how code which follows physical addresses, like page table
+/// walkers, reaches the memory it describes. In a kernel this may be a
+/// direct map of physical memory; in a loader or a test it is usually a
+/// buffer of pages standing in for physical memory (see `Contiguous`).
+pub trait Frames<P = Page> {
+    /// Returns the frame at the specified physical address, if accessible
+    fn frame(&self, addr: Address<u64, P>) -> Option<&P>;
+}
+
+/// Mutable access to frames of memory by their physical address
+pub trait FramesMut<P = Page>: Frames<P> {
+    /// Returns the frame at the specified physical address, if accessible
+    fn frame_mut(&mut self, addr: Address<u64, P>) -> Option<&mut P>;
+}
+
+impl<P, F: Frames<P> + ?Sized> Frames<P> for &F {
+    #[inline]
+    fn frame(&self, addr: Address<u64, P>) -> Option<&P> {
+        (**self).frame(addr)
+    }
+}
+
+impl<P, F: Frames<P> + ?Sized> Frames<P> for &mut F {
+    #[inline]
+    fn frame(&self, addr: Address<u64, P>) -> Option<&P> {
+        (**self).frame(addr)
+    }
+}
+
+impl<P, F: FramesMut<P> + ?Sized> FramesMut<P> for &mut F {
+    #[inline]
+    fn frame_mut(&mut self, addr: Address<u64, P>) -> Option<&mut P> {
+        (**self).frame_mut(addr)
+    }
+}
+
+/// A source of free frames
+///
+/// This is used by code which needs to allocate physical memory, like page
+/// table builders. Frames handed out by the source must not be in use.
+pub trait FrameSource<P = Page> {
+    /// Allocates a frame, returning its physical address
+    fn allocate(&mut self) -> Option<Address<u64, P>>;
+}
+
+impl<P, F: FrameSource<P> + ?Sized> FrameSource<P> for &mut F {
+    #[inline]
+    fn allocate(&mut self) -> Option<Address<u64, P>> {
+        (**self).allocate()
+    }
+}
+
+/// Hands out the frames in a range of addresses in ascending order
+impl<P> FrameSource<P> for Addresses<u64, P> {
+    #[inline]
+    fn allocate(&mut self) -> Option<Address<u64, P>> {
+        self.next()
+    }
+}
+
+/// A physically contiguous run of frames
+///
+/// The frames of `pages` are located at successive physical addresses
+/// starting at `base`.
+pub struct Contiguous<T, P = Page> {
+    base: Address<u64, P>,
+    pages: T,
+}
+
+impl<T, P> Contiguous<T, P> {
+    /// Places `pages` at the physical address `base`
+    #[inline]
+    pub const fn new(base: Address<u64, P>, pages: T) -> Self {
+        Self { base, pages }
+    }
+
+    /// Returns the physical address of the first frame
+    #[inline]
+    pub fn base(&self) -> Address<u64, P> {
+        self.base
+    }
+
+    /// Returns the underlying pages
+    #[inline]
+    pub fn into_inner(self) -> T {
+        self.pages
+    }
+
+    #[inline]
+    fn index(&self, addr: Address<u64, P>) -> Option<usize> {
+        let offset = addr.checked_offset_from(self.base)?;
+        usize::try_from(offset.items()).ok()
+    }
+}
+
+impl<T: AsRef<[P]>, P> Frames<P> for Contiguous<T, P> {
+    #[inline]
+    fn frame(&self, addr: Address<u64, P>) -> Option<&P> {
+        self.pages.as_ref().get(self.index(addr)?)
+    }
+}
+
+impl<T: AsRef<[P]> + AsMut<[P]>, P> FramesMut<P> for Contiguous<T, P> {
+    #[inline]
+    fn frame_mut(&mut self, addr: Address<u64, P>) -> Option<&mut P> {
+        let index = self.index(addr)?;
+        self.pages.as_mut().get_mut(index)
+    }
+}
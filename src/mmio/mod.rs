@@ -0,0 +1,159 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Memory-mapped I/O
+//!
+//! The `register_block!` macro describes a block of device registers once:
+//! the offset, width, access mode and bitfields of every register. It
+//! generates a structure which is overlaid on the device memory at an
+//! `Address<usize, Block>` and hands out `Volatile` registers, so no driver
+//! has to compute offsets by hand.
+
+mod uart;
+
+pub use uart::Uart16550;
+
+/// Defines a block of memory-mapped registers
+///
+/// The block is declared with its size in bytes, followed by its registers.
+/// Every register has an offset, a name, a width (`u8`, `u16`, `u32` or
+/// `u64`), an access mode (`ReadOnly`, `WriteOnly` or `ReadWrite`) and
+/// optionally a list of bitfields. Several registers may share an offset,
+/// for example a read-only and a write-only register.
+///
+/// ```text
+/// register_block! {
+///     /// A timer
+///     #[repr(align(16))]
+///     pub struct Timer(0x10) {
+///         /// The control register
+///         0x0 => control: u32, ReadWrite {
+///             /// The timer is running
+///             CONTROL_ENABLE = 0..1,
+///         };
+///         0x8 => count: u64, ReadOnly;
+///     }
+/// }
+/// ```
+///
+/// The generated structure has the given size and a method for every
+/// register which returns a `&Volatile<Register<width>, access>`. The
+/// bitfields become associated constants holding the range of bits. The
+/// offsets are checked for alignment and bounds at compile time. The block
+/// must be at least as aligned as its widest register, so registers wider
+/// than a byte need a `#[repr(align)]` attribute.
+///
+/// Use `Block::at()` to overlay the structure on a device.
+#[macro_export]
+macro_rules! register_block {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident($size:expr) {
+            $(
+                $(#[$rattr:meta])*
+                $offset:literal => $register:ident: $width:ident, $access:ident $({
+                    $(
+                        $(#[$fattr:meta])*
+                        $field:ident = $bits:expr
+                    ),* $(,)?
+                })?;
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        #[repr(C)]
+        $vis struct $name {
+            bytes: ::core::cell::UnsafeCell<[u8; $size]>,
+        }
+
+        #[allow(unused_qualifications)]
+        impl $name {
+            /// The size of the register block in bytes
+            pub const SIZE: usize = $size;
+
+            $($($(
+                $(#[$fattr])*
+                pub const $field: ::core::ops::Range<u32> = $bits;
+            )*)?)*
+
+            /// Overlays the register block at `addr`
+            ///
+            /// The address is aligned to the block, and so to every register.
+            ///
+            /// # Safety
+            /// The address must point to the registers of the device, or to
+            /// memory, for the lifetime `'a`. All accesses go through the
+            /// returned reference.
+            #[inline]
+            pub unsafe fn at<'a>(addr: $crate::Address<usize, Self>) -> &'a Self {
+                &*addr.as_ptr()
+            }
+
+            $(
+                $(#[$rattr])*
+                #[inline]
+                pub fn $register(
+                    &self,
+                ) -> &$crate::Volatile<$crate::Register<$width>, $crate::$access> {
+                    #[allow(clippy::int_plus_one)]
+                    const _: () = assert!(
+                        $offset % ::core::mem::size_of::<$width>() == 0
+                            && $offset + ::core::mem::size_of::<$width>() <= $size,
+                        "misaligned or out of bounds register"
+                    );
+                    const _: () = assert!(
+                        ::core::mem::align_of::<$name>() >= ::core::mem::size_of::<$width>(),
+                        "register block less aligned than its registers"
+                    );
+
+                    // SAFETY: the register is inside the block, at an offset
+                    // aligned to its width, and the block is at least as
+                    // aligned as the register.
+                    unsafe { &*(self.bytes.get().cast::<u8>().add($offset) as *const _) }
+                }
+            )*
+        }
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Address, Page, Register};
+
+    register_block! {
+        /// A test block
+        #[repr(align(16))]
+        struct Timer(0x10) {
+            /// The control register
+            0x0 => control: u32, ReadWrite {
+                /// The timer is running
+                CONTROL_ENABLE = 0..1,
+                CONTROL_MODE = 1..3,
+            };
+            0x4 => command: u32, WriteOnly;
+            0x8 => count: u64, ReadOnly;
+        }
+    }
+
+    #[test]
+    fn block() {
+        let mut page = Page::default();
+        page[8..16].copy_from_slice(&0x1234u64.to_le_bytes());
+
+        let addr = Address::from(&mut page as *mut Page as usize).lower();
+        let timer = unsafe { Timer::at(addr) };
+
+        assert_eq!(Timer::SIZE, 0x10);
+        assert_eq!(Timer::CONTROL_MODE, 1..3);
+        assert_eq!(u64::from(timer.count().read()), 0x1234);
+
+        timer
+            .control()
+            .write(Register::from(1u32 << Timer::CONTROL_ENABLE.start));
+        timer
+            .control()
+            .update(|control| Register::from(u32::from(control) | 0b100));
+        timer.command().write(Register::from(0xdead_beefu32));
+
+        assert_eq!(page[..8], [5, 0, 0, 0, 0xef, 0xbe, 0xad, 0xde]);
+    }
+}
@@ -0,0 +1,179 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Register;
+
+crate::register_block! {
+    /// The registers of a 16550 UART with a register stride of one byte
+    ///
+    /// While the divisor latch is enabled (`LCR_DLAB`), offsets 0 and 1
+    /// address the divisor latch (`dll`, `dlm`) instead.
+    pub struct Uart16550(8) {
+        /// The receiver buffer register
+        0 => rbr: u8, ReadOnly;
+
+        /// The transmitter holding register
+        0 => thr: u8, WriteOnly;
+
+        /// The divisor latch, low byte
+        0 => dll: u8, ReadWrite;
+
+        /// The divisor latch, high byte
+        1 => dlm: u8, ReadWrite;
+
+        /// The interrupt enable register
+        1 => ier: u8, ReadWrite {
+            /// Received data available
+            IER_RECEIVED = 0..1,
+
+            /// Transmitter holding register empty
+            IER_TRANSMIT = 1..2,
+
+            /// Receiver line status
+            IER_LINE_STATUS = 2..3,
+
+            /// Modem status
+            IER_MODEM_STATUS = 3..4,
+        };
+
+        /// The interrupt identification register
+        2 => iir: u8, ReadOnly {
+            /// No interrupt is pending, if set
+            IIR_NONE_PENDING = 0..1,
+
+            /// The pending interrupt
+            IIR_ID = 1..4,
+
+            /// The FIFO state
+            IIR_FIFO = 6..8,
+        };
+
+        /// The FIFO control register
+        2 => fcr: u8, WriteOnly {
+            /// Enables the FIFOs
+            FCR_ENABLE = 0..1,
+
+            /// Clears the receive FIFO
+            FCR_CLEAR_RECEIVE = 1..2,
+
+            /// Clears the transmit FIFO
+            FCR_CLEAR_TRANSMIT = 2..3,
+
+            /// The receive FIFO trigger level
+            FCR_TRIGGER = 6..8,
+        };
+
+        /// The line control register
+        3 => lcr: u8, ReadWrite {
+            /// The word length minus five
+            LCR_WORD_LENGTH = 0..2,
+
+            /// Two stop bits, if set
+            LCR_STOP_BITS = 2..3,
+
+            /// The parity mode
+            LCR_PARITY = 3..6,
+
+            /// Sends a break
+            LCR_BREAK = 6..7,
+
+            /// Enables the divisor latch
+            LCR_DLAB = 7..8,
+        };
+
+        /// The modem control register
+        4 => mcr: u8, ReadWrite {
+            /// Data terminal ready
+            MCR_DTR = 0..1,
+
+            /// Request to send
+            MCR_RTS = 1..2,
+
+            /// Enables interrupts on the PC
+            MCR_OUT2 = 3..4,
+
+            /// Loopback mode
+            MCR_LOOPBACK = 4..5,
+        };
+
+        /// The line status register
+        5 => lsr: u8, ReadOnly {
+            /// Received data is ready
+            LSR_DATA_READY = 0..1,
+
+            /// Received data was lost
+            LSR_OVERRUN = 1..2,
+
+            /// The transmitter holding register is empty
+            LSR_THR_EMPTY = 5..6,
+
+            /// The transmitter is idle
+            LSR_IDLE = 6..7,
+        };
+
+        /// The modem status register
+        6 => msr: u8, ReadOnly;
+
+        /// The scratch register
+        7 => scr: u8, ReadWrite;
+    }
+}
+
+impl Uart16550 {
+    /// Programs the baud rate divisor and 8N1 framing
+    pub fn init(&self, divisor: u16) {
+        let [low, high] = divisor.to_le_bytes();
+
+        self.ier().write(Register::from(0u8));
+        self.lcr()
+            .write(Register::from(1u8 << Self::LCR_DLAB.start));
+        self.dll().write(Register::from(low));
+        self.dlm().write(Register::from(high));
+        self.lcr()
+            .write(Register::from(0b11u8 << Self::LCR_WORD_LENGTH.start));
+        self.fcr().write(Register::from(0b111u8));
+    }
+
+    /// Writes a byte, if the transmitter is ready
+    pub fn try_send(&self, byte: u8) -> bool {
+        let ready = u8::from(self.lsr().read()) & (1 << Self::LSR_THR_EMPTY.start) != 0;
+        if ready {
+            self.thr().write(Register::from(byte));
+        }
+
+        ready
+    }
+
+    /// Reads a byte, if one was received
+    pub fn try_receive(&self) -> Option<u8> {
+        match u8::from(self.lsr().read()) & (1 << Self::LSR_DATA_READY.start) {
+            0 => None,
+            _ => Some(u8::from(self.rbr().read())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Address, Page};
+
+    #[test]
+    fn uart() {
+        let mut page = Page::default();
+        let addr = Address::from(&mut page as *mut Page as usize).lower();
+        let uart = unsafe { Uart16550::at(addr) };
+
+        uart.init(3);
+        uart.scr().write(Register::from(0x5a_u8));
+        assert!(!uart.try_send(b'a'));
+        assert_eq!(uart.try_receive(), None);
+        assert_eq!(page[..8], [3, 0, 0b111, 0b11, 0, 0, 0, 0x5a]);
+
+        // With plain memory, the holding and buffer registers are the same
+        page[5] = 0x21;
+        let uart = unsafe { Uart16550::at(addr) };
+        assert!(uart.try_send(b'a'));
+        assert_eq!(uart.try_receive(), Some(b'a'));
+        assert_eq!(page[0], b'a');
+    }
+}
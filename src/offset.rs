@@ -9,16 +9,35 @@ use core::ops::*;
 /// An offset of a number of items of type `T` from a base
 ///
 /// Note well that this is NOT stored in memory as the number of bytes,
-/// but rather the number of items.
+/// but rather the number of items. Zero-sized item types (like `()`) are
+/// treated as having a size of one byte, so an untyped offset counts bytes.
 ///
 /// One important additional feature is that offsets can be converted between
 /// underlying types so long as the conversion is lossless for the target CPU
 /// architecture. For example, `Offset<u64>` can be converted to
 /// `Offset<usize>` on 64-bit systems.
-#[derive(Copy, Clone, Default)]
 #[repr(transparent)]
 pub struct Offset<T, U>(T, PhantomData<U>);
 
+// These are implemented by hand, since the derived versions would
+// needlessly require `U` to implement the trait as well.
+
+impl<T: Clone, U> Clone for Offset<T, U> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl<T: Copy, U> Copy for Offset<T, U> {}
+
+impl<T: Default, U> Default for Offset<T, U> {
+    #[inline]
+    fn default() -> Self {
+        Self(T::default(), PhantomData)
+    }
+}
+
 impl<T: core::fmt::Debug, U> core::fmt::Debug for Offset<T, U> {
     fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         core::fmt::Debug::fmt(&self.0, f)
@@ -96,10 +115,156 @@ where
     /// Get the number of bytes
     #[inline]
     pub fn bytes(self) -> T {
-        self.0 * Offset(size_of::<U>(), PhantomData).into().items()
+        self.0 * Offset(stride::<U>(), PhantomData).into().items()
     }
 }
 
+/// The number of bytes occupied by a single item of type `U`
+///
+/// Zero-sized types are counted as a single byte.
+#[inline]
+pub(crate) const fn stride<U>() -> usize {
+    match size_of::<U>() {
+        0 => 1,
+        size => size,
+    }
+}
+
+macro_rules! implarith {
+    ($($num:ident)+) => {
+        $(
+            impl<U> Offset<$num, U> {
+                /// The size of a single item, if it can be represented
+                #[inline]
+                const fn stride() -> Option<$num> {
+                    if stride::<U>() as u128 > $num::MAX as u128 {
+                        return None;
+                    }
+
+                    Some(stride::<U>() as $num)
+                }
+
+                /// Get the number of bytes, returning `None` on overflow
+                #[inline]
+                pub const fn checked_bytes(self) -> Option<$num> {
+                    match Self::stride() {
+                        Some(stride) => self.0.checked_mul(stride),
+                        None if self.0 == 0 => Some(0),
+                        None => None,
+                    }
+                }
+
+                /// Get the number of bytes, wrapping around on overflow
+                #[inline]
+                pub const fn wrapping_bytes(self) -> $num {
+                    self.0.wrapping_mul(stride::<U>() as $num)
+                }
+
+                /// Get the number of bytes, saturating at the numeric bound on overflow
+                #[inline]
+                pub const fn saturating_bytes(self) -> $num {
+                    match self.checked_bytes() {
+                        Some(bytes) => bytes,
+                        None => $num::MAX,
+                    }
+                }
+
+                /// Get the number of bytes along with a boolean indicating overflow
+                #[inline]
+                pub const fn overflowing_bytes(self) -> ($num, bool) {
+                    (self.wrapping_bytes(), self.checked_bytes().is_none())
+                }
+
+                /// Adds two offsets, returning `None` on overflow
+                #[inline]
+                pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+                    match self.0.checked_add(rhs.0) {
+                        Some(items) => Some(Self(items, PhantomData)),
+                        None => None,
+                    }
+                }
+
+                /// Subtracts two offsets, returning `None` on overflow
+                #[inline]
+                pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+                    match self.0.checked_sub(rhs.0) {
+                        Some(items) => Some(Self(items, PhantomData)),
+                        None => None,
+                    }
+                }
+
+                /// Multiplies two offsets, returning `None` on overflow
+                #[inline]
+                pub const fn checked_mul(self, rhs: Self) -> Option<Self> {
+                    match self.0.checked_mul(rhs.0) {
+                        Some(items) => Some(Self(items, PhantomData)),
+                        None => None,
+                    }
+                }
+
+                /// Adds two offsets, wrapping around on overflow
+                #[inline]
+                pub const fn wrapping_add(self, rhs: Self) -> Self {
+                    Self(self.0.wrapping_add(rhs.0), PhantomData)
+                }
+
+                /// Subtracts two offsets, wrapping around on overflow
+                #[inline]
+                pub const fn wrapping_sub(self, rhs: Self) -> Self {
+                    Self(self.0.wrapping_sub(rhs.0), PhantomData)
+                }
+
+                /// Multiplies two offsets, wrapping around on overflow
+                #[inline]
+                pub const fn wrapping_mul(self, rhs: Self) -> Self {
+                    Self(self.0.wrapping_mul(rhs.0), PhantomData)
+                }
+
+                /// Adds two offsets, saturating at the numeric bound on overflow
+                #[inline]
+                pub const fn saturating_add(self, rhs: Self) -> Self {
+                    Self(self.0.saturating_add(rhs.0), PhantomData)
+                }
+
+                /// Subtracts two offsets, saturating at zero on overflow
+                #[inline]
+                pub const fn saturating_sub(self, rhs: Self) -> Self {
+                    Self(self.0.saturating_sub(rhs.0), PhantomData)
+                }
+
+                /// Multiplies two offsets, saturating at the numeric bound on overflow
+                #[inline]
+                pub const fn saturating_mul(self, rhs: Self) -> Self {
+                    Self(self.0.saturating_mul(rhs.0), PhantomData)
+                }
+
+                /// Adds two offsets along with a boolean indicating overflow
+                #[inline]
+                pub const fn overflowing_add(self, rhs: Self) -> (Self, bool) {
+                    let (items, overflow) = self.0.overflowing_add(rhs.0);
+                    (Self(items, PhantomData), overflow)
+                }
+
+                /// Subtracts two offsets along with a boolean indicating overflow
+                #[inline]
+                pub const fn overflowing_sub(self, rhs: Self) -> (Self, bool) {
+                    let (items, overflow) = self.0.overflowing_sub(rhs.0);
+                    (Self(items, PhantomData), overflow)
+                }
+
+                /// Multiplies two offsets along with a boolean indicating overflow
+                #[inline]
+                pub const fn overflowing_mul(self, rhs: Self) -> (Self, bool) {
+                    let (items, overflow) = self.0.overflowing_mul(rhs.0);
+                    (Self(items, PhantomData), overflow)
+                }
+            }
+        )+
+    };
+}
+
+implarith! { u8 u16 u32 u64 u128 usize }
+
 impl<T: Zero, U: Copy> Zero for Offset<T, U> {
     const ZERO: Offset<T, U> = Offset::from_items(T::ZERO);
 }
@@ -236,3 +401,54 @@ impl<T: SubAssign<T>, U> SubAssign for Offset<T, U> {
         self.0 -= rhs.0;
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn bytes() {
+        assert_eq!(Offset::<usize, u32>::from_items(3).bytes(), 12);
+        assert_eq!(Offset::<usize, ()>::from_items(3).bytes(), 3);
+        assert_eq!(Offset::<u64, Page>::from_items(2).bytes(), 8192);
+    }
+
+    #[test]
+    fn checked_bytes() {
+        let max = Offset::<u32, u64>::from_items(u32::MAX / 8);
+        assert_eq!(max.checked_bytes(), Some(u32::MAX / 8 * 8));
+
+        let over = Offset::<u32, u64>::from_items(u32::MAX / 8 + 1);
+        assert_eq!(over.checked_bytes(), None);
+        assert_eq!(over.wrapping_bytes(), 0);
+        assert_eq!(over.saturating_bytes(), u32::MAX);
+        assert_eq!(over.overflowing_bytes(), (0, true));
+
+        // An item that does not fit in the backing type at all
+        assert_eq!(Offset::<u8, Page>::from_items(0).checked_bytes(), Some(0));
+        assert_eq!(Offset::<u8, Page>::from_items(1).checked_bytes(), None);
+        assert_eq!(
+            Offset::<u8, Page>::from_items(1).overflowing_bytes(),
+            (0, true)
+        );
+    }
+
+    #[test]
+    fn arithmetic() {
+        let zero = Offset::<u8, ()>::from_items(0);
+        let one = Offset::<u8, ()>::from_items(1);
+        let max = Offset::<u8, ()>::from_items(u8::MAX);
+
+        assert_eq!(max.checked_add(one), None);
+        assert_eq!(one.checked_sub(max), None);
+        assert_eq!(max.checked_mul(one), Some(max));
+        assert_eq!(max.wrapping_add(one), zero);
+        assert_eq!(zero.wrapping_sub(one), max);
+        assert_eq!(max.saturating_add(one), max);
+        assert_eq!(one.saturating_sub(max), zero);
+        assert_eq!(max.saturating_mul(max), max);
+        assert_eq!(max.overflowing_add(one), (zero, true));
+        assert!(!max.overflowing_sub(one).1);
+        assert_eq!(max.overflowing_mul(max), (one, true));
+    }
+}
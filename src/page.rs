@@ -3,89 +3,208 @@
 use core::borrow::{Borrow, BorrowMut};
 use core::ops::{Deref, DerefMut};
 
-/// A single page of memory
-///
-/// This type is page-aligned and page-sized.
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
-#[repr(C, align(4096))]
-pub struct Page([u8; Self::SIZE]);
-
-#[cfg(feature = "const-default")]
-impl const_default::ConstDefault for Page {
-    const DEFAULT: Self = Self::zeroed();
+mod sealed {
+    pub trait Sealed {}
 }
 
-impl Default for Page {
-    #[inline]
-    fn default() -> Self {
-        Self::zeroed()
-    }
-}
+/// The size of a page type
+///
+/// This trait is implemented for all page types in this crate and allows
+/// code to be generic over the page size. It is also implemented by
+/// `Page1G`, which only describes a page size; code which accesses the
+/// bytes of a page uses `Frame` instead.
+pub trait PageSize: sealed::Sealed {
+    /// The size of the page in bytes
+    const SIZE: usize;
 
-impl Deref for Page {
-    type Target = [u8];
+    /// The base-2 logarithm of the page size
+    const SHIFT: u32;
 
-    #[inline]
-    fn deref(&self) -> &Self::Target {
-        unsafe { self.0.align_to().1 }
-    }
+    /// The mask selecting the offset of an address within the page
+    ///
+    /// This is `SIZE - 1`; the page-aligned part of an address is
+    /// therefore `addr & !MASK`.
+    const MASK: usize;
 }
 
-impl DerefMut for Page {
-    #[inline]
-    fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe { self.0.align_to_mut().1 }
-    }
-}
+/// A page type holding the bytes of a page
+///
+/// This trait is sealed, since other code relies on implementors being
+/// plain, page-sized and page-aligned bytes.
+pub trait Frame: PageSize + Copy + AsRef<[u8]> + AsMut<[u8]> {}
 
-impl AsRef<[u8]> for Page {
-    #[inline]
-    fn as_ref(&self) -> &[u8] {
-        unsafe { self.0.align_to().1 }
-    }
-}
+macro_rules! page {
+    ($($(#[$attr:meta])* $name:ident($align:literal, $shift:literal);)+) => {
+        $(
+            $(#[$attr])*
+            #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+            #[repr(C, align($align))]
+            pub struct $name([u8; Self::SIZE]);
 
-impl AsMut<[u8]> for Page {
-    #[inline]
-    fn as_mut(&mut self) -> &mut [u8] {
-        unsafe { self.0.align_to_mut().1 }
-    }
-}
+            impl sealed::Sealed for $name {}
 
-impl Borrow<[u8]> for Page {
-    #[inline]
-    fn borrow(&self) -> &[u8] {
-        unsafe { self.0.align_to().1 }
-    }
+            impl PageSize for $name {
+                const SIZE: usize = 1 << $shift;
+                const SHIFT: u32 = $shift;
+                const MASK: usize = Self::SIZE - 1;
+            }
+
+            impl Frame for $name {}
+
+            #[cfg(feature = "const-default")]
+            impl const_default::ConstDefault for $name {
+                const DEFAULT: Self = Self::zeroed();
+            }
+
+            impl Default for $name {
+                #[inline]
+                fn default() -> Self {
+                    Self::zeroed()
+                }
+            }
+
+            impl Deref for $name {
+                type Target = [u8];
+
+                #[inline]
+                fn deref(&self) -> &Self::Target {
+                    unsafe { self.0.align_to().1 }
+                }
+            }
+
+            impl DerefMut for $name {
+                #[inline]
+                fn deref_mut(&mut self) -> &mut Self::Target {
+                    unsafe { self.0.align_to_mut().1 }
+                }
+            }
+
+            impl AsRef<[u8]> for $name {
+                #[inline]
+                fn as_ref(&self) -> &[u8] {
+                    unsafe { self.0.align_to().1 }
+                }
+            }
+
+            impl AsMut<[u8]> for $name {
+                #[inline]
+                fn as_mut(&mut self) -> &mut [u8] {
+                    unsafe { self.0.align_to_mut().1 }
+                }
+            }
+
+            impl Borrow<[u8]> for $name {
+                #[inline]
+                fn borrow(&self) -> &[u8] {
+                    unsafe { self.0.align_to().1 }
+                }
+            }
+
+            impl BorrowMut<[u8]> for $name {
+                #[inline]
+                fn borrow_mut(&mut self) -> &mut [u8] {
+                    unsafe { self.0.align_to_mut().1 }
+                }
+            }
+
+            impl From<[u8; Self::SIZE]> for $name {
+                #[inline]
+                fn from(value: [u8; Self::SIZE]) -> Self {
+                    Self(value)
+                }
+            }
+
+            impl $name {
+                /// The size of the page in bytes
+                pub const SIZE: usize = 1 << $shift;
+
+                /// Creates a new page from its bytes
+                #[inline]
+                pub const fn new(value: [u8; Self::SIZE]) -> Self {
+                    Self(value)
+                }
+
+                /// Returns a page full of zeroes
+                #[inline]
+                pub const fn zeroed() -> Self {
+                    Self([0; Self::SIZE])
+                }
+            }
+        )+
+    };
 }
 
-impl BorrowMut<[u8]> for Page {
-    #[inline]
-    fn borrow_mut(&mut self) -> &mut [u8] {
-        unsafe { self.0.align_to_mut().1 }
-    }
+page! {
+    /// A single 4 KiB page of memory
+    ///
+    /// This type is page-aligned and page-sized.
+    Page4K(4096, 12);
+
+    /// A single 16 KiB page of memory
+    ///
+    /// This is the 16 KiB translation granule on aarch64. This type is
+    /// page-aligned and page-sized.
+    Page16K(16384, 14);
+
+    /// A single 64 KiB page of memory
+    ///
+    /// This is the 64 KiB translation granule on aarch64. This type is
+    /// page-aligned and page-sized.
+    Page64K(65536, 16);
+
+    /// A single 2 MiB huge page of memory
+    ///
+    /// This type is page-aligned and page-sized.
+    Page2M(2097152, 21);
 }
 
-impl From<[u8; Self::SIZE]> for Page {
-    #[inline]
-    fn from(value: [u8; Self::SIZE]) -> Self {
-        Self(value)
-    }
+/// The size of a 1 GiB huge page
+///
+/// Rust does not support alignments larger than 512 MiB, so unlike the
+/// other page types this one cannot hold the bytes of a page and has no
+/// values. It only implements `PageSize`, for code which is generic over
+/// the page size. Since it is not aligned to its size, it must not be used
+/// as the item type of an `Address`; use `Address::align_down()` instead.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Page1G {}
+
+impl sealed::Sealed for Page1G {}
+
+impl PageSize for Page1G {
+    const SIZE: usize = 1 << 30;
+    const SHIFT: u32 = 30;
+    const MASK: usize = Self::SIZE - 1;
 }
 
-impl Page {
-    /// The page size on the platform
-    pub const SIZE: usize = 4096;
+/// A single page of memory
+///
+/// This is the 4 KiB base page size on the platform.
+pub type Page = Page4K;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::mem::{align_of, size_of};
 
-    /// Creates a new page from its bytes
-    #[inline]
-    pub const fn new(value: [u8; Self::SIZE]) -> Self {
-        Self(value)
+    fn check<P: Frame>() {
+        assert_eq!(size_of::<P>(), P::SIZE);
+        assert_eq!(align_of::<P>(), P::SIZE);
+        assert_eq!(1 << P::SHIFT, P::SIZE);
+        assert_eq!(P::MASK, P::SIZE - 1);
     }
 
-    /// Returns a Page full of zeroes
-    #[inline]
-    pub const fn zeroed() -> Self {
-        Self([0; Self::SIZE])
+    #[test]
+    fn sizes() {
+        check::<Page4K>();
+        check::<Page16K>();
+        check::<Page64K>();
+        check::<Page2M>();
+
+        assert_eq!(Page::SIZE, 4096);
+        assert_eq!(Page2M::SIZE, 2 << 20);
+
+        assert_eq!(Page1G::SIZE, 1 << 30);
+        assert_eq!(1 << Page1G::SHIFT, Page1G::SIZE);
+        assert_eq!(Page1G::MASK, Page1G::SIZE - 1);
     }
 }
@@ -1,28 +1,35 @@
 #![allow(missing_docs)]
 
-use super::Page;
+use super::Frame;
 
+use super::Page;
 use core::borrow::{Borrow, BorrowMut};
+use core::marker::PhantomData;
 use core::ops::{Deref, DerefMut};
 
 /// A wrapper type around types that provide page slices
-pub struct Pages<T>(T);
+///
+/// The pages may be of any type implementing `Frame`; by default they
+/// are 4 KiB pages.
+pub struct Pages<T, P = Page>(T, PhantomData<P>);
 
-impl<T> Pages<T> {
+impl<T, P> Pages<T, P> {
     /// Wraps the specified value
     #[inline]
     pub const fn new(value: T) -> Self {
-        Self(value)
+        Self(value, PhantomData)
     }
 }
 
 #[cfg(feature = "const-default")]
-impl<const N: usize> const_default::ConstDefault for Pages<[Page; N]> {
-    const DEFAULT: Self = Self([Page::DEFAULT; N]);
+impl<P: Frame + const_default::ConstDefault, const N: usize> const_default::ConstDefault
+    for Pages<[P; N], P>
+{
+    const DEFAULT: Self = Self([P::DEFAULT; N], PhantomData);
 }
 
 #[cfg(feature = "alloc")]
-impl Pages<alloc::vec::Vec<Page>> {
+impl<P: Frame> Pages<alloc::vec::Vec<P>, P> {
     /// Copies all specified bytes into a page-aligned vector
     pub fn copy(data: &[u8]) -> Self {
         Self::copy_into(data, data.len(), 0)
@@ -38,7 +45,7 @@ impl Pages<alloc::vec::Vec<Page>> {
         let data = &data[..core::cmp::min(size, data.len())];
 
         // Allocate a buffer large enough for offset + size.
-        let count = (offset + size + Page::SIZE - 1) / Page::SIZE;
+        let count = (offset + size + P::SIZE - 1) / P::SIZE;
         let mut buf = alloc::vec::Vec::with_capacity(count);
         let bytes: &mut [u8] = unsafe {
             buf.set_len(count);
@@ -54,18 +61,18 @@ impl Pages<alloc::vec::Vec<Page>> {
         bytes.copy_from_slice(data);
         suffix.fill(0);
 
-        Self(buf)
+        Self(buf, PhantomData)
     }
 }
 
-impl<T> From<T> for Pages<T> {
+impl<T, P> From<T> for Pages<T, P> {
     fn from(value: T) -> Self {
-        Self(value)
+        Self(value, PhantomData)
     }
 }
 
-impl<T: Deref<Target = [Page]>> Deref for Pages<T> {
-    type Target = [Page];
+impl<P: Frame, T: Deref<Target = [P]>> Deref for Pages<T, P> {
+    type Target = [P];
 
     #[inline]
     fn deref(&self) -> &Self::Target {
@@ -73,65 +80,85 @@ impl<T: Deref<Target = [Page]>> Deref for Pages<T> {
     }
 }
 
-impl<T: DerefMut<Target = [Page]>> DerefMut for Pages<T> {
+impl<P: Frame, T: DerefMut<Target = [P]>> DerefMut for Pages<T, P> {
     #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
         self.0.deref_mut()
     }
 }
 
-impl<T: AsRef<[Page]>> AsRef<[Page]> for Pages<T> {
+impl<P: Frame, T: AsRef<[P]>> AsRef<[P]> for Pages<T, P> {
     #[inline]
-    fn as_ref(&self) -> &[Page] {
+    fn as_ref(&self) -> &[P] {
         self.0.as_ref()
     }
 }
 
-impl<T: AsMut<[Page]>> AsMut<[Page]> for Pages<T> {
+impl<P: Frame, T: AsMut<[P]>> AsMut<[P]> for Pages<T, P> {
     #[inline]
-    fn as_mut(&mut self) -> &mut [Page] {
+    fn as_mut(&mut self) -> &mut [P] {
         self.0.as_mut()
     }
 }
 
-impl<T: AsRef<[Page]>> AsRef<[u8]> for Pages<T> {
+impl<P: Frame, T: AsRef<[P]>> AsRef<[u8]> for Pages<T, P> {
     #[inline]
     fn as_ref(&self) -> &[u8] {
         unsafe { self.0.as_ref().align_to().1 }
     }
 }
 
-impl<T: AsMut<[Page]>> AsMut<[u8]> for Pages<T> {
+impl<P: Frame, T: AsMut<[P]>> AsMut<[u8]> for Pages<T, P> {
     #[inline]
     fn as_mut(&mut self) -> &mut [u8] {
         unsafe { self.0.as_mut().align_to_mut().1 }
     }
 }
 
-impl<T: Borrow<[Page]>> Borrow<[Page]> for Pages<T> {
+impl<P: Frame, T: Borrow<[P]>> Borrow<[P]> for Pages<T, P> {
     #[inline]
-    fn borrow(&self) -> &[Page] {
+    fn borrow(&self) -> &[P] {
         self.0.borrow()
     }
 }
 
-impl<T: BorrowMut<[Page]>> BorrowMut<[Page]> for Pages<T> {
+impl<P: Frame, T: BorrowMut<[P]>> BorrowMut<[P]> for Pages<T, P> {
     #[inline]
-    fn borrow_mut(&mut self) -> &mut [Page] {
+    fn borrow_mut(&mut self) -> &mut [P] {
         self.0.borrow_mut()
     }
 }
 
-impl<T: Borrow<[Page]>> Borrow<[u8]> for Pages<T> {
+impl<P: Frame, T: Borrow<[P]>> Borrow<[u8]> for Pages<T, P> {
     #[inline]
     fn borrow(&self) -> &[u8] {
         unsafe { self.0.borrow().align_to().1 }
     }
 }
 
-impl<T: BorrowMut<[Page]>> BorrowMut<[u8]> for Pages<T> {
+impl<P: Frame, T: BorrowMut<[P]>> BorrowMut<[u8]> for Pages<T, P> {
     #[inline]
     fn borrow_mut(&mut self) -> &mut [u8] {
         unsafe { self.0.borrow_mut().align_to_mut().1 }
     }
 }
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::*;
+    use crate::Page16K;
+
+    #[test]
+    fn copy_into() {
+        let pages: Pages<alloc::vec::Vec<Page>> = Pages::copy_into(&[1, 2, 3], 4097, 4095);
+        let bytes: &[u8] = pages.as_ref();
+        assert_eq!(pages.len(), 2);
+        assert_eq!(bytes[4094..4099], [0, 1, 2, 3, 0]);
+
+        let pages: Pages<_, Page16K> = Pages::copy(&[1, 2, 3]);
+        let bytes: &[u8] = pages.as_ref();
+        assert_eq!(pages.len(), 1);
+        assert_eq!(bytes.len(), Page16K::SIZE);
+        assert_eq!(bytes[..4], [1, 2, 3, 0]);
+    }
+}
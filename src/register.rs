@@ -1,5 +1,7 @@
 // SPDX-License-Identifier: Apache-2.0
 
+use core::ops::Range;
+
 macro_rules! implfrom {
     () => {};
 
@@ -267,7 +269,7 @@ implptr! {
 /// underlying types so long as the conversion does not truncate. For example,
 /// `Register<u64>` can be converted to `Register<usize>` on 64-bit systems.
 /// Likewise, `Register<usize>` can be converted to and from a pointer.
-#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
 #[repr(transparent)]
 pub struct Register<T>(T);
 
@@ -308,6 +310,179 @@ impl<T> Register<T> {
     }
 }
 
+macro_rules! implbits {
+    ($($t:ident)*) => {
+        $(
+            impl Register<$t> {
+                /// Returns a mask with the bits in `range` set
+                ///
+                /// The range must not be empty and must fit into the register.
+                #[inline]
+                pub fn mask(range: Range<u32>) -> $t {
+                    assert!(
+                        range.start < range.end && range.end <= $t::BITS,
+                        "invalid bit range"
+                    );
+
+                    $t::MAX >> ($t::BITS - (range.end - range.start)) << range.start
+                }
+
+                /// Returns the value of the bit at `bit`
+                #[inline]
+                pub fn get_bit(self, bit: u32) -> bool {
+                    assert!(bit < $t::BITS, "invalid bit");
+                    self.0 >> bit & 1 == 1
+                }
+
+                /// Sets the bit at `bit` to `value`
+                #[inline]
+                pub fn set_bit(&mut self, bit: u32, value: bool) -> &mut Self {
+                    assert!(bit < $t::BITS, "invalid bit");
+                    self.0 = self.0 & !(1 << bit) | (value as $t) << bit;
+                    self
+                }
+
+                /// Returns the bits in `range`, shifted down to bit zero
+                #[inline]
+                pub fn get_bits(self, range: Range<u32>) -> $t {
+                    (self.0 & Self::mask(range.clone())) >> range.start
+                }
+
+                /// Sets the bits in `range` to `value`
+                ///
+                /// The value must fit into the range.
+                #[inline]
+                pub fn set_bits(&mut self, range: Range<u32>, value: $t) -> &mut Self {
+                    let mask = Self::mask(range.clone());
+                    assert!(value <= mask >> range.start, "value does not fit");
+                    self.0 = self.0 & !mask | value << range.start;
+                    self
+                }
+
+                /// Returns the register with the bits in `range` set to `value`
+                ///
+                /// The value must fit into the range.
+                #[inline]
+                pub fn with_bits(mut self, range: Range<u32>, value: $t) -> Self {
+                    self.set_bits(range, value);
+                    self
+                }
+            }
+        )*
+    };
+}
+
+implbits! { u8 u16 u32 u64 u128 usize }
+
+/// Defines a named bitfield layout over a `Register`
+///
+/// Every field has a getter, a setter and either a single bit or a range of
+/// bits. Single bits are `bool`s; ranges are values of the register type,
+/// shifted down to bit zero.
+///
+/// ```text
+/// bitfields! {
+///     /// A control register
+///     pub struct Control(u64) {
+///         /// The device is enabled
+///         enabled, set_enabled: 0;
+///
+///         /// The operating mode
+///         mode, set_mode: 4..8;
+///     }
+/// }
+/// ```
+///
+/// The generated type wraps a `Register` and converts to and from it. Its
+/// `Debug` implementation prints the named fields.
+#[macro_export]
+macro_rules! bitfields {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident($t:ident) {
+            $(
+                $(#[$fattr:meta])*
+                $get:ident, $set:ident: $lo:literal $(.. $hi:literal)?;
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone, Default, PartialEq, Eq)]
+        #[repr(transparent)]
+        $vis struct $name($crate::Register<$t>);
+
+        impl $name {
+            /// Creates the layout from the raw bits
+            #[inline]
+            pub fn from_bits(bits: $t) -> Self {
+                Self($crate::Register::from(bits))
+            }
+
+            /// Returns the raw bits
+            #[inline]
+            pub fn bits(self) -> $t {
+                self.0.into()
+            }
+
+            $(
+                $crate::bitfields!(@field $(#[$fattr])* $get, $set, $t: $lo $(.. $hi)?);
+            )*
+        }
+
+        impl ::core::convert::From<$crate::Register<$t>> for $name {
+            #[inline]
+            fn from(value: $crate::Register<$t>) -> Self {
+                Self(value)
+            }
+        }
+
+        impl ::core::convert::From<$name> for $crate::Register<$t> {
+            #[inline]
+            fn from(value: $name) -> Self {
+                value.0
+            }
+        }
+
+        impl ::core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_struct(stringify!($name))
+                    $(.field(stringify!($get), &self.$get()))*
+                    .finish()
+            }
+        }
+    };
+
+    (@field $(#[$fattr:meta])* $get:ident, $set:ident, $t:ident: $bit:literal) => {
+        $(#[$fattr])*
+        #[inline]
+        pub fn $get(&self) -> bool {
+            self.0.get_bit($bit)
+        }
+
+        $(#[$fattr])*
+        #[inline]
+        pub fn $set(&mut self, value: bool) -> &mut Self {
+            self.0.set_bit($bit, value);
+            self
+        }
+    };
+
+    (@field $(#[$fattr:meta])* $get:ident, $set:ident, $t:ident: $lo:literal .. $hi:literal) => {
+        $(#[$fattr])*
+        #[inline]
+        pub fn $get(&self) -> $t {
+            self.0.get_bits($lo..$hi)
+        }
+
+        $(#[$fattr])*
+        #[inline]
+        pub fn $set(&mut self, value: $t) -> &mut Self {
+            self.0.set_bits($lo..$hi, value);
+            self
+        }
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::Register;
@@ -355,6 +530,38 @@ mod tests {
         assert_eq!(buf[3], 0);
     }
 
+    #[test]
+    fn bits() {
+        assert_eq!(Register::<u64>::mask(0..64), u64::MAX);
+        assert_eq!(Register::<u64>::mask(4..8), 0xf0);
+        assert_eq!(Register::<u8>::mask(7..8), 0x80);
+
+        let mut reg = Register::<u32>::from(0x8000_0001u32);
+        assert!(reg.get_bit(0));
+        assert!(!reg.get_bit(1));
+        assert!(reg.get_bit(31));
+
+        reg.set_bit(0, false).set_bit(4, true).set_bits(8..16, 0xab);
+        assert_eq!(u32::from(reg), 0x8000_ab10);
+        assert_eq!(reg.get_bits(8..12), 0xb);
+        assert_eq!(reg.get_bits(0..32), 0x8000_ab10);
+
+        let reg = reg.with_bits(28..32, 0x7);
+        assert_eq!(u32::from(reg), 0x7000_ab10);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bits_overflow() {
+        Register::<u16>::from(0u16).set_bits(0..4, 0x10);
+    }
+
+    #[test]
+    #[should_panic]
+    fn bits_range() {
+        Register::<u16>::mask(8..17);
+    }
+
     #[test]
     fn signed_from_register_usize() {
         let r = Register::<isize>::from(-1isize);
@@ -366,4 +573,31 @@ mod tests {
         let r: i32 = u.try_into().unwrap();
         assert_eq!(r, -1i32);
     }
+
+    mod shadowed {
+        /// A local `core` module must not break the generated code
+        #[allow(dead_code)]
+        mod core {}
+
+        crate::bitfields! {
+            pub struct Flags(u8) {
+                first, set_first: 0;
+                rest, set_rest: 1..8;
+            }
+        }
+
+        #[test]
+        fn bitfields() {
+            extern crate std;
+
+            let mut flags = Flags::from_bits(0x3);
+            flags.set_rest(0x7f);
+            assert!(flags.first());
+            assert_eq!(flags.bits(), 0xff);
+            assert_eq!(
+                std::format!("{:?}", flags),
+                "Flags { first: true, rest: 127 }"
+            );
+        }
+    }
 }
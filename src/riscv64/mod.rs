@@ -0,0 +1,7 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Primitives specific to the RV64 architecture
+//!
+//! These are plain data definitions and do not require running on RISC-V.
+
+pub mod paging;
@@ -0,0 +1,614 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! RISC-V page tables
+//!
+//! This module describes the Sv39, Sv48 and Sv57 page table formats and
+//! provides a software walker which translates virtual addresses through a
+//! page table hierarchy reached through the `Frames` trait.
+
+use crate::{Address, Frames, Page, PageSize, SpaceAddress, Translate, Virtual};
+
+use core::marker::PhantomData;
+use core::ops::{Index, IndexMut};
+
+/// A virtual memory system, which determines the number of levels
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Mode {
+    /// 3-level paging with 39-bit virtual addresses
+    Sv39,
+
+    /// 4-level paging with 48-bit virtual addresses
+    Sv48,
+
+    /// 5-level paging with 57-bit virtual addresses
+    Sv57,
+}
+
+impl Mode {
+    /// Returns the number of significant bits in a virtual address
+    #[inline]
+    pub const fn bits(self) -> u32 {
+        match self {
+            Self::Sv39 => 39,
+            Self::Sv48 => 48,
+            Self::Sv57 => 57,
+        }
+    }
+
+    /// Returns the value of the `MODE` field of the `satp` register
+    #[inline]
+    pub const fn satp(self) -> u64 {
+        match self {
+            Self::Sv39 => 8,
+            Self::Sv48 => 9,
+            Self::Sv57 => 10,
+        }
+    }
+
+    /// Returns `true` if the virtual address is properly sign-extended
+    #[inline]
+    pub const fn is_canonical<U>(self, addr: Address<u64, U>) -> bool {
+        let shift = 64 - self.bits();
+        ((addr.0 << shift) as i64 >> shift) as u64 == addr.0
+    }
+}
+
+flags! {
+    /// The flags of a page table entry
+    pub struct Flags {
+        /// The entry is valid
+        VALID = 0;
+
+        /// The mapped memory is readable
+        READ = 1;
+
+        /// The mapped memory is writable
+        WRITE = 2;
+
+        /// The mapped memory is executable
+        EXECUTE = 3;
+
+        /// The mapped memory is accessible from user mode
+        USER = 4;
+
+        /// The translation is global
+        GLOBAL = 5;
+
+        /// The entry has been used for a translation
+        ACCESSED = 6;
+
+        /// The mapped memory has been written to
+        DIRTY = 7;
+
+        /// The mapped memory is non-cacheable (Svpbmt)
+        NON_CACHEABLE = 61;
+
+        /// The mapped memory is I/O memory (Svpbmt)
+        IO = 62;
+
+        /// The entry is part of a naturally aligned power-of-2 range (Svnapot)
+        NAPOT = 63;
+    }
+}
+
+impl Flags {
+    /// Creates flags from raw bits, discarding the physical page number bits
+    #[inline]
+    pub const fn from_bits_truncate(bits: u64) -> Self {
+        Self(bits & !PageTableEntry::PPN_MASK)
+    }
+}
+
+/// A page table entry
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct PageTableEntry(u64);
+
+impl core::fmt::Debug for PageTableEntry {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("PageTableEntry")
+            .field(&self.address())
+            .field(&self.flags())
+            .finish()
+    }
+}
+
+impl PageTableEntry {
+    /// The mask selecting the physical page number bits of an entry
+    pub const PPN_MASK: u64 = 0x003f_ffff_ffff_fc00;
+
+    /// An unused (all zero) entry
+    pub const UNUSED: Self = Self(0);
+
+    const PPN_SHIFT: u32 = 10;
+
+    /// Creates an entry referencing `addr` with the specified flags
+    ///
+    /// The entry references a table unless any of `READ`, `WRITE` or
+    /// `EXECUTE` is set.
+    #[inline]
+    pub const fn new(addr: Address<u64, Page>, flags: Flags) -> Self {
+        let ppn = (addr.0 >> Page::SHIFT) << Self::PPN_SHIFT;
+        Self((ppn & Self::PPN_MASK) | flags.0)
+    }
+
+    /// Creates an entry from its raw value
+    #[inline]
+    pub const fn from_raw(value: u64) -> Self {
+        Self(value)
+    }
+
+    /// Returns the raw value of the entry
+    #[inline]
+    pub const fn raw(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the physical address referenced by the entry
+    #[inline]
+    pub const fn address(self) -> Address<u64, Page> {
+        let ppn = (self.0 & Self::PPN_MASK) >> Self::PPN_SHIFT;
+        Address(ppn << Page::SHIFT, PhantomData)
+    }
+
+    /// Returns the flags of the entry
+    #[inline]
+    pub const fn flags(self) -> Flags {
+        Flags::from_bits_truncate(self.0)
+    }
+
+    /// Returns the entry with its flags replaced
+    #[inline]
+    pub const fn with_flags(self, flags: Flags) -> Self {
+        Self((self.0 & Self::PPN_MASK) | flags.0)
+    }
+
+    /// Returns `true` if the entry is valid
+    #[inline]
+    pub const fn is_valid(self) -> bool {
+        self.flags().contains(Flags::VALID)
+    }
+
+    /// Returns `true` if the entry maps memory rather than referencing a table
+    #[inline]
+    pub const fn is_leaf(self) -> bool {
+        self.0 & (Flags::READ.0 | Flags::WRITE.0 | Flags::EXECUTE.0) != 0
+    }
+}
+
+/// A level in the page table hierarchy
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// Level 0, whose entries map 4 KiB pages
+    L0 = 0,
+
+    /// Level 1, whose entries may map 2 MiB megapages
+    L1 = 1,
+
+    /// Level 2, whose entries may map 1 GiB gigapages
+    L2 = 2,
+
+    /// Level 3, whose entries may map 512 GiB terapages (Sv48 and Sv57)
+    L3 = 3,
+
+    /// Level 4, whose entries may map 256 TiB petapages (Sv57)
+    L4 = 4,
+}
+
+impl Level {
+    /// Returns the top level of the hierarchy for the mode
+    #[inline]
+    pub const fn root(mode: Mode) -> Self {
+        match mode {
+            Mode::Sv39 => Self::L2,
+            Mode::Sv48 => Self::L3,
+            Mode::Sv57 => Self::L4,
+        }
+    }
+
+    /// Returns the next level down the hierarchy
+    #[inline]
+    pub const fn next(self) -> Option<Self> {
+        match self {
+            Self::L4 => Some(Self::L3),
+            Self::L3 => Some(Self::L2),
+            Self::L2 => Some(Self::L1),
+            Self::L1 => Some(Self::L0),
+            Self::L0 => None,
+        }
+    }
+
+    /// Returns the position of the lowest virtual address bit indexing this level
+    #[inline]
+    pub const fn shift(self) -> u32 {
+        12 + 9 * self as u32
+    }
+
+    /// Returns the size of the memory region covered by one entry
+    #[inline]
+    pub const fn size(self) -> u64 {
+        1 << self.shift()
+    }
+
+    /// Returns the index into a table at this level for a virtual address
+    #[inline]
+    pub const fn index<U>(self, addr: Address<u64, U>) -> usize {
+        ((addr.0 >> self.shift()) as usize) % PageTable::ENTRIES
+    }
+}
+
+/// A page table
+///
+/// A page table is page-sized and page-aligned, and may be converted to
+/// and from a `Page`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C, align(4096))]
+pub struct PageTable([PageTableEntry; PageTable::ENTRIES]);
+
+impl Default for PageTable {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PageTable {
+    /// The number of entries in a page table
+    pub const ENTRIES: usize = 512;
+
+    /// Creates a page table with all entries unused
+    #[inline]
+    pub const fn new() -> Self {
+        Self([PageTableEntry::UNUSED; Self::ENTRIES])
+    }
+
+    /// Interprets a page as a page table
+    #[inline]
+    pub fn from_page(page: &Page) -> &Self {
+        // SAFETY: both types have the same size and alignment and every
+        // bit pattern is a valid page table.
+        unsafe { &*(page as *const Page as *const Self) }
+    }
+
+    /// Interprets a page as a mutable page table
+    #[inline]
+    pub fn from_page_mut(page: &mut Page) -> &mut Self {
+        // SAFETY: both types have the same size and alignment and every
+        // bit pattern is a valid page table and a valid page.
+        unsafe { &mut *(page as *mut Page as *mut Self) }
+    }
+
+    /// Returns an iterator over the entries
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, PageTableEntry> {
+        self.0.iter()
+    }
+
+    /// Returns an iterator over the entries which allows modifying them
+    #[inline]
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, PageTableEntry> {
+        self.0.iter_mut()
+    }
+}
+
+impl Index<usize> for PageTable {
+    type Output = PageTableEntry;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for PageTable {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+impl From<Page> for PageTable {
+    #[inline]
+    fn from(value: Page) -> Self {
+        *Self::from_page(&value)
+    }
+}
+
+impl From<PageTable> for Page {
+    #[inline]
+    fn from(value: PageTable) -> Self {
+        // SAFETY: both types have the same size and every bit pattern is a
+        // valid page.
+        unsafe { core::mem::transmute(value) }
+    }
+}
+
+impl AsRef<Page> for PageTable {
+    #[inline]
+    fn as_ref(&self) -> &Page {
+        // SAFETY: both types have the same size and alignment and every
+        // bit pattern is a valid page.
+        unsafe { &*(self as *const Self as *const Page) }
+    }
+}
+
+/// The result of translating a virtual address
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Translation {
+    /// The physical address
+    pub address: Address<u64, ()>,
+
+    /// The level of the entry which mapped the address
+    ///
+    /// This determines the size of the mapped page (see `Level::size`).
+    pub level: Level,
+
+    /// The flags of the mapping entry
+    ///
+    /// Unlike on other architectures, the entries referencing tables carry
+    /// no permissions, so these are the effective flags.
+    pub flags: Flags,
+}
+
+/// An error encountered while walking the page tables
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WalkError {
+    /// The virtual address is not properly sign-extended
+    NonCanonical(Address<u64, ()>),
+
+    /// The entry at the specified level is not valid
+    NotValid(Level),
+
+    /// The entry at the specified level uses a reserved encoding
+    ///
+    /// This is the case for writable entries which are not readable and
+    /// for entries at level 0 which reference a table.
+    Reserved(Level),
+
+    /// The entry at the specified level maps a misaligned superpage
+    Misaligned(Level),
+
+    /// A page table is located at a physical address which is not accessible
+    MissingTable(Address<u64, Page>),
+}
+
+/// A software page table walker
+pub struct Walker<'a, M: ?Sized> {
+    mode: Mode,
+    root: Address<u64, Page>,
+    memory: &'a M,
+}
+
+impl<'a, M: Frames + ?Sized> Walker<'a, M> {
+    /// Creates a walker for the hierarchy with the top-level table at `root`
+    #[inline]
+    pub fn new(mode: Mode, root: Address<u64, Page>, memory: &'a M) -> Self {
+        Self { mode, root, memory }
+    }
+
+    /// Returns the entry mapping `addr` along with its level
+    pub fn entry<U>(&self, addr: Address<u64, U>) -> Result<(PageTableEntry, Level), WalkError> {
+        let addr: Address<u64, ()> = Address(addr.0, PhantomData);
+
+        if !self.mode.is_canonical(addr) {
+            return Err(WalkError::NonCanonical(addr));
+        }
+
+        let mut table = self.root;
+        let mut level = Level::root(self.mode);
+
+        loop {
+            let page = self
+                .memory
+                .frame(table)
+                .ok_or(WalkError::MissingTable(table))?;
+            let entry = PageTable::from_page(page)[level.index(addr)];
+            let flags = entry.flags();
+
+            if !entry.is_valid() {
+                return Err(WalkError::NotValid(level));
+            }
+
+            if flags.contains(Flags::WRITE) && !flags.contains(Flags::READ) {
+                return Err(WalkError::Reserved(level));
+            }
+
+            if entry.is_leaf() {
+                let mask = (level.size() - 1) >> Page::SHIFT << PageTableEntry::PPN_SHIFT;
+                if entry.0 & mask != 0 {
+                    return Err(WalkError::Misaligned(level));
+                }
+
+                return Ok((entry, level));
+            }
+
+            match level.next() {
+                Some(next) => {
+                    table = entry.address();
+                    level = next;
+                }
+
+                None => return Err(WalkError::Reserved(level)),
+            }
+        }
+    }
+
+    /// Translates a virtual address
+    pub fn walk<U>(&self, addr: Address<u64, U>) -> Result<Translation, WalkError> {
+        let (entry, level) = self.entry(addr)?;
+        let mask = level.size() - 1;
+
+        Ok(Translation {
+            address: Address(entry.address().0 | (addr.0 & mask), PhantomData),
+            level,
+            flags: entry.flags(),
+        })
+    }
+}
+
+impl<'a, M: Frames + ?Sized, D> Translate<Virtual, D, u64> for Walker<'a, M> {
+    #[inline]
+    fn translate(&self, addr: SpaceAddress<Virtual, u64, ()>) -> Option<SpaceAddress<D, u64, ()>> {
+        let translation = self.walk(addr.address()).ok()?;
+        Some(SpaceAddress::new(translation.address))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{frame, set};
+    use crate::Contiguous;
+
+    fn page(addr: u64, flags: Flags) -> PageTableEntry {
+        PageTableEntry::new(Address::from(addr).lower(), flags | Flags::VALID)
+    }
+
+    /// Builds an Sv39 hierarchy with a 4 KiB page, a megapage and a gigapage
+    fn hierarchy() -> [Page; 3] {
+        let rw = Flags::READ | Flags::WRITE;
+        let mut pages = [Page::zeroed(); 3];
+
+        // L2[1] -> L1, L2[2] -> 1 GiB at 0xc000_0000
+        set(
+            &mut pages,
+            0,
+            1,
+            PageTableEntry::new(frame(1), Flags::VALID),
+        );
+        set(&mut pages, 0, 2, page(0xc000_0000, rw));
+
+        // L1[3] -> L0, L1[4] -> 2 MiB at 0x60_0000
+        set(
+            &mut pages,
+            1,
+            3,
+            PageTableEntry::new(frame(2), Flags::VALID),
+        );
+        set(&mut pages, 1, 4, page(0x60_0000, Flags::EXECUTE));
+
+        // L0[5] -> 4 KiB at 0x7000
+        set(&mut pages, 2, 5, page(0x7000, rw | Flags::USER));
+
+        pages
+    }
+
+    fn va(l2: u64, l1: u64, l0: u64, offset: u64) -> Address<u64, ()> {
+        Address::from(l2 << 30 | l1 << 21 | l0 << 12 | offset)
+    }
+
+    #[test]
+    fn entry() {
+        let entry = page(0x12_3456_7000, Flags::READ);
+        assert_eq!(entry.raw(), 0x12_3456_7000 >> 2 | 0b11);
+        assert_eq!(entry.address().raw(), 0x12_3456_7000);
+        assert!(entry.is_leaf());
+        assert!(!PageTableEntry::new(frame(0), Flags::VALID).is_leaf());
+
+        assert_eq!(Level::root(Mode::Sv57).shift(), 48);
+        assert!(Mode::Sv39.is_canonical(Address::from(0xffff_ffc0_0000_0000u64)));
+        assert!(!Mode::Sv39.is_canonical(Address::from(0x40_0000_0000u64)));
+        assert!(Mode::Sv48.is_canonical(Address::from(0x40_0000_0000u64)));
+    }
+
+    #[test]
+    fn walk() {
+        let memory = Contiguous::new(frame(0), hierarchy());
+        let walker = Walker::new(Mode::Sv39, frame(0), &memory);
+
+        let t = walker.walk(va(1, 3, 5, 0x123)).unwrap();
+        assert_eq!(t.address.raw(), 0x7123);
+        assert_eq!(t.level, Level::L0);
+        assert_eq!(
+            t.flags,
+            Flags::VALID | Flags::READ | Flags::WRITE | Flags::USER
+        );
+
+        let t = walker.walk(va(1, 4, 5, 0x123)).unwrap();
+        assert_eq!(t.address.raw(), 0x60_5123);
+        assert_eq!(t.level, Level::L1);
+
+        let t = walker.walk(va(2, 4, 5, 0x123)).unwrap();
+        assert_eq!(t.address.raw(), 0xc080_5123);
+        assert_eq!(t.level, Level::L2);
+
+        assert_eq!(
+            walker.walk(va(1, 3, 6, 0)),
+            Err(WalkError::NotValid(Level::L0))
+        );
+        assert_eq!(
+            walker.walk(Address::from(0x40_0000_0000u64)),
+            Err(WalkError::NonCanonical(Address::from(0x40_0000_0000u64)))
+        );
+    }
+
+    #[test]
+    fn invalid() {
+        let mut pages = hierarchy();
+
+        // Write-only, a misaligned megapage and a table pointer at level 0
+        set(&mut pages, 0, 3, page(0x4000_0000, Flags::WRITE));
+        set(&mut pages, 1, 5, page(0x1000, Flags::READ));
+        set(
+            &mut pages,
+            2,
+            6,
+            PageTableEntry::new(frame(0), Flags::VALID),
+        );
+
+        let memory = Contiguous::new(frame(0), pages);
+        let walker = Walker::new(Mode::Sv39, frame(0), &memory);
+
+        assert_eq!(
+            walker.walk(va(3, 0, 0, 0)),
+            Err(WalkError::Reserved(Level::L2))
+        );
+        assert_eq!(
+            walker.walk(va(1, 5, 0, 0)),
+            Err(WalkError::Misaligned(Level::L1))
+        );
+        assert_eq!(
+            walker.walk(va(1, 3, 6, 0)),
+            Err(WalkError::Reserved(Level::L0))
+        );
+    }
+
+    #[test]
+    fn sv48() {
+        let mut pages = [Page::zeroed(); 4];
+
+        // The top of the address space: L3[0x1ff] -> L2[0x1ff] -> L1[0x1ff] -> L0
+        set(
+            &mut pages,
+            0,
+            0x1ff,
+            PageTableEntry::new(frame(1), Flags::VALID),
+        );
+        set(
+            &mut pages,
+            1,
+            0x1ff,
+            PageTableEntry::new(frame(2), Flags::VALID),
+        );
+        set(
+            &mut pages,
+            2,
+            0x1ff,
+            PageTableEntry::new(frame(3), Flags::VALID),
+        );
+        set(&mut pages, 3, 0x1ff, page(0xabc_d000, Flags::READ));
+
+        let memory = Contiguous::new(frame(0), pages);
+        let walker = Walker::new(Mode::Sv48, frame(0), &memory);
+
+        let t = walker
+            .walk(Address::from(0xffff_ffff_ffff_f008u64))
+            .unwrap();
+        assert_eq!(t.address.raw(), 0xabc_d008);
+
+        let va: crate::VirtAddr<u64, u64> =
+            crate::VirtAddr::new(Address::from(0xffff_ffff_ffff_f010u64)).lower();
+        let pa: crate::HostPhysAddr<u64, u64> = va.translate(&walker).unwrap();
+        assert_eq!(pa.raw(), 0xabc_d010);
+    }
+}
@@ -0,0 +1,342 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use core::cmp::Ordering;
+use core::marker::PhantomData;
+use core::ops::*;
+
+/// An address space
+///
+/// Types implementing this trait are used as markers to distinguish
+/// addresses which live in different address spaces. See `SpaceAddress`.
+pub trait Space {
+    /// The name of the address space
+    const NAME: &'static str;
+}
+
+/// The virtual address space
+pub enum Virtual {}
+
+impl Space for Virtual {
+    const NAME: &'static str = "Virtual";
+}
+
+/// The guest-physical address space
+pub enum GuestPhysical {}
+
+impl Space for GuestPhysical {
+    const NAME: &'static str = "GuestPhysical";
+}
+
+/// The host-physical address space
+pub enum HostPhysical {}
+
+impl Space for HostPhysical {
+    const NAME: &'static str = "HostPhysical";
+}
+
+/// A virtual address
+pub type VirtAddr<T, U> = SpaceAddress<Virtual, T, U>;
+
+/// A guest-physical address
+pub type GuestPhysAddr<T, U> = SpaceAddress<GuestPhysical, T, U>;
+
+/// A host-physical address
+pub type HostPhysAddr<T, U> = SpaceAddress<HostPhysical, T, U>;
+
+/// An address in the address space `S`
+///
+/// This type wraps an `Address` and tags it with the address space it
+/// belongs to, so that addresses from different address spaces cannot be
+/// mixed up. Apart from that, it behaves just like `Address`. The only way
+/// to move an address into another address space is to translate it
+/// explicitly (see `Translate`).
+#[repr(transparent)]
+pub struct SpaceAddress<S, T, U>(Address<T, U>, PhantomData<S>);
+
+impl<S, T: Clone, U> Clone for SpaceAddress<S, T, U> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self(self.0.clone(), PhantomData)
+    }
+}
+
+impl<S, T: Copy, U> Copy for SpaceAddress<S, T, U> {}
+
+impl<S, T: Default, U> Default for SpaceAddress<S, T, U> {
+    #[inline]
+    fn default() -> Self {
+        Self(Address::default(), PhantomData)
+    }
+}
+
+impl<S: Space, T: core::fmt::LowerHex, U> core::fmt::Debug for SpaceAddress<S, T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}{:?}", S::NAME, self.0)
+    }
+}
+
+impl<S, T: core::fmt::Display, U> core::fmt::Display for SpaceAddress<S, T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl<S, T: core::fmt::LowerHex, U> core::fmt::LowerHex for SpaceAddress<S, T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl<S, T: core::fmt::UpperHex, U> core::fmt::UpperHex for SpaceAddress<S, T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::UpperHex::fmt(&self.0, f)
+    }
+}
+
+impl<S, T: PartialEq, U> PartialEq for SpaceAddress<S, T, U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0 == other.0
+    }
+}
+
+impl<S, T: Eq, U> Eq for SpaceAddress<S, T, U> {}
+
+impl<S, T: PartialOrd, U> PartialOrd for SpaceAddress<S, T, U> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.0.partial_cmp(&other.0)
+    }
+}
+
+impl<S, T: Ord, U> Ord for SpaceAddress<S, T, U> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl<S, T: Zero, U> SpaceAddress<S, T, U> {
+    /// The NULL address
+    pub const NULL: Self = Self(Address::NULL, PhantomData);
+}
+
+impl<S, T, U> SpaceAddress<S, T, U> {
+    /// Tags an `Address` as belonging to the address space `S`
+    #[inline]
+    pub const fn new(addr: Address<T, U>) -> Self {
+        Self(addr, PhantomData)
+    }
+
+    /// Returns the untagged `Address`
+    #[inline]
+    pub fn address(self) -> Address<T, U> {
+        self.0
+    }
+
+    /// Converts the address to its raw inner type
+    #[inline]
+    pub fn raw(self) -> T {
+        self.0.raw()
+    }
+}
+
+impl<S, T, U> SpaceAddress<S, T, U>
+where
+    Offset<usize, ()>: Into<Offset<T, ()>>,
+    T: Add<T, Output = T>,
+    T: Sub<T, Output = T>,
+    T: Mul<T, Output = T>,
+    T: Div<T, Output = T>,
+    T: One,
+{
+    /// Cast the address into an address of a different type by aligning up
+    #[inline]
+    pub fn raise<V>(self) -> SpaceAddress<S, T, V> {
+        SpaceAddress(self.0.raise(), PhantomData)
+    }
+
+    /// Cast the address into an address of a different type by aligning down
+    #[inline]
+    pub fn lower<V>(self) -> SpaceAddress<S, T, V> {
+        SpaceAddress(self.0.lower(), PhantomData)
+    }
+
+    /// Translates the address into the address space `D`
+    ///
+    /// Returns `None` if the translator has no translation for the address
+    /// or if the translated address is not properly aligned for `U`.
+    #[inline]
+    pub fn translate<D, X>(self, translator: &X) -> Option<SpaceAddress<D, T, U>>
+    where
+        X: Translate<S, D, T> + ?Sized,
+        T: Copy + PartialEq,
+    {
+        let addr = translator.translate(SpaceAddress::new(Address::from(self.raw())))?;
+        let aligned = addr.lower::<U>();
+
+        if aligned.raw() != addr.raw() {
+            return None;
+        }
+
+        Some(aligned)
+    }
+}
+
+macro_rules! implarith {
+    ($($num:ident)+) => {
+        $(
+            impl<S, U> SpaceAddress<S, $num, U> {
+                /// Adds an offset, returning `None` on overflow
+                #[inline]
+                pub const fn checked_add(self, rhs: Offset<$num, U>) -> Option<Self> {
+                    match self.0.checked_add(rhs) {
+                        Some(addr) => Some(Self(addr, PhantomData)),
+                        None => None,
+                    }
+                }
+
+                /// Subtracts an offset, returning `None` on overflow
+                #[inline]
+                pub const fn checked_sub(self, rhs: Offset<$num, U>) -> Option<Self> {
+                    match self.0.checked_sub(rhs) {
+                        Some(addr) => Some(Self(addr, PhantomData)),
+                        None => None,
+                    }
+                }
+
+                /// Calculates the offset from `origin` to `self`
+                ///
+                /// Returns `None` if `origin` is above `self`.
+                #[inline]
+                pub const fn checked_offset_from(self, origin: Self) -> Option<Offset<$num, U>> {
+                    self.0.checked_offset_from(origin.0)
+                }
+            }
+        )+
+    };
+}
+
+implarith! { u32 u64 usize }
+
+impl<S, T, U> Add<Offset<T, U>> for SpaceAddress<S, T, U>
+where
+    Address<T, U>: Add<Offset<T, U>, Output = Address<T, U>>,
+{
+    type Output = Self;
+
+    #[inline]
+    fn add(self, rhs: Offset<T, U>) -> Self::Output {
+        Self(self.0 + rhs, PhantomData)
+    }
+}
+
+impl<S, T, U> AddAssign<Offset<T, U>> for SpaceAddress<S, T, U>
+where
+    Address<T, U>: AddAssign<Offset<T, U>>,
+{
+    #[inline]
+    fn add_assign(&mut self, rhs: Offset<T, U>) {
+        self.0 += rhs;
+    }
+}
+
+impl<S, T, U> Sub<SpaceAddress<S, T, U>> for SpaceAddress<S, T, U>
+where
+    Address<T, U>: Sub<Address<T, U>, Output = Offset<T, U>>,
+{
+    type Output = Offset<T, U>;
+
+    #[inline]
+    fn sub(self, rhs: SpaceAddress<S, T, U>) -> Self::Output {
+        self.0 - rhs.0
+    }
+}
+
+impl<S, T, U> Sub<Offset<T, U>> for SpaceAddress<S, T, U>
+where
+    Address<T, U>: Sub<Offset<T, U>, Output = Address<T, U>>,
+{
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, rhs: Offset<T, U>) -> Self::Output {
+        Self(self.0 - rhs, PhantomData)
+    }
+}
+
+impl<S, T, U> SubAssign<Offset<T, U>> for SpaceAddress<S, T, U>
+where
+    Address<T, U>: SubAssign<Offset<T, U>>,
+{
+    #[inline]
+    fn sub_assign(&mut self, rhs: Offset<T, U>) {
+        self.0 -= rhs;
+    }
+}
+
+/// A translation of byte addresses from the address space `S` to `D`
+///
+/// This is the only sanctioned way to move an address between address
+/// spaces. It is implemented for closures of the appropriate signature.
+pub trait Translate<S, D, T> {
+    /// Translates an address, returning `None` if it has no translation
+    fn translate(&self, addr: SpaceAddress<S, T, ()>) -> Option<SpaceAddress<D, T, ()>>;
+}
+
+impl<S, D, T, F> Translate<S, D, T> for F
+where
+    F: Fn(SpaceAddress<S, T, ()>) -> Option<SpaceAddress<D, T, ()>>,
+{
+    #[inline]
+    fn translate(&self, addr: SpaceAddress<S, T, ()>) -> Option<SpaceAddress<D, T, ()>> {
+        self(addr)
+    }
+}
+
+/// An identity translation
+///
+/// This translates every address to the same value in the other address
+/// space, for example when the guest-physical memory is identity mapped.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Identity;
+
+impl<S, D, T> Translate<S, D, T> for Identity {
+    #[inline]
+    fn translate(&self, addr: SpaceAddress<S, T, ()>) -> Option<SpaceAddress<D, T, ()>> {
+        Some(SpaceAddress::new(addr.0))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn arithmetic() {
+        let va: VirtAddr<u64, Page> = VirtAddr::new(Address::from(0x1234u64)).raise();
+        assert_eq!(va.raw(), 0x2000);
+        assert_eq!((va + Offset::from_items(2)).raw(), 0x4000);
+        assert_eq!((va - Offset::from_items(1)).raw(), 0x1000);
+        assert_eq!(va - VirtAddr::NULL, Offset::from_items(2));
+        assert_eq!(va.checked_sub(Offset::from_items(3)), None);
+        assert_eq!(va.lower::<()>().raw(), 0x2000);
+    }
+
+    #[test]
+    fn translate() {
+        let va: VirtAddr<u64, Page> = VirtAddr::new(Address::from(0x2000u64)).raise();
+
+        let gpa: GuestPhysAddr<u64, Page> = va.translate(&Identity).unwrap();
+        assert_eq!(gpa.raw(), 0x2000);
+
+        let shift = |addr: GuestPhysAddr<u64, ()>| {
+            Some(HostPhysAddr::new(Address::from(addr.raw() + 0x10_0000)))
+        };
+        let hpa = gpa.translate(&shift).unwrap();
+        assert_eq!(hpa.raw(), 0x10_2000);
+
+        // Translations which break the alignment are rejected
+        let skew =
+            |addr: GuestPhysAddr<u64, ()>| Some(HostPhysAddr::new(Address::from(addr.raw() + 1)));
+        assert_eq!(gpa.translate(&skew), None);
+    }
+}
@@ -0,0 +1,262 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::*;
+use core::ops::{Add, Sub};
+
+/// A region of memory described by its start address and item count
+///
+/// A `Span` describes the same region as the `Line` with bounds
+/// `start..start + count`. Unlike a `Line`, a `Span` can also describe a
+/// region which ends at the top of the address space, so only the spans
+/// whose end can be represented convert to a `Line`.
+pub struct Span<T, U> {
+    /// The first address in the region
+    pub start: Address<T, U>,
+
+    /// The number of items in the region
+    pub count: Offset<T, U>,
+}
+
+impl<T: Clone, U> Clone for Span<T, U> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self::new(self.start.clone(), self.count.clone())
+    }
+}
+
+impl<T: Copy, U> Copy for Span<T, U> {}
+
+impl<T: core::fmt::LowerHex + core::fmt::Debug, U> core::fmt::Debug for Span<T, U> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Span")
+            .field("start", &self.start)
+            .field("count", &self.count)
+            .finish()
+    }
+}
+
+impl<T: PartialEq, U> PartialEq for Span<T, U> {
+    fn eq(&self, other: &Self) -> bool {
+        self.start == other.start && self.count == other.count
+    }
+}
+
+impl<T: Eq, U> Eq for Span<T, U> {}
+
+impl<T, U> Span<T, U> {
+    /// Creates a new span from its start address and item count
+    #[inline]
+    pub const fn new(start: Address<T, U>, count: Offset<T, U>) -> Self {
+        Self { start, count }
+    }
+}
+
+impl<T: Copy + Ord + Zero, U> Span<T, U>
+where
+    Address<T, U>: Add<Offset<T, U>, Output = Address<T, U>>,
+    Address<T, U>: Sub<Output = Offset<T, U>>,
+    Offset<T, U>: Sub<Output = Offset<T, U>>,
+{
+    /// Returns `true` if the span contains no items
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.count <= Offset::from_items(T::ZERO)
+    }
+
+    /// Returns the first address past the end of the span
+    ///
+    /// The end of a span which reaches the top of the address space cannot
+    /// be represented, so this overflows. Use `checked_end()` for spans
+    /// which may do so.
+    #[inline]
+    pub fn end(&self) -> Address<T, U> {
+        self.start + self.count
+    }
+
+    /// Returns `true` if `addr` lies within the span
+    #[inline]
+    pub fn contains(&self, addr: &Address<T, U>) -> bool {
+        *addr >= self.start && *addr - self.start < self.count
+    }
+
+    /// Returns `true` if the two spans share at least one address
+    #[inline]
+    pub fn overlaps(&self, other: &Self) -> bool {
+        self.intersection(other).is_some()
+    }
+
+    /// Returns the span of addresses common to both spans, if any
+    #[inline]
+    pub fn intersection(&self, other: &Self) -> Option<Self> {
+        let start = self.start.max(other.start);
+        let count = self.rest(start).min(other.rest(start));
+        let span = Self::new(start, count);
+
+        if span.is_empty() {
+            return None;
+        }
+
+        Some(span)
+    }
+
+    /// Splits the span in two at the specified address
+    ///
+    /// Returns `None` if `at` lies outside of `start..=end()`.
+    #[inline]
+    pub fn split_at(self, at: Address<T, U>) -> Option<(Self, Self)> {
+        if at < self.start || at - self.start > self.count {
+            return None;
+        }
+
+        let count = at - self.start;
+        Some((
+            Self::new(self.start, count),
+            Self::new(at, self.count - count),
+        ))
+    }
+
+    /// Returns an iterator over each address in the span
+    #[inline]
+    pub fn iter(&self) -> Addresses<T, U> {
+        Addresses(*self)
+    }
+
+    /// Returns the number of items in the span from `at` onwards
+    ///
+    /// The address must not be below the start of the span.
+    #[inline]
+    fn rest(&self, at: Address<T, U>) -> Offset<T, U> {
+        let skipped = at - self.start;
+
+        if skipped >= self.count {
+            return Offset::from_items(T::ZERO);
+        }
+
+        self.count - skipped
+    }
+}
+
+macro_rules! implend {
+    ($($num:ident)+) => {
+        $(
+            impl<U> Span<$num, U> {
+                /// Returns the first address past the end of the span
+                ///
+                /// Returns `None` if the span reaches the top of the address
+                /// space, or past it.
+                #[inline]
+                pub const fn checked_end(&self) -> Option<Address<$num, U>> {
+                    self.start.checked_add(self.count)
+                }
+            }
+
+            impl<U> TryFrom<Span<$num, U>> for Line<$num, U> {
+                type Error = AddressError<$num>;
+
+                #[inline]
+                fn try_from(value: Span<$num, U>) -> Result<Self, Self::Error> {
+                    match value.checked_end() {
+                        Some(end) => Ok(Self::new(value.start, end)),
+                        None => Err(AddressError::Overflow),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+implend! { u32 u64 usize }
+
+impl<T: Copy + Ord + Zero + One, U> IntoIterator for Span<T, U>
+where
+    Address<T, U>: Add<Offset<T, U>, Output = Address<T, U>>,
+    Address<T, U>: Sub<Output = Offset<T, U>>,
+    Offset<T, U>: Sub<Output = Offset<T, U>>,
+{
+    type Item = Address<T, U>;
+    type IntoIter = Addresses<T, U>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<T: Copy + Ord + Zero, U> From<Line<T, U>> for Span<T, U>
+where
+    Address<T, U>: Sub<Output = Offset<T, U>>,
+{
+    #[inline]
+    fn from(value: Line<T, U>) -> Self {
+        Self::new(value.start, value.count())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn span(start: u64, count: u64) -> Span<u64, Page> {
+        Span::new(Address::from(start).raise(), Offset::from_items(count))
+    }
+
+    #[test]
+    fn line() {
+        let s = span(0x1000, 3);
+        assert_eq!(s.end().raw(), 0x4000);
+        assert_eq!(Span::from(Line::try_from(s).unwrap()), s);
+        assert!(span(0x1000, 0).is_empty());
+    }
+
+    #[test]
+    fn top() {
+        // The last page of a 32-bit address space
+        let last: Span<u32, Page> = Span::new(
+            Address::<u32, Page>::try_new(0xffff_f000).unwrap(),
+            Offset::from_items(1),
+        );
+        assert_eq!(last.checked_end(), None);
+        assert_eq!(Line::try_from(last), Err(AddressError::Overflow));
+
+        let s = span(0xffff_ffff_ffff_0000, 0x10);
+        let below = span(0xffff_ffff_fffe_0000, 0x11);
+        assert_eq!(s.checked_end(), None);
+        assert!(s.contains(&Address::from(0xffff_ffff_ffff_f000u64).lower()));
+        assert!(!s.contains(&Address::from(0xffff_ffff_fffe_f000u64).lower()));
+        assert_eq!(
+            below.checked_end(),
+            Some(Address::from(0xffff_ffff_ffff_1000u64).lower())
+        );
+        assert!(s.overlaps(&below));
+        assert_eq!(s.intersection(&below), Some(span(0xffff_ffff_ffff_0000, 1)));
+        assert_eq!(s.intersection(&s), Some(s));
+        assert_eq!(
+            s.split_at(Address::from(0xffff_ffff_ffff_8000u64).lower()),
+            Some((
+                span(0xffff_ffff_ffff_0000, 8),
+                span(0xffff_ffff_ffff_8000, 8)
+            ))
+        );
+        assert_eq!(s.iter().count(), 0x10);
+        assert_eq!(
+            s.iter().last(),
+            Some(Address::from(0xffff_ffff_ffff_f000u64).lower())
+        );
+    }
+
+    #[test]
+    fn operations() {
+        let a = span(0x1000, 4);
+        let b = span(0x3000, 4);
+
+        assert!(a.contains(&Address::from(0x4000u64).lower()));
+        assert!(!a.contains(&Address::from(0x5000u64).lower()));
+        assert!(a.overlaps(&b));
+        assert_eq!(a.intersection(&b), Some(span(0x3000, 2)));
+        assert_eq!(
+            a.split_at(Address::from(0x2000u64).lower()),
+            Some((span(0x1000, 1), span(0x2000, 3)))
+        );
+        assert_eq!(a.iter().count(), 4);
+    }
+}
@@ -0,0 +1,52 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Fixtures shared by the page table tests of all architectures
+//!
+//! The tests build their hierarchies in a plain buffer of pages which is
+//! placed at `BASE`, with the tables identified by their index in it.
+
+use crate::{Address, Frame, PageSize};
+
+use core::mem::size_of;
+
+/// The physical address of the first page of a test hierarchy
+pub const BASE: u64 = 0x10_0000;
+
+/// A page table entry, as stored in a table
+pub trait Entry: Copy {
+    /// Returns the raw value of the entry
+    fn raw(self) -> u64;
+}
+
+impl Entry for crate::aarch64::paging::Descriptor {
+    #[inline]
+    fn raw(self) -> u64 {
+        self.raw()
+    }
+}
+
+impl Entry for crate::riscv64::paging::PageTableEntry {
+    #[inline]
+    fn raw(self) -> u64 {
+        self.raw()
+    }
+}
+
+impl Entry for crate::x86_64::paging::PageTableEntry {
+    #[inline]
+    fn raw(self) -> u64 {
+        self.raw()
+    }
+}
+
+/// Returns the address of the page at `index` in a test hierarchy
+pub fn frame<P: PageSize>(index: u64) -> Address<u64, P> {
+    Address::from(BASE + index * P::SIZE as u64).lower()
+}
+
+/// Stores `entry` at `index` in the table at `table` in a test hierarchy
+pub fn set<P: Frame, E: Entry>(pages: &mut [P], table: u64, index: usize, entry: E) {
+    let offset = index * size_of::<u64>();
+    let bytes = pages[table as usize].as_mut();
+    bytes[offset..][..size_of::<u64>()].copy_from_slice(&entry.raw().to_ne_bytes());
+}
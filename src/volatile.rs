@@ -0,0 +1,392 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use core::cell::UnsafeCell;
+use core::marker::PhantomData;
+use core::mem::{align_of, size_of};
+use core::ptr::NonNull;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// The access mode of a volatile location
+///
+/// This trait is sealed; the modes are `ReadOnly`, `WriteOnly` and
+/// `ReadWrite`.
+pub trait Access: sealed::Sealed {}
+
+/// An access mode which allows reads
+pub trait Readable: Access {}
+
+/// An access mode which allows writes
+pub trait Writable: Access {}
+
+/// Reads are allowed, writes are not
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ReadOnly(());
+
+/// Writes are allowed, reads are not
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct WriteOnly(());
+
+/// Both reads and writes are allowed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ReadWrite(());
+
+impl sealed::Sealed for ReadOnly {}
+impl sealed::Sealed for WriteOnly {}
+impl sealed::Sealed for ReadWrite {}
+
+impl Access for ReadOnly {}
+impl Access for WriteOnly {}
+impl Access for ReadWrite {}
+
+impl Readable for ReadOnly {}
+impl Readable for ReadWrite {}
+impl Writable for WriteOnly {}
+impl Writable for ReadWrite {}
+
+/// A value which is only ever accessed with volatile loads and stores
+///
+/// This type is meant to be used as a field of `#[repr(C)]` structures
+/// which are overlaid on device registers or memory shared with another
+/// party, such as the GHCB. It has the same layout as `U`.
+#[repr(transparent)]
+pub struct Volatile<U, A = ReadWrite> {
+    value: UnsafeCell<U>,
+    access: PhantomData<A>,
+}
+
+impl<U, A> Volatile<U, A> {
+    /// Creates a new volatile value
+    #[inline]
+    pub const fn new(value: U) -> Self {
+        Self {
+            value: UnsafeCell::new(value),
+            access: PhantomData,
+        }
+    }
+
+    /// Returns a raw pointer to the value
+    #[inline]
+    pub const fn as_ptr(&self) -> *mut U {
+        self.value.get()
+    }
+}
+
+impl<U: Copy, A: Readable> Volatile<U, A> {
+    /// Reads the value
+    #[inline]
+    pub fn read(&self) -> U {
+        // SAFETY: the cell is valid for reads for the lifetime of `self`.
+        unsafe { self.value.get().read_volatile() }
+    }
+}
+
+impl<U: Copy, A: Writable> Volatile<U, A> {
+    /// Writes the value
+    #[inline]
+    pub fn write(&self, value: U) {
+        // SAFETY: the cell is valid for writes for the lifetime of `self`.
+        unsafe { self.value.get().write_volatile(value) }
+    }
+}
+
+impl<U: Copy, A: Readable + Writable> Volatile<U, A> {
+    /// Replaces the value by `f` applied to it
+    #[inline]
+    pub fn update(&self, f: impl FnOnce(U) -> U) {
+        self.write(f(self.read()))
+    }
+}
+
+impl<U: Copy + Default, A> Default for Volatile<U, A> {
+    #[inline]
+    fn default() -> Self {
+        Self::new(U::default())
+    }
+}
+
+impl<U: Copy + core::fmt::Debug, A: Readable> core::fmt::Debug for Volatile<U, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("Volatile").field(&self.read()).finish()
+    }
+}
+
+/// A reference to a value which is only accessed with volatile loads and stores
+///
+/// Unlike `&mut U`, this never allows the compiler to merge, reorder or
+/// elide the accesses. Use `volatile_field!` to project it onto the fields
+/// of a `#[repr(C)]` structure.
+pub struct VolatileRef<'a, U, A = ReadWrite> {
+    ptr: NonNull<U>,
+    phantom: PhantomData<(&'a mut U, A)>,
+}
+
+impl<U, A> core::fmt::Debug for VolatileRef<'_, U, A> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("VolatileRef").field(&self.ptr).finish()
+    }
+}
+
+impl<'a, U> VolatileRef<'a, U, ReadOnly> {
+    /// Creates a read-only volatile reference from a shared reference
+    #[inline]
+    pub fn from_ref(value: &'a U) -> Self {
+        // SAFETY: a shared reference is valid for reads.
+        unsafe { Self::new(NonNull::from(value)) }
+    }
+}
+
+impl<'a, U> VolatileRef<'a, U> {
+    /// Creates a volatile reference from a mutable reference
+    #[inline]
+    pub fn from_mut(value: &'a mut U) -> Self {
+        // SAFETY: a mutable reference is valid for reads and writes.
+        unsafe { Self::new(NonNull::from(value)) }
+    }
+}
+
+impl<'a, U, A: Access> VolatileRef<'a, U, A> {
+    /// Creates a volatile reference from a raw pointer
+    ///
+    /// # Safety
+    /// The pointer must be aligned and valid for the accesses allowed by
+    /// `A` for the lifetime `'a`.
+    #[inline]
+    pub unsafe fn new(ptr: NonNull<U>) -> Self {
+        Self {
+            ptr,
+            phantom: PhantomData,
+        }
+    }
+
+    /// Returns the raw pointer
+    #[inline]
+    pub fn as_ptr(&self) -> NonNull<U> {
+        self.ptr
+    }
+
+    /// Reborrows the reference for a shorter lifetime
+    #[inline]
+    pub fn borrow_mut(&mut self) -> VolatileRef<'_, U, A> {
+        // SAFETY: the pointer is valid for the borrow of `self`.
+        unsafe { VolatileRef::new(self.ptr) }
+    }
+
+    /// Converts the reference into a read-only reference
+    #[inline]
+    pub fn read_only(self) -> VolatileRef<'a, U, ReadOnly>
+    where
+        A: Readable,
+    {
+        // SAFETY: the access mode only becomes more restrictive.
+        unsafe { VolatileRef::new(self.ptr) }
+    }
+
+    /// Converts the reference into a write-only reference
+    #[inline]
+    pub fn write_only(self) -> VolatileRef<'a, U, WriteOnly>
+    where
+        A: Writable,
+    {
+        // SAFETY: the access mode only becomes more restrictive.
+        unsafe { VolatileRef::new(self.ptr) }
+    }
+
+    /// Projects the reference onto a part of the value
+    ///
+    /// Prefer `volatile_field!`, which only projects onto fields.
+    ///
+    /// # Safety
+    /// `f` must return a pointer into the value it is given.
+    #[inline]
+    pub unsafe fn map<V>(self, f: impl FnOnce(NonNull<U>) -> NonNull<V>) -> VolatileRef<'a, V, A> {
+        VolatileRef::new(f(self.ptr))
+    }
+}
+
+impl<'a, U, A: Access> VolatileRef<'a, U, A> {
+    /// Projects the reference onto a field, for `volatile_field!`
+    ///
+    /// This panics if the field is not inside the value or not aligned,
+    /// such as a field of a packed structure.
+    ///
+    /// # Safety
+    /// `field` must be derived from the pointer of this reference.
+    #[doc(hidden)]
+    #[inline]
+    pub unsafe fn project_field<V>(self, field: *mut V) -> VolatileRef<'a, V, A> {
+        let start = self.ptr.as_ptr() as usize;
+        let address = field as usize;
+
+        assert!(
+            address >= start && address + size_of::<V>() <= start + size_of::<U>(),
+            "the field is outside of the value"
+        );
+        assert!(address % align_of::<V>() == 0, "the field is not aligned");
+
+        VolatileRef::new(NonNull::new_unchecked(field))
+    }
+}
+
+impl<U, A: Readable> VolatileRef<'_, U, A> {
+    /// Borrows the reference as a read-only reference
+    #[inline]
+    pub fn borrow(&self) -> VolatileRef<'_, U, ReadOnly> {
+        // SAFETY: the pointer is valid for reads for the borrow of `self`.
+        unsafe { VolatileRef::new(self.ptr) }
+    }
+}
+
+impl<U: Copy, A: Readable> VolatileRef<'_, U, A> {
+    /// Reads the value
+    #[inline]
+    pub fn read(&self) -> U {
+        // SAFETY: the pointer is valid for reads.
+        unsafe { self.ptr.as_ptr().read_volatile() }
+    }
+}
+
+impl<U: Copy, A: Writable> VolatileRef<'_, U, A> {
+    /// Writes the value
+    #[inline]
+    pub fn write(&mut self, value: U) {
+        // SAFETY: the pointer is valid for writes.
+        unsafe { self.ptr.as_ptr().write_volatile(value) }
+    }
+}
+
+impl<U: Copy, A: Readable + Writable> VolatileRef<'_, U, A> {
+    /// Replaces the value by `f` applied to it
+    #[inline]
+    pub fn update(&mut self, f: impl FnOnce(U) -> U) {
+        let value = f(self.read());
+        self.write(value)
+    }
+}
+
+impl<'a, T, A: Access, const N: usize> VolatileRef<'a, [T; N], A> {
+    /// Projects the reference onto the element at `index`, if it exists
+    #[inline]
+    pub fn index(self, index: usize) -> Option<VolatileRef<'a, T, A>> {
+        if index >= N {
+            return None;
+        }
+
+        // SAFETY: the element is inside the array.
+        unsafe { Some(self.map(|ptr| NonNull::new_unchecked((ptr.as_ptr() as *mut T).add(index)))) }
+    }
+}
+
+/// Projects a `VolatileRef` onto a field of a `#[repr(C)]` structure
+///
+/// The reference is consumed; use `borrow()` or `borrow_mut()` first to
+/// keep it. The access mode carries over to the field.
+///
+/// The field must be aligned: projecting onto a field of a packed
+/// structure, or onto a field reached through `Deref`, panics.
+///
+/// For example, `volatile_field!(ghcb.borrow_mut(), sw_exit_code).write(0x72)`
+/// writes only the `sw_exit_code` field of a GHCB.
+#[macro_export]
+macro_rules! volatile_field {
+    ($ref:expr, $field:ident) => {{
+        let reference = $ref;
+        let ptr = reference.as_ptr().as_ptr();
+
+        // SAFETY: the place is only used to compute the address of the field,
+        // which is checked before the reference is created.
+        #[allow(unused_qualifications)]
+        let field = unsafe {
+            let field = ::core::ptr::addr_of_mut!((*ptr).$field);
+            reference.project_field(field)
+        };
+
+        field
+    }};
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[repr(C)]
+    struct Block {
+        id: Volatile<u32, ReadOnly>,
+        command: Volatile<u32, WriteOnly>,
+        data: Volatile<u64>,
+    }
+
+    #[repr(C)]
+    #[derive(Copy, Clone, Debug, Default, PartialEq)]
+    struct Shared {
+        flags: u32,
+        words: [u64; 4],
+    }
+
+    #[test]
+    fn volatile() {
+        let block = Block {
+            id: Volatile::new(7),
+            command: Volatile::new(0),
+            data: Volatile::default(),
+        };
+
+        assert_eq!(block.id.read(), 7);
+        block.command.write(3);
+        block.data.update(|data| data + 0x10);
+        block.data.update(|data| data * 2);
+        assert_eq!(block.data.read(), 0x20);
+        assert_eq!(unsafe { *block.command.as_ptr() }, 3);
+        assert_eq!(size_of::<Block>(), 16);
+    }
+
+    #[test]
+    fn volatile_ref() {
+        let mut shared = Shared::default();
+        let mut all = VolatileRef::from_mut(&mut shared);
+
+        volatile_field!(all.borrow_mut(), flags).write(1);
+        volatile_field!(all.borrow_mut(), words)
+            .index(2)
+            .unwrap()
+            .write(0xdead);
+        assert!(volatile_field!(all.borrow_mut(), words).index(4).is_none());
+
+        let mut flags = volatile_field!(all.borrow_mut(), flags);
+        flags.update(|flags| flags | 4);
+        assert_eq!(flags.read(), 5);
+
+        let words = volatile_field!(all.borrow(), words);
+        assert_eq!(words.read(), [0, 0, 0xdead, 0]);
+
+        let mut copy = all.read();
+        copy.words[0] = 1;
+        all.write(copy);
+        assert_eq!(shared.words, [1, 0, 0xdead, 0]);
+
+        let shared = Shared::default();
+        let read_only = VolatileRef::from_ref(&shared);
+        assert_eq!(volatile_field!(read_only, flags).read(), 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "the field is not aligned")]
+    fn volatile_ref_packed() {
+        #[repr(C, packed)]
+        #[derive(Default)]
+        struct Packed {
+            tag: u8,
+            value: u32,
+        }
+
+        #[repr(C, align(8))]
+        #[derive(Default)]
+        struct Aligned(Packed);
+
+        let mut aligned = Aligned::default();
+        let all = VolatileRef::from_mut(&mut aligned.0);
+        volatile_field!(all, value).write(1);
+    }
+}
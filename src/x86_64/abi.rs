@@ -0,0 +1,108 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The System V calling conventions
+//!
+//! `SyscallRegs` holds the registers of a Linux system call and `CallRegs`
+//! the integer registers of a function call. Use `Arguments` to pack typed
+//! arguments into them.
+
+use crate::{Errno, Register};
+
+/// The registers of a system call
+///
+/// The number is passed in `rax` and the arguments in `rdi`, `rsi`, `rdx`,
+/// `r10`, `r8` and `r9`. The result is returned in `rax`, with `rdx` as the
+/// second return value of a few system calls.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct SyscallRegs {
+    /// The system call number (`rax`)
+    pub number: Register<usize>,
+
+    /// The arguments (`rdi`, `rsi`, `rdx`, `r10`, `r8`, `r9`)
+    pub args: [Register<usize>; 6],
+
+    /// The return values (`rax`, `rdx`)
+    pub ret: [Register<usize>; 2],
+}
+
+impl SyscallRegs {
+    /// Creates the registers of a system call
+    #[inline]
+    pub fn new(number: impl Into<Register<usize>>, args: impl Into<[Register<usize>; 6]>) -> Self {
+        Self {
+            number: number.into(),
+            args: args.into(),
+            ret: Default::default(),
+        }
+    }
+
+    /// Decodes the first return value
+    #[inline]
+    pub fn result(&self) -> Result<Register<usize>, Errno> {
+        Errno::decode(self.ret[0])
+    }
+
+    /// Encodes a result into the first return value
+    #[inline]
+    pub fn set_result(&mut self, result: Result<Register<usize>, Errno>) {
+        self.ret[0] = Errno::encode(result);
+    }
+}
+
+/// The integer registers of a function call
+///
+/// The arguments are passed in `rdi`, `rsi`, `rdx`, `rcx`, `r8` and `r9`
+/// and the result is returned in `rax` and `rdx`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct CallRegs {
+    /// The arguments (`rdi`, `rsi`, `rdx`, `rcx`, `r8`, `r9`)
+    pub args: [Register<usize>; 6],
+
+    /// The return values (`rax`, `rdx`)
+    pub ret: [Register<usize>; 2],
+}
+
+impl CallRegs {
+    /// Creates the registers of a function call
+    #[inline]
+    pub fn new(args: impl Into<[Register<usize>; 6]>) -> Self {
+        Self {
+            args: args.into(),
+            ret: Default::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Arguments;
+    use core::mem::size_of;
+
+    #[test]
+    fn syscall() {
+        let buf = *b"hello";
+        let args = Arguments::new().arg(1usize).slice(&buf[..]);
+        let mut regs = SyscallRegs::new(1usize, args);
+
+        assert_eq!(usize::from(regs.number), 1);
+        assert_eq!(usize::from(regs.args[1]), buf.as_ptr() as usize);
+        assert_eq!(usize::from(regs.args[2]), 5);
+
+        regs.set_result(Err(Errno(9)));
+        assert_eq!(isize::from(regs.ret[0]), -9);
+        assert_eq!(regs.result(), Err(Errno(9)));
+
+        regs.ret[0] = Register::from(5usize);
+        assert_eq!(regs.result(), Ok(Register::from(5usize)));
+    }
+
+    #[test]
+    fn call() {
+        let args = [Register::from(7usize); 6];
+        assert_eq!(CallRegs::new(args).args, args);
+        assert_eq!(size_of::<CallRegs>(), size_of::<[Register<usize>; 8]>());
+    }
+}
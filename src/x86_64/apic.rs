@@ -0,0 +1,231 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Local and I/O APIC registers
+
+use crate::Register;
+
+crate::register_block! {
+    /// The registers of a local APIC in xAPIC mode
+    ///
+    /// The block occupies the 4 KiB page at `IA32_APIC_BASE`, which is
+    /// `0xfee0_0000` by default. All registers are 16-byte aligned.
+    #[repr(align(4096))]
+    pub struct Lapic(0x1000) {
+        /// The local APIC ID
+        0x020 => id: u32, ReadWrite {
+            /// The APIC ID
+            ID = 24..32,
+        };
+
+        /// The local APIC version
+        0x030 => version: u32, ReadOnly {
+            /// The version
+            VERSION = 0..8,
+
+            /// The number of LVT entries minus one
+            VERSION_MAX_LVT = 16..24,
+        };
+
+        /// The task priority register
+        0x080 => tpr: u32, ReadWrite;
+
+        /// The end of interrupt register
+        0x0b0 => eoi: u32, WriteOnly;
+
+        /// The logical destination register
+        0x0d0 => ldr: u32, ReadWrite;
+
+        /// The spurious interrupt vector register
+        0x0f0 => svr: u32, ReadWrite {
+            /// The spurious interrupt vector
+            SVR_VECTOR = 0..8,
+
+            /// Enables the local APIC
+            SVR_ENABLE = 8..9,
+        };
+
+        /// The error status register
+        0x280 => esr: u32, ReadWrite;
+
+        /// The interrupt command register, low half
+        0x300 => icr_low: u32, ReadWrite {
+            /// The vector
+            ICR_VECTOR = 0..8,
+
+            /// The delivery mode (fixed, SMI, NMI, INIT, SIPI)
+            ICR_DELIVERY_MODE = 8..11,
+
+            /// The IPI has not been accepted yet
+            ICR_PENDING = 12..13,
+
+            /// The level for INIT level de-assert
+            ICR_LEVEL = 14..15,
+
+            /// The destination shorthand
+            ICR_SHORTHAND = 18..20,
+        };
+
+        /// The interrupt command register, high half
+        0x310 => icr_high: u32, ReadWrite {
+            /// The destination APIC ID
+            ICR_DESTINATION = 24..32,
+        };
+
+        /// The LVT timer register
+        0x320 => lvt_timer: u32, ReadWrite {
+            /// The vector
+            LVT_VECTOR = 0..8,
+
+            /// The interrupt is masked
+            LVT_MASKED = 16..17,
+
+            /// The timer mode (one-shot, periodic, TSC deadline)
+            LVT_TIMER_MODE = 17..19,
+        };
+
+        /// The LVT LINT0 register
+        0x350 => lvt_lint0: u32, ReadWrite;
+
+        /// The LVT LINT1 register
+        0x360 => lvt_lint1: u32, ReadWrite;
+
+        /// The LVT error register
+        0x370 => lvt_error: u32, ReadWrite;
+
+        /// The timer initial count register
+        0x380 => timer_initial: u32, ReadWrite;
+
+        /// The timer current count register
+        0x390 => timer_current: u32, ReadOnly;
+
+        /// The timer divide configuration register
+        0x3e0 => timer_divide: u32, ReadWrite;
+    }
+}
+
+impl Lapic {
+    /// Enables the local APIC with the spurious interrupt `vector`
+    pub fn enable(&self, vector: u8) {
+        let enable = 1 << Self::SVR_ENABLE.start;
+        self.svr()
+            .update(|svr| Register::from(u32::from(svr) & !0xff | enable | u32::from(vector)));
+    }
+
+    /// Signals the end of the current interrupt
+    #[inline]
+    pub fn end_of_interrupt(&self) {
+        self.eoi().write(Register::from(0u32));
+    }
+
+    /// Sends an IPI to the local APIC with the ID `destination`
+    ///
+    /// `command` is the low half of the interrupt command register.
+    pub fn send_ipi(&self, destination: u8, command: u32) {
+        let destination = u32::from(destination) << Self::ICR_DESTINATION.start;
+        self.icr_high().write(Register::from(destination));
+        self.icr_low().write(Register::from(command));
+    }
+}
+
+crate::register_block! {
+    /// The registers of an I/O APIC
+    ///
+    /// The I/O APIC registers are reached indirectly: the index is written
+    /// to `ioregsel` and the value is then accessed through `iowin`.
+    #[repr(align(16))]
+    pub struct IoApic(0x20) {
+        /// The register select register
+        0x00 => ioregsel: u32, ReadWrite;
+
+        /// The register window
+        0x10 => iowin: u32, ReadWrite;
+    }
+}
+
+impl IoApic {
+    /// The index of the I/O APIC ID register
+    pub const ID: u32 = 0x00;
+
+    /// The index of the I/O APIC version register
+    pub const VERSION: u32 = 0x01;
+
+    /// The index of the I/O APIC arbitration register
+    pub const ARBITRATION: u32 = 0x02;
+
+    /// The index of the first redirection table register
+    pub const REDIRECTION_TABLE: u32 = 0x10;
+
+    /// Reads the register at `index`
+    #[inline]
+    pub fn read(&self, index: u32) -> u32 {
+        self.ioregsel().write(Register::from(index));
+        self.iowin().read().into()
+    }
+
+    /// Writes the register at `index`
+    #[inline]
+    pub fn write(&self, index: u32, value: u32) {
+        self.ioregsel().write(Register::from(index));
+        self.iowin().write(Register::from(value));
+    }
+
+    /// Reads the redirection table entry for `irq`
+    pub fn redirection(&self, irq: u8) -> u64 {
+        let index = Self::REDIRECTION_TABLE + u32::from(irq) * 2;
+        let low = self.read(index);
+        let high = self.read(index + 1);
+        u64::from(high) << 32 | u64::from(low)
+    }
+
+    /// Writes the redirection table entry for `irq`
+    ///
+    /// The high half is written first, so that the entry is never unmasked
+    /// with a stale destination.
+    pub fn set_redirection(&self, irq: u8, entry: u64) {
+        let index = Self::REDIRECTION_TABLE + u32::from(irq) * 2;
+        self.write(index + 1, (entry >> 32) as u32);
+        self.write(index, entry as u32);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{Address, Page};
+    use core::mem::size_of;
+
+    fn read(page: &Page, offset: usize) -> u32 {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(&page[offset..offset + 4]);
+        u32::from_le_bytes(bytes)
+    }
+
+    #[test]
+    fn lapic() {
+        let mut page = Page::default();
+        let addr = Address::from(&mut page as *mut Page as usize).lower();
+        let lapic = unsafe { Lapic::at(addr) };
+
+        lapic.enable(0xff);
+        lapic.send_ipi(3, 0x4500);
+        lapic.end_of_interrupt();
+
+        assert_eq!(read(&page, 0xf0), 0x1ff);
+        assert_eq!(read(&page, 0x310), 0x0300_0000);
+        assert_eq!(read(&page, 0x300), 0x4500);
+        assert_eq!(size_of::<Lapic>(), 0x1000);
+    }
+
+    #[test]
+    fn ioapic() {
+        let mut page = Page::default();
+        let addr = Address::from(&mut page as *mut Page as usize).lower();
+        let ioapic = unsafe { IoApic::at(addr) };
+
+        // With plain memory, the window keeps the last value written
+        ioapic.set_redirection(2, 0x0100_0000_0001_0030);
+        assert_eq!(read(&page, 0x00), 0x14);
+        assert_eq!(read(&page, 0x10), 0x0001_0030);
+        assert_eq!(ioapic.read(IoApic::VERSION), 0x0001_0030);
+    }
+}
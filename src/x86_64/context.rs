@@ -0,0 +1,257 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The general-purpose register file
+//!
+//! `RegisterFile` holds the integer state of a thread: the sixteen
+//! general-purpose registers in encoding order, RIP, RFLAGS and the segment
+//! selectors. It is the state saved on VM exits, signals and enclave exits.
+
+use crate::Register;
+
+use core::fmt::{Debug, Formatter, Result};
+use core::mem::size_of;
+use core::ops::{Index, IndexMut};
+
+/// The general-purpose registers, RIP, RFLAGS and the segment selectors
+///
+/// The general-purpose registers are laid out in the order of their
+/// encoding, so indexing the register file with a register number selects
+/// that register (`regs[0]` is RAX, `regs[8]` is R8). The segment selectors
+/// also follow their encoding (ES, CS, SS, DS, FS, GS).
+#[derive(Copy, Clone, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct RegisterFile {
+    /// RAX (0)
+    pub rax: Register<u64>,
+
+    /// RCX (1)
+    pub rcx: Register<u64>,
+
+    /// RDX (2)
+    pub rdx: Register<u64>,
+
+    /// RBX (3)
+    pub rbx: Register<u64>,
+
+    /// RSP (4)
+    pub rsp: Register<u64>,
+
+    /// RBP (5)
+    pub rbp: Register<u64>,
+
+    /// RSI (6)
+    pub rsi: Register<u64>,
+
+    /// RDI (7)
+    pub rdi: Register<u64>,
+
+    /// R8
+    pub r8: Register<u64>,
+
+    /// R9
+    pub r9: Register<u64>,
+
+    /// R10
+    pub r10: Register<u64>,
+
+    /// R11
+    pub r11: Register<u64>,
+
+    /// R12
+    pub r12: Register<u64>,
+
+    /// R13
+    pub r13: Register<u64>,
+
+    /// R14
+    pub r14: Register<u64>,
+
+    /// R15
+    pub r15: Register<u64>,
+
+    /// The instruction pointer
+    pub rip: Register<u64>,
+
+    /// The flags
+    pub rflags: Register<u64>,
+
+    /// The ES selector
+    pub es: Register<u64>,
+
+    /// The CS selector
+    pub cs: Register<u64>,
+
+    /// The SS selector
+    pub ss: Register<u64>,
+
+    /// The DS selector
+    pub ds: Register<u64>,
+
+    /// The FS selector
+    pub fs: Register<u64>,
+
+    /// The GS selector
+    pub gs: Register<u64>,
+}
+
+const _: () = assert!(size_of::<RegisterFile>() == size_of::<[Register<u64>; RegisterFile::LEN]>());
+
+impl RegisterFile {
+    /// The number of registers
+    pub const LEN: usize = 24;
+
+    /// The number of general-purpose registers
+    pub const GPRS: usize = 16;
+
+    /// The names of the registers in layout order
+    pub const NAMES: [&'static str; Self::LEN] = [
+        "rax", "rcx", "rdx", "rbx", "rsp", "rbp", "rsi", "rdi", "r8", "r9", "r10", "r11", "r12",
+        "r13", "r14", "r15", "rip", "rflags", "es", "cs", "ss", "ds", "fs", "gs",
+    ];
+
+    /// Returns the registers in layout order
+    #[inline]
+    pub fn as_array(&self) -> &[Register<u64>; Self::LEN] {
+        // SAFETY: the structure consists of `LEN` registers and nothing else.
+        unsafe { &*(self as *const Self).cast() }
+    }
+
+    /// Returns the registers in layout order, mutably
+    #[inline]
+    pub fn as_array_mut(&mut self) -> &mut [Register<u64>; Self::LEN] {
+        // SAFETY: the structure consists of `LEN` registers and nothing else.
+        unsafe { &mut *(self as *mut Self).cast() }
+    }
+
+    /// Returns the general-purpose register with the given number
+    #[inline]
+    pub fn gpr(&self, number: usize) -> Option<Register<u64>> {
+        self.as_array()[..Self::GPRS].get(number).copied()
+    }
+
+    /// Returns the general-purpose register with the given number, mutably
+    #[inline]
+    pub fn gpr_mut(&mut self, number: usize) -> Option<&mut Register<u64>> {
+        self.as_array_mut()[..Self::GPRS].get_mut(number)
+    }
+
+    /// Iterates over the registers in layout order
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, Register<u64>> {
+        self.as_array().iter()
+    }
+
+    /// Iterates mutably over the registers in layout order
+    #[inline]
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, Register<u64>> {
+        self.as_array_mut().iter_mut()
+    }
+}
+
+impl Index<usize> for RegisterFile {
+    type Output = Register<u64>;
+
+    #[inline]
+    fn index(&self, number: usize) -> &Self::Output {
+        &self.as_array()[..Self::GPRS][number]
+    }
+}
+
+impl IndexMut<usize> for RegisterFile {
+    #[inline]
+    fn index_mut(&mut self, number: usize) -> &mut Self::Output {
+        &mut self.as_array_mut()[..Self::GPRS][number]
+    }
+}
+
+impl<'a> IntoIterator for &'a RegisterFile {
+    type Item = &'a Register<u64>;
+    type IntoIter = core::slice::Iter<'a, Register<u64>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut RegisterFile {
+    type Item = &'a mut Register<u64>;
+    type IntoIter = core::slice::IterMut<'a, Register<u64>>;
+
+    #[inline]
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl Debug for RegisterFile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+        let mut debug = f.debug_struct("RegisterFile");
+        for (name, value) in Self::NAMES.iter().zip(self) {
+            debug.field(name, &format_args!("{:#x}", u64::from(*value)));
+        }
+
+        debug.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use std::format;
+
+    #[test]
+    fn index() {
+        let mut regs = RegisterFile {
+            rip: Register::from(0x40_1000u64),
+            ..Default::default()
+        };
+
+        regs[0] = Register::from(60u64);
+        regs[7] = Register::from(1u64);
+        *regs.gpr_mut(15).unwrap() = Register::from(0xffu64);
+
+        assert_eq!(regs.rax, Register::from(60u64));
+        assert_eq!(regs.rdi, Register::from(1u64));
+        assert_eq!(regs.gpr(15), Some(Register::from(0xffu64)));
+        assert_eq!(regs.gpr(16), None);
+        assert!(regs.gpr_mut(16).is_none());
+        assert_eq!(regs.as_array()[16], regs.rip);
+    }
+
+    #[test]
+    #[should_panic]
+    fn index_rip() {
+        let regs = RegisterFile::default();
+        let _ = regs[16];
+    }
+
+    #[test]
+    fn iter() {
+        let mut regs = RegisterFile::default();
+        for (i, reg) in regs.iter_mut().enumerate() {
+            *reg = Register::from(i as u64);
+        }
+
+        assert_eq!(regs.r8, Register::from(8u64));
+        assert_eq!(regs.rflags, Register::from(17u64));
+        assert_eq!(regs.gs, Register::from(23u64));
+        assert_eq!((&regs).into_iter().count(), RegisterFile::LEN);
+    }
+
+    #[test]
+    fn debug() {
+        let regs = RegisterFile {
+            rax: Register::from(0x3cu64),
+            rflags: Register::from(0x202u64),
+            ..Default::default()
+        };
+
+        let debug = format!("{:?}", regs);
+        assert!(debug.starts_with("RegisterFile { rax: 0x3c, rcx: 0x0,"));
+        assert!(debug.contains("rflags: 0x202,"));
+        assert!(debug.ends_with("gs: 0x0 }"));
+    }
+}
@@ -0,0 +1,1049 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! CPUID decoding
+//!
+//! The `CPUID` instruction returns four registers for a leaf and subleaf.
+//! This module decodes those results into typed structures. The results
+//! come from a `Source`, which is either a table of recorded results or,
+//! with the `asm` feature, the processor itself.
+
+use super::paging::CBit;
+use super::registers::Xcr0;
+use crate::{Address, Offset, Page, Paging, Register, Span};
+
+/// The result of `CPUID` for one leaf and subleaf
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Cpuid {
+    /// The value of EAX
+    pub eax: Register<u32>,
+
+    /// The value of EBX
+    pub ebx: Register<u32>,
+
+    /// The value of ECX
+    pub ecx: Register<u32>,
+
+    /// The value of EDX
+    pub edx: Register<u32>,
+}
+
+impl Cpuid {
+    /// Creates a result from the raw register values
+    #[inline]
+    pub fn new(eax: u32, ebx: u32, ecx: u32, edx: u32) -> Self {
+        Self {
+            eax: eax.into(),
+            ebx: ebx.into(),
+            ecx: ecx.into(),
+            edx: edx.into(),
+        }
+    }
+}
+
+impl From<[Register<u32>; 4]> for Cpuid {
+    #[inline]
+    fn from([eax, ebx, ecx, edx]: [Register<u32>; 4]) -> Self {
+        Self { eax, ebx, ecx, edx }
+    }
+}
+
+impl From<Cpuid> for [Register<u32>; 4] {
+    #[inline]
+    fn from(value: Cpuid) -> Self {
+        [value.eax, value.ebx, value.ecx, value.edx]
+    }
+}
+
+/// A source of `CPUID` results
+pub trait Source {
+    /// Returns the result of `CPUID` for `leaf` and `subleaf`
+    fn cpuid(&self, leaf: u32, subleaf: u32) -> Cpuid;
+}
+
+impl<S: Source + ?Sized> Source for &S {
+    #[inline]
+    fn cpuid(&self, leaf: u32, subleaf: u32) -> Cpuid {
+        (**self).cpuid(leaf, subleaf)
+    }
+}
+
+/// A recorded `CPUID` result
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Entry {
+    /// The leaf (EAX input)
+    pub leaf: u32,
+
+    /// The subleaf (ECX input)
+    pub subleaf: u32,
+
+    /// The result
+    pub result: Cpuid,
+}
+
+/// A table of recorded results
+///
+/// Leaves and subleaves missing from the table return all zeros.
+impl Source for [Entry] {
+    fn cpuid(&self, leaf: u32, subleaf: u32) -> Cpuid {
+        self.iter()
+            .find(|entry| entry.leaf == leaf && entry.subleaf == subleaf)
+            .map(|entry| entry.result)
+            .unwrap_or_default()
+    }
+}
+
+/// Executes `CPUID` on the current processor
+#[cfg(all(feature = "asm", target_arch = "x86_64"))]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Native;
+
+#[cfg(all(feature = "asm", target_arch = "x86_64"))]
+impl Source for Native {
+    #[inline]
+    fn cpuid(&self, leaf: u32, subleaf: u32) -> Cpuid {
+        // SAFETY: every x86_64 processor supports `CPUID`. The intrinsic is
+        // safe in newer compilers.
+        #[allow(unused_unsafe)]
+        let result = unsafe { core::arch::x86_64::__cpuid_count(leaf, subleaf) };
+        Cpuid::new(result.eax, result.ebx, result.ecx, result.edx)
+    }
+}
+
+/// The leaf with the vendor and the highest basic leaf
+pub const LEAF_VENDOR: u32 = 0x0;
+
+/// The leaf with the basic feature flags
+pub const LEAF_FEATURES: u32 = 0x1;
+
+/// The leaf with the structured extended feature flags
+pub const LEAF_EXTENDED_FEATURES: u32 = 0x7;
+
+/// The leaf with the XSAVE state components
+pub const LEAF_XSAVE: u32 = 0xd;
+
+/// The leaf with the SGX capabilities
+pub const LEAF_SGX: u32 = 0x12;
+
+/// The leaf with the highest extended leaf
+pub const LEAF_EXTENDED_MAX: u32 = 0x8000_0000;
+
+/// The leaf with the extended feature flags
+pub const LEAF_EXTENDED_INFO: u32 = 0x8000_0001;
+
+/// The leaf with the address widths
+pub const LEAF_ADDRESS_WIDTHS: u32 = 0x8000_0008;
+
+/// The leaf with the AMD memory encryption capabilities
+pub const LEAF_SEV: u32 = 0x8000_001f;
+
+/// Returns `true` if `source` implements `leaf`
+fn has_leaf(source: &(impl Source + ?Sized), leaf: u32) -> bool {
+    let max = source.cpuid(leaf & 0x8000_0000, 0).eax;
+    leaf <= u32::from(max)
+}
+
+/// The processor vendor, from leaf 0
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Vendor {
+    /// `GenuineIntel`
+    Intel,
+
+    /// `AuthenticAMD`
+    Amd,
+
+    /// `HygonGenuine`
+    Hygon,
+
+    /// Any other vendor string
+    Other([u8; 12]),
+}
+
+impl From<Cpuid> for Vendor {
+    fn from(value: Cpuid) -> Self {
+        let mut vendor = [0; 12];
+        vendor[..4].copy_from_slice(&u32::from(value.ebx).to_le_bytes());
+        vendor[4..8].copy_from_slice(&u32::from(value.edx).to_le_bytes());
+        vendor[8..].copy_from_slice(&u32::from(value.ecx).to_le_bytes());
+
+        match &vendor {
+            b"GenuineIntel" => Self::Intel,
+            b"AuthenticAMD" => Self::Amd,
+            b"HygonGenuine" => Self::Hygon,
+            _ => Self::Other(vendor),
+        }
+    }
+}
+
+crate::bitfields! {
+    /// The feature flags in ECX of leaf 1
+    pub struct Leaf1Ecx(u32) {
+        /// SSE3
+        sse3, set_sse3: 0;
+
+        /// `PCLMULQDQ`
+        pclmulqdq, set_pclmulqdq: 1;
+
+        /// `MONITOR` and `MWAIT`
+        monitor, set_monitor: 3;
+
+        /// Virtual machine extensions
+        vmx, set_vmx: 5;
+
+        /// Safer mode extensions
+        smx, set_smx: 6;
+
+        /// SSSE3
+        ssse3, set_ssse3: 9;
+
+        /// Fused multiply-add
+        fma, set_fma: 12;
+
+        /// `CMPXCHG16B`
+        cx16, set_cx16: 13;
+
+        /// Process-context identifiers
+        pcid, set_pcid: 17;
+
+        /// SSE4.1
+        sse4_1, set_sse4_1: 19;
+
+        /// SSE4.2
+        sse4_2, set_sse4_2: 20;
+
+        /// x2APIC
+        x2apic, set_x2apic: 21;
+
+        /// `MOVBE`
+        movbe, set_movbe: 22;
+
+        /// `POPCNT`
+        popcnt, set_popcnt: 23;
+
+        /// TSC deadline timer
+        tsc_deadline, set_tsc_deadline: 24;
+
+        /// AES-NI
+        aes, set_aes: 25;
+
+        /// `XSAVE`
+        xsave, set_xsave: 26;
+
+        /// `XSAVE` is enabled by the OS (`CR4.OSXSAVE`)
+        osxsave, set_osxsave: 27;
+
+        /// AVX
+        avx, set_avx: 28;
+
+        /// 16-bit floating-point conversions
+        f16c, set_f16c: 29;
+
+        /// `RDRAND`
+        rdrand, set_rdrand: 30;
+
+        /// Running under a hypervisor
+        hypervisor, set_hypervisor: 31;
+    }
+}
+
+crate::bitfields! {
+    /// The feature flags in EDX of leaf 1
+    pub struct Leaf1Edx(u32) {
+        /// x87 FPU
+        fpu, set_fpu: 0;
+
+        /// Virtual-8086 mode extensions
+        vme, set_vme: 1;
+
+        /// Debugging extensions
+        de, set_de: 2;
+
+        /// Page size extensions
+        pse, set_pse: 3;
+
+        /// Time stamp counter
+        tsc, set_tsc: 4;
+
+        /// `RDMSR` and `WRMSR`
+        msr, set_msr: 5;
+
+        /// Physical address extension
+        pae, set_pae: 6;
+
+        /// Machine check exception
+        mce, set_mce: 7;
+
+        /// `CMPXCHG8B`
+        cx8, set_cx8: 8;
+
+        /// Local APIC
+        apic, set_apic: 9;
+
+        /// `SYSENTER` and `SYSEXIT`
+        sep, set_sep: 11;
+
+        /// Memory type range registers
+        mtrr, set_mtrr: 12;
+
+        /// Page global enable
+        pge, set_pge: 13;
+
+        /// Machine check architecture
+        mca, set_mca: 14;
+
+        /// `CMOV`
+        cmov, set_cmov: 15;
+
+        /// Page attribute table
+        pat, set_pat: 16;
+
+        /// 36-bit page size extension
+        pse36, set_pse36: 17;
+
+        /// `CLFLUSH`
+        clflush, set_clflush: 19;
+
+        /// MMX
+        mmx, set_mmx: 23;
+
+        /// `FXSAVE` and `FXRSTOR`
+        fxsr, set_fxsr: 24;
+
+        /// SSE
+        sse, set_sse: 25;
+
+        /// SSE2
+        sse2, set_sse2: 26;
+
+        /// Hyper-threading
+        htt, set_htt: 28;
+    }
+}
+
+crate::bitfields! {
+    /// The feature flags in EBX of leaf 7, subleaf 0
+    pub struct Leaf7Ebx(u32) {
+        /// `RDFSBASE` and friends
+        fsgsbase, set_fsgsbase: 0;
+
+        /// Software guard extensions
+        sgx, set_sgx: 2;
+
+        /// Bit manipulation instructions 1
+        bmi1, set_bmi1: 3;
+
+        /// AVX2
+        avx2, set_avx2: 5;
+
+        /// Supervisor mode execution prevention
+        smep, set_smep: 7;
+
+        /// Bit manipulation instructions 2
+        bmi2, set_bmi2: 8;
+
+        /// Enhanced `REP MOVSB` and `STOSB`
+        erms, set_erms: 9;
+
+        /// `INVPCID`
+        invpcid, set_invpcid: 10;
+
+        /// AVX-512 foundation
+        avx512f, set_avx512f: 16;
+
+        /// `RDSEED`
+        rdseed, set_rdseed: 18;
+
+        /// `ADCX` and `ADOX`
+        adx, set_adx: 19;
+
+        /// Supervisor mode access prevention
+        smap, set_smap: 20;
+
+        /// `CLFLUSHOPT`
+        clflushopt, set_clflushopt: 23;
+
+        /// `CLWB`
+        clwb, set_clwb: 24;
+
+        /// SHA extensions
+        sha, set_sha: 29;
+    }
+}
+
+crate::bitfields! {
+    /// The feature flags in ECX of leaf 7, subleaf 0
+    pub struct Leaf7Ecx(u32) {
+        /// User-mode instruction prevention
+        umip, set_umip: 2;
+
+        /// Protection keys for user pages
+        pku, set_pku: 3;
+
+        /// Protection keys are enabled by the OS (`CR4.PKE`)
+        ospke, set_ospke: 4;
+
+        /// 5-level paging
+        la57, set_la57: 16;
+
+        /// `RDPID`
+        rdpid, set_rdpid: 22;
+
+        /// SGX launch configuration
+        sgx_lc, set_sgx_lc: 30;
+    }
+}
+
+crate::bitfields! {
+    /// The feature flags in EDX of leaf 7, subleaf 0
+    pub struct Leaf7Edx(u32) {
+        /// Hybrid processor
+        hybrid, set_hybrid: 15;
+
+        /// `PCONFIG`
+        pconfig, set_pconfig: 18;
+
+        /// Indirect branch tracking
+        ibt, set_ibt: 20;
+
+        /// `IA32_SPEC_CTRL.IBRS`
+        ibrs, set_ibrs: 26;
+
+        /// `IA32_SPEC_CTRL.STIBP`
+        stibp, set_stibp: 27;
+
+        /// `IA32_FLUSH_CMD`
+        l1d_flush, set_l1d_flush: 28;
+
+        /// `IA32_ARCH_CAPABILITIES`
+        arch_capabilities, set_arch_capabilities: 29;
+
+        /// `IA32_SPEC_CTRL.SSBD`
+        ssbd, set_ssbd: 31;
+    }
+}
+
+crate::bitfields! {
+    /// The feature flags in ECX of leaf 0x8000_0001
+    pub struct ExtendedEcx(u32) {
+        /// `LAHF` and `SAHF` in 64-bit mode
+        lahf_lm, set_lahf_lm: 0;
+
+        /// Secure virtual machine
+        svm, set_svm: 2;
+
+        /// `LZCNT`
+        abm, set_abm: 5;
+
+        /// SSE4A
+        sse4a, set_sse4a: 6;
+
+        /// `PREFETCHW`
+        prefetchw, set_prefetchw: 8;
+    }
+}
+
+crate::bitfields! {
+    /// The feature flags in EDX of leaf 0x8000_0001
+    pub struct ExtendedEdx(u32) {
+        /// `SYSCALL` and `SYSRET`
+        syscall, set_syscall: 11;
+
+        /// No-execute pages
+        nx, set_nx: 20;
+
+        /// 1 GiB pages
+        page1gb, set_page1gb: 26;
+
+        /// `RDTSCP`
+        rdtscp, set_rdtscp: 27;
+
+        /// Long mode
+        lm, set_lm: 29;
+    }
+}
+
+/// The feature flags of leaves 1, 7 and 0x8000_0001
+///
+/// Flags of leaves which are not implemented are clear.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Features {
+    /// ECX of leaf 1
+    pub ecx: Leaf1Ecx,
+
+    /// EDX of leaf 1
+    pub edx: Leaf1Edx,
+
+    /// EBX of leaf 7
+    pub leaf7_ebx: Leaf7Ebx,
+
+    /// ECX of leaf 7
+    pub leaf7_ecx: Leaf7Ecx,
+
+    /// EDX of leaf 7
+    pub leaf7_edx: Leaf7Edx,
+
+    /// ECX of leaf 0x8000_0001
+    pub extended_ecx: ExtendedEcx,
+
+    /// EDX of leaf 0x8000_0001
+    pub extended_edx: ExtendedEdx,
+}
+
+impl Features {
+    /// Reads the feature flags from `source`
+    pub fn read(source: &(impl Source + ?Sized)) -> Self {
+        let mut features = Self::default();
+
+        if has_leaf(source, LEAF_FEATURES) {
+            let leaf = source.cpuid(LEAF_FEATURES, 0);
+            features.ecx = leaf.ecx.into();
+            features.edx = leaf.edx.into();
+        }
+
+        if has_leaf(source, LEAF_EXTENDED_FEATURES) {
+            let leaf = source.cpuid(LEAF_EXTENDED_FEATURES, 0);
+            features.leaf7_ebx = leaf.ebx.into();
+            features.leaf7_ecx = leaf.ecx.into();
+            features.leaf7_edx = leaf.edx.into();
+        }
+
+        if has_leaf(source, LEAF_EXTENDED_INFO) {
+            let leaf = source.cpuid(LEAF_EXTENDED_INFO, 0);
+            features.extended_ecx = leaf.ecx.into();
+            features.extended_edx = leaf.edx.into();
+        }
+
+        features
+    }
+}
+
+/// The address widths, from leaf 0x8000_0008
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct AddressWidths {
+    /// The number of physical address bits
+    pub physical: u32,
+
+    /// The number of linear (virtual) address bits
+    pub linear: u32,
+}
+
+impl From<Cpuid> for AddressWidths {
+    #[inline]
+    fn from(value: Cpuid) -> Self {
+        Self {
+            physical: value.eax.get_bits(0..8),
+            linear: value.eax.get_bits(8..16),
+        }
+    }
+}
+
+impl AddressWidths {
+    /// Returns the widest paging mode supported for the linear addresses
+    #[inline]
+    pub fn paging(self) -> Paging {
+        match self.linear >= Paging::Level5.bits() {
+            true => Paging::Level5,
+            false => Paging::Level4,
+        }
+    }
+
+    /// Returns the mask of the implemented physical address bits
+    #[inline]
+    pub fn physical_mask(self) -> u64 {
+        match self.physical {
+            0 => 0,
+            bits => Register::<u64>::mask(0..bits.min(64)),
+        }
+    }
+}
+
+crate::bitfields! {
+    /// The XSAVE extensions in EAX of leaf 0xD, subleaf 1
+    pub struct XsaveFeatures(u32) {
+        /// `XSAVEOPT`
+        xsaveopt, set_xsaveopt: 0;
+
+        /// `XSAVEC` and the compacted format
+        xsavec, set_xsavec: 1;
+
+        /// `XGETBV` with ECX = 1
+        xgetbv1, set_xgetbv1: 2;
+
+        /// `XSAVES`, `XRSTORS` and `IA32_XSS`
+        xsaves, set_xsaves: 3;
+    }
+}
+
+/// The XSAVE capabilities, from leaf 0xD
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Xsave {
+    /// The user state components which may be enabled in XCR0
+    pub supported: Xcr0,
+
+    /// The supervisor state components which may be enabled in `IA32_XSS`
+    pub supervisor: u64,
+
+    /// The size of the XSAVE area for the components enabled in XCR0
+    pub size: u32,
+
+    /// The size of the XSAVE area for all supported user components
+    pub max_size: u32,
+
+    /// The size of the compacted area for the components enabled in XCR0
+    /// and `IA32_XSS`
+    pub compacted_size: u32,
+
+    /// The XSAVE extensions
+    pub features: XsaveFeatures,
+}
+
+impl Xsave {
+    /// Reads the XSAVE capabilities, if the processor has any
+    pub fn read(source: &(impl Source + ?Sized)) -> Option<Self> {
+        if !has_leaf(source, LEAF_XSAVE) {
+            return None;
+        }
+
+        let main = source.cpuid(LEAF_XSAVE, 0);
+        let sub = source.cpuid(LEAF_XSAVE, 1);
+        let join = |low: Register<u32>, high: Register<u32>| {
+            u64::from(u32::from(high)) << 32 | u64::from(u32::from(low))
+        };
+
+        match u32::from(main.eax) {
+            0 => None,
+            _ => Some(Self {
+                supported: Xcr0::from_bits(join(main.eax, main.edx)),
+                supervisor: join(sub.ecx, sub.edx),
+                size: main.ebx.into(),
+                max_size: main.ecx.into(),
+                compacted_size: sub.ebx.into(),
+                features: sub.eax.into(),
+            }),
+        }
+    }
+
+    /// Reads the layout of state component `index`, if it is supported
+    ///
+    /// Components 0 and 1 (x87 and SSE) are part of the legacy region and
+    /// have no separate layout.
+    pub fn component(&self, source: &(impl Source + ?Sized), index: u32) -> Option<XsaveComponent> {
+        let supported = self.supported.bits() | self.supervisor;
+        if !(2..64).contains(&index) || supported >> index & 1 == 0 {
+            return None;
+        }
+
+        Some(source.cpuid(LEAF_XSAVE, index).into())
+    }
+}
+
+/// The layout of an XSAVE state component, from leaf 0xD, subleaf 2 and up
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct XsaveComponent {
+    /// The size in bytes
+    pub size: u32,
+
+    /// The offset in the standard format, or zero for supervisor components
+    pub offset: u32,
+
+    /// The component is a supervisor component (`IA32_XSS`)
+    pub supervisor: bool,
+
+    /// The component is 64-byte aligned in the compacted format
+    pub aligned: bool,
+}
+
+impl From<Cpuid> for XsaveComponent {
+    #[inline]
+    fn from(value: Cpuid) -> Self {
+        Self {
+            size: value.eax.into(),
+            offset: value.ebx.into(),
+            supervisor: value.ecx.get_bit(0),
+            aligned: value.ecx.get_bit(1),
+        }
+    }
+}
+
+/// The SGX capabilities, from leaf 0x12
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Sgx {
+    /// SGX1 instructions are supported
+    pub sgx1: bool,
+
+    /// SGX2 instructions are supported
+    pub sgx2: bool,
+
+    /// The bits which may be set in `SECS.MISCSELECT`
+    pub misc_select: u32,
+
+    /// The base-2 logarithm of the maximum enclave size outside 64-bit mode
+    pub max_enclave_size_32: u32,
+
+    /// The base-2 logarithm of the maximum enclave size in 64-bit mode
+    pub max_enclave_size_64: u32,
+
+    /// The bits which may be set in the low half of `SECS.ATTRIBUTES`
+    pub attributes: u64,
+
+    /// The bits which may be set in `SECS.ATTRIBUTES.XFRM`
+    pub xfrm: u64,
+}
+
+impl Sgx {
+    /// Reads the SGX capabilities, if SGX is supported
+    pub fn read(source: &(impl Source + ?Sized)) -> Option<Self> {
+        if !Features::read(source).leaf7_ebx.sgx() || !has_leaf(source, LEAF_SGX) {
+            return None;
+        }
+
+        let main = source.cpuid(LEAF_SGX, 0);
+        let attributes = source.cpuid(LEAF_SGX, 1);
+        let join = |low: Register<u32>, high: Register<u32>| {
+            u64::from(u32::from(high)) << 32 | u64::from(u32::from(low))
+        };
+
+        Some(Self {
+            sgx1: main.eax.get_bit(0),
+            sgx2: main.eax.get_bit(1),
+            misc_select: main.ebx.into(),
+            max_enclave_size_32: main.edx.get_bits(0..8),
+            max_enclave_size_64: main.edx.get_bits(8..16),
+            attributes: join(attributes.eax, attributes.ebx),
+            xfrm: join(attributes.ecx, attributes.edx),
+        })
+    }
+
+    /// Returns the enclave page cache sections, from subleaf 2 and up
+    pub fn epc_sections<S: Source>(source: S) -> impl Iterator<Item = Span<u64, Page>> {
+        (2..).map_while(move |subleaf| epc_section(source.cpuid(LEAF_SGX, subleaf)))
+    }
+}
+
+/// Decodes an EPC section from leaf 0x12, subleaf 2 and up
+///
+/// Returns `None` for the invalid entry which terminates the list.
+pub fn epc_section(value: Cpuid) -> Option<Span<u64, Page>> {
+    if value.eax.get_bits(0..4) != 1 {
+        return None;
+    }
+
+    let base =
+        u64::from(value.ebx.get_bits(0..20)) << 32 | u64::from(value.eax.get_bits(12..32)) << 12;
+    let size =
+        u64::from(value.edx.get_bits(0..20)) << 32 | u64::from(value.ecx.get_bits(12..32)) << 12;
+
+    Some(Span::new(
+        Address::from(base).lower(),
+        Offset::from_items(size >> 12),
+    ))
+}
+
+crate::bitfields! {
+    /// The AMD memory encryption features in EAX of leaf 0x8000_001F
+    pub struct SevFeatures(u32) {
+        /// Secure memory encryption
+        sme, set_sme: 0;
+
+        /// Secure encrypted virtualization
+        sev, set_sev: 1;
+
+        /// The page flush MSR
+        page_flush, set_page_flush: 2;
+
+        /// SEV encrypted state
+        sev_es, set_sev_es: 3;
+
+        /// SEV secure nested paging
+        snp, set_snp: 4;
+
+        /// Virtual machine privilege levels
+        vmpl, set_vmpl: 5;
+
+        /// `RMPQUERY`
+        rmpquery, set_rmpquery: 6;
+
+        /// VMPL supervisor shadow stacks
+        vmpl_sss, set_vmpl_sss: 7;
+
+        /// Secure TSC
+        secure_tsc, set_secure_tsc: 8;
+
+        /// Virtualized `TSC_AUX`
+        tsc_aux_virtualization, set_tsc_aux_virtualization: 9;
+
+        /// Hardware cache coherency across encryption domains
+        coherency, set_coherency: 10;
+
+        /// SEV guests must run in 64-bit mode
+        host_64bit, set_host_64bit: 11;
+
+        /// Restricted injection
+        restricted_injection, set_restricted_injection: 12;
+
+        /// Alternate injection
+        alternate_injection, set_alternate_injection: 13;
+
+        /// Debug register swapping
+        debug_swap, set_debug_swap: 14;
+
+        /// Preventing the host from using IBS on guests
+        prevent_host_ibs, set_prevent_host_ibs: 15;
+
+        /// Virtual transparent encryption
+        vte, set_vte: 16;
+    }
+}
+
+/// The AMD memory encryption capabilities, from leaf 0x8000_001F
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Sev {
+    /// The supported features
+    pub features: SevFeatures,
+
+    /// The position of the C-bit in page table entries
+    pub c_bit_position: u32,
+
+    /// The number of physical address bits lost when encryption is enabled
+    pub physical_reduction: u32,
+
+    /// The number of VMPLs
+    pub vmpls: u32,
+
+    /// The number of encrypted guests supported simultaneously
+    pub guests: u32,
+
+    /// The minimum ASID of guests without SEV-ES
+    pub min_sev_asid: u32,
+}
+
+impl From<Cpuid> for Sev {
+    #[inline]
+    fn from(value: Cpuid) -> Self {
+        Self {
+            features: value.eax.into(),
+            c_bit_position: value.ebx.get_bits(0..6),
+            physical_reduction: value.ebx.get_bits(6..12),
+            vmpls: value.ebx.get_bits(12..16),
+            guests: value.ecx.into(),
+            min_sev_asid: value.edx.into(),
+        }
+    }
+}
+
+impl Sev {
+    /// Reads the memory encryption capabilities, if the leaf is implemented
+    pub fn read(source: &(impl Source + ?Sized)) -> Option<Self> {
+        match has_leaf(source, LEAF_SEV) {
+            true => Some(source.cpuid(LEAF_SEV, 0).into()),
+            false => None,
+        }
+    }
+
+    /// Returns the C-bit, if memory encryption is supported
+    #[inline]
+    pub fn c_bit(&self) -> Option<CBit> {
+        match self.features.sme() || self.features.sev() {
+            true => CBit::new(self.c_bit_position),
+            false => None,
+        }
+    }
+}
+
+/// `CPUID` results of real processors, shared by the tests
+#[cfg(test)]
+pub(crate) mod fixtures {
+    use super::*;
+
+    /// Returns the entry for `leaf` and `subleaf` with the given registers
+    pub fn entry(leaf: u32, subleaf: u32, eax: u32, ebx: u32, ecx: u32, edx: u32) -> Entry {
+        Entry {
+            leaf,
+            subleaf,
+            result: Cpuid::new(eax, ebx, ecx, edx),
+        }
+    }
+
+    /// Representative results of an AMD EPYC (Milan) SEV-SNP host
+    pub fn milan() -> [Entry; 13] {
+        [
+            entry(0x0, 0, 0x10, 0x6874_7541, 0x444d_4163, 0x6974_6e65),
+            entry(0x1, 0, 0x00a0_0f11, 0x0040_0800, 0x7ef8_320b, 0x178b_fbff),
+            entry(0x7, 0, 0, 0x219c_97a9, 0x0040_069c, 0x10),
+            entry(0xd, 0, 0x207, 0x988, 0x988, 0),
+            entry(0xd, 1, 0xf, 0x348, 0x1800, 0),
+            entry(0xd, 2, 0x100, 0x240, 0, 0),
+            entry(0xd, 9, 0x8, 0x980, 0, 0),
+            entry(0xd, 11, 0x10, 0, 0x1, 0),
+            entry(0xd, 12, 0x18, 0, 0x1, 0),
+            entry(
+                0x8000_0000,
+                0,
+                0x8000_0023,
+                0x6874_7541,
+                0x444d_4163,
+                0x6974_6e65,
+            ),
+            entry(
+                0x8000_0001,
+                0,
+                0x00a0_0f11,
+                0x4000_0000,
+                0x75c2_37ff,
+                0x2fd3_fbff,
+            ),
+            entry(0x8000_0008, 0, 0x3030, 0x791e_f257, 0x707f, 0x1_0000),
+            entry(0x8000_001f, 0, 0x0001_789f, 0x4073, 0x1fd, 0x1),
+        ]
+    }
+
+    /// Representative results of an Intel Xeon (Ice Lake) SGX host
+    pub fn ice_lake() -> [Entry; 10] {
+        [
+            entry(0x0, 0, 0x1b, 0x756e_6547, 0x6c65_746e, 0x4965_6e69),
+            entry(0x1, 0, 0x0006_06a6, 0x0080_0800, 0x7ffa_fbbf, 0xbfeb_fbff),
+            entry(0x7, 0, 0, 0xf3bf_b7ef, 0x4041_7f5e, 0xbc04_0412),
+            entry(0x12, 0, 0x403, 0x1, 0, 0x381f),
+            entry(0x12, 1, 0xb6, 0, 0x2e7, 0),
+            entry(0x12, 2, 0x7020_0001, 0, 0x7ec0_0001, 0),
+            entry(0x12, 3, 0x0040_0001, 0x80, 0x7f80_0001, 0),
+            entry(0x8000_0000, 0, 0x8000_0008, 0, 0, 0),
+            entry(0x8000_0001, 0, 0, 0, 0x121, 0x2c10_0800),
+            entry(0x8000_0008, 0, 0x392e, 0, 0, 0),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::fixtures::*;
+    use super::*;
+
+    #[test]
+    fn vendor() {
+        assert_eq!(Vendor::from(milan().cpuid(0, 0)), Vendor::Amd);
+        assert_eq!(Vendor::from(ice_lake().cpuid(0, 0)), Vendor::Intel);
+        assert_eq!(
+            Vendor::from(Cpuid::new(0, 0x4b4d_564b, 0x4d, 0x564b_4d56)),
+            Vendor::Other(*b"KVMKVMKVM\0\0\0")
+        );
+
+        let regs: [Register<u32>; 4] = milan().cpuid(0, 0).into();
+        assert_eq!(Cpuid::from(regs), milan().cpuid(0, 0));
+        assert_eq!(milan().cpuid(0x4000_0000, 0), Cpuid::default());
+    }
+
+    #[test]
+    fn features() {
+        let amd = Features::read(&milan()[..]);
+        assert!(amd.ecx.avx() && amd.ecx.xsave() && !amd.ecx.hypervisor());
+        assert!(amd.edx.pae() && amd.edx.sse2());
+        assert!(amd.leaf7_ebx.avx2() && !amd.leaf7_ebx.sgx() && !amd.leaf7_ebx.avx512f());
+        assert!(amd.extended_ecx.svm());
+        assert!(amd.extended_edx.nx() && amd.extended_edx.page1gb() && amd.extended_edx.lm());
+
+        let intel = Features::read(&ice_lake()[..]);
+        assert!(intel.leaf7_ebx.sgx() && intel.leaf7_ebx.avx512f());
+        assert!(intel.leaf7_ecx.la57() && intel.leaf7_ecx.sgx_lc());
+        assert!(!intel.extended_ecx.svm());
+
+        // Leaves beyond the highest leaf are not read
+        let mut limited = milan();
+        limited[0].result.eax = 0x1.into();
+        assert_eq!(Features::read(&limited[..]).leaf7_ebx, Leaf7Ebx::default());
+    }
+
+    #[test]
+    fn address_widths() {
+        let amd = AddressWidths::from(milan().cpuid(LEAF_ADDRESS_WIDTHS, 0));
+        assert_eq!(amd.physical, 48);
+        assert_eq!(amd.linear, 48);
+        assert_eq!(amd.paging(), Paging::Level4);
+        assert_eq!(amd.physical_mask(), 0xffff_ffff_ffff);
+
+        let intel = AddressWidths::from(ice_lake().cpuid(LEAF_ADDRESS_WIDTHS, 0));
+        assert_eq!(intel.physical, 46);
+        assert_eq!(intel.paging(), Paging::Level5);
+
+        let addr = Address::from(0x00ff_0000_0000_0000u64);
+        assert!(!addr.is_canonical(amd.paging()));
+        assert!(addr.is_canonical(intel.paging()));
+    }
+
+    #[test]
+    fn xsave() {
+        let milan = milan();
+        let xsave = Xsave::read(&milan[..]).unwrap();
+        assert!(xsave.supported.avx() && xsave.supported.pkru());
+        assert!(!xsave.supported.opmask());
+        assert_eq!(xsave.supervisor, 0x1800);
+        assert_eq!(xsave.size, 0x988);
+        assert_eq!(xsave.compacted_size, 0x348);
+        assert!(xsave.features.xsaves());
+
+        let avx = xsave.component(&milan[..], 2).unwrap();
+        assert_eq!(
+            avx,
+            XsaveComponent {
+                size: 0x100,
+                offset: 0x240,
+                supervisor: false,
+                aligned: false,
+            }
+        );
+        assert_eq!(xsave.component(&milan[..], 9).unwrap().offset, 0x980);
+        assert_eq!(xsave.component(&milan[..], 5), None);
+        assert_eq!(xsave.component(&milan[..], 1), None);
+
+        assert_eq!(Xsave::read(&ice_lake()[..2]), None);
+    }
+
+    #[test]
+    fn sgx() {
+        let ice_lake = ice_lake();
+        let sgx = Sgx::read(&ice_lake[..]).unwrap();
+        assert!(sgx.sgx1 && sgx.sgx2);
+        assert_eq!(sgx.misc_select, 0x1);
+        assert_eq!(sgx.max_enclave_size_32, 31);
+        assert_eq!(sgx.max_enclave_size_64, 56);
+        assert_eq!(sgx.attributes, 0xb6);
+        assert_eq!(sgx.xfrm, 0x2e7);
+
+        let page = |addr: u64| Address::from(addr).lower::<Page>();
+        let mut sections = Sgx::epc_sections(&ice_lake[..]);
+        assert_eq!(
+            sections.next(),
+            Some(Span::new(page(0x7020_0000), Offset::from_items(0x7ec00)))
+        );
+        assert_eq!(
+            sections.next(),
+            Some(Span::new(page(0x80_0040_0000), Offset::from_items(0x7f800)))
+        );
+        assert_eq!(sections.next(), None);
+
+        assert_eq!(Sgx::read(&milan()[..]), None);
+    }
+
+    #[test]
+    fn sev() {
+        let sev = Sev::read(&milan()[..]).unwrap();
+        assert!(sev.features.sev() && sev.features.sev_es() && sev.features.snp());
+        assert_eq!(sev.c_bit_position, 51);
+        assert_eq!(sev.c_bit(), CBit::new(51));
+        assert_eq!(sev.physical_reduction, 1);
+        assert_eq!(sev.vmpls, 4);
+        assert_eq!(sev.guests, 509);
+        assert_eq!(sev.min_sev_asid, 1);
+
+        assert_eq!(Sev::read(&ice_lake()[..]), None);
+        assert_eq!(Sev::from(Cpuid::new(0, 0x33, 0, 0)).c_bit(), None);
+    }
+
+    #[cfg(all(feature = "asm", target_arch = "x86_64"))]
+    #[test]
+    fn native() {
+        let features = Features::read(&Native);
+        assert!(features.edx.sse2());
+        assert!(features.extended_edx.lm());
+        assert!(AddressWidths::from(Native.cpuid(LEAF_ADDRESS_WIDTHS, 0)).physical >= 32);
+    }
+}
@@ -0,0 +1,53 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Primitives specific to the x86_64 architecture
+//!
+//! These are plain data definitions and do not require running on x86_64.
+
+/// Implements `From<Page>` and `AsRef<Page>` for a page overlay
+///
+/// The type must have the size and the alignment of a `Page`, which is
+/// checked at compile time, and every bit pattern must be a valid value.
+macro_rules! page_overlay {
+    ($name:ident) => {
+        const _: () = {
+            use ::core::mem::{align_of, size_of};
+            assert!(
+                size_of::<$name>() == size_of::<$crate::Page>(),
+                "page overlay is not page-sized"
+            );
+            assert!(
+                align_of::<$name>() == align_of::<$crate::Page>(),
+                "page overlay is not page-aligned"
+            );
+        };
+
+        impl From<$crate::Page> for $name {
+            #[inline]
+            fn from(value: $crate::Page) -> Self {
+                // SAFETY: both types have the same size and any bytes are a
+                // valid value.
+                unsafe { ::core::mem::transmute(value) }
+            }
+        }
+
+        impl AsRef<$crate::Page> for $name {
+            #[inline]
+            fn as_ref(&self) -> &$crate::Page {
+                // SAFETY: both types have the same size and alignment, and
+                // the overlay is plain bytes.
+                unsafe { &*(self as *const Self).cast() }
+            }
+        }
+    };
+}
+
+pub mod abi;
+pub mod apic;
+pub mod context;
+pub mod cpuid;
+pub mod paging;
+pub mod registers;
+pub mod sgx;
+pub mod snp;
+pub mod xsave;
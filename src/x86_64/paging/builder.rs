@@ -0,0 +1,721 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{CBit, Config, Flags, Level, PageTable, PageTableEntry, Walker};
+use crate::{Address, FrameSource, FramesMut, Page, Span};
+
+use core::marker::PhantomData;
+
+/// The flags of entries referencing page tables
+///
+/// These are as permissive as possible, so that the permissions of a mapping
+/// are determined by its leaf entry alone.
+const TABLE: Flags = Flags::PRESENT.union(Flags::WRITABLE).union(Flags::USER);
+
+/// An error encountered while modifying page tables
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MapError {
+    /// The virtual address is not canonical
+    NonCanonical(Address<u64, ()>),
+
+    /// The virtual address is already mapped
+    AlreadyMapped(Address<u64, ()>),
+
+    /// The range extends beyond the end of the physical address space
+    Overflow,
+
+    /// The frame source is exhausted
+    OutOfFrames,
+
+    /// A page table is located at a physical address which is not accessible
+    MissingTable(Address<u64, Page>),
+}
+
+/// A slot in a page table
+#[derive(Copy, Clone)]
+struct Slot {
+    table: Address<u64, Page>,
+    index: usize,
+    level: Level,
+    entry: PageTableEntry,
+}
+
+/// A page table builder
+///
+/// The builder maps ranges of virtual memory to ranges of physical memory
+/// using the largest pages possible, allocating page tables from a frame
+/// source as required. Huge pages are split transparently when only a part
+/// of them is unmapped or changes protection. Page tables which become empty
+/// are not released, but a huge page may be mapped over them.
+///
+/// If an operation fails, the mappings it made so far are left in place.
+pub struct Builder<M, F> {
+    config: Config,
+    root: Address<u64, Page>,
+    largest: Level,
+    memory: M,
+    frames: F,
+}
+
+impl<M: FramesMut, F: FrameSource> Builder<M, F> {
+    /// Creates a builder for a new hierarchy
+    ///
+    /// The top-level table is allocated from `frames`.
+    pub fn new(config: Config, memory: M, frames: F) -> Result<Self, MapError> {
+        let mut builder = Self::with_root(config, Address::NULL, memory, frames);
+        builder.root = builder.allocate()?;
+        Ok(builder)
+    }
+
+    /// Creates a builder for the existing hierarchy with the top-level table at `root`
+    #[inline]
+    pub fn with_root(config: Config, root: Address<u64, Page>, memory: M, frames: F) -> Self {
+        Self {
+            config,
+            root,
+            largest: Level::Pdpt,
+            memory,
+            frames,
+        }
+    }
+
+    /// Limits the size of the pages used for new mappings
+    ///
+    /// By default, 1 GiB pages are used where possible. Not all processors
+    /// support them, though, in which case `Level::Pd` should be passed here.
+    #[inline]
+    pub fn with_largest(mut self, level: Level) -> Self {
+        self.largest = level;
+        self
+    }
+
+    /// Returns the physical address of the top-level table
+    #[inline]
+    pub fn root(&self) -> Address<u64, Page> {
+        self.root
+    }
+
+    /// Returns a walker for the hierarchy
+    #[inline]
+    pub fn walker(&self) -> Walker<'_, M> {
+        Walker::new(self.config, self.root, &self.memory)
+    }
+
+    /// Returns the memory and the frame source
+    #[inline]
+    pub fn into_inner(self) -> (M, F) {
+        (self.memory, self.frames)
+    }
+
+    /// Maps `virt` to the physically contiguous range starting at `phys`
+    ///
+    /// The mapped memory is encrypted if a C-bit is configured, in which case
+    /// the physical range must end below the C-bit. The `PRESENT` flag is
+    /// implied and the `HUGE` flag is managed by the builder.
+    pub fn map(
+        &mut self,
+        virt: Span<u64, Page>,
+        phys: Address<u64, Page>,
+        flags: Flags,
+    ) -> Result<(), MapError> {
+        self.map_with(virt, phys, flags, self.config.c_bit)
+    }
+
+    /// Maps `virt` to the physically contiguous range starting at `phys` unencrypted
+    ///
+    /// This is used for memory shared with the host when a C-bit is
+    /// configured. Otherwise, it is the same as `map()`.
+    pub fn map_shared(
+        &mut self,
+        virt: Span<u64, Page>,
+        phys: Address<u64, Page>,
+        flags: Flags,
+    ) -> Result<(), MapError> {
+        self.map_with(virt, phys, flags, None)
+    }
+
+    /// Removes all mappings in `virt`
+    ///
+    /// Parts of `virt` which are not mapped are skipped.
+    pub fn unmap(&mut self, virt: Span<u64, Page>) -> Result<(), MapError> {
+        self.update(virt, |_| PageTableEntry::UNUSED)
+    }
+
+    /// Changes the flags of all mappings in `virt`
+    ///
+    /// Parts of `virt` which are not mapped are skipped. The `PRESENT` flag
+    /// is implied and the `HUGE` flag is managed by the builder.
+    pub fn protect(&mut self, virt: Span<u64, Page>, flags: Flags) -> Result<(), MapError> {
+        let flags = flags.difference(Flags::HUGE) | Flags::PRESENT;
+
+        self.update(virt, |entry| {
+            if entry.is_huge() {
+                entry.with_flags(flags | Flags::HUGE)
+            } else {
+                entry.with_flags(flags)
+            }
+        })
+    }
+
+    fn map_with(
+        &mut self,
+        virt: Span<u64, Page>,
+        phys: Address<u64, Page>,
+        flags: Flags,
+        c_bit: Option<CBit>,
+    ) -> Result<(), MapError> {
+        let flags = flags.difference(Flags::HUGE) | Flags::PRESENT;
+        let mut remaining = virt.count.checked_bytes().ok_or(MapError::Overflow)?;
+        let mut va = virt.start.raw();
+        let mut pa = phys.raw();
+
+        // Physical addresses must stay below the C-bit, even for shared
+        // memory, or they could not be told apart from encrypted ones
+        let limit = match self.config.c_bit {
+            Some(c_bit) => c_bit.mask(),
+            None => PageTableEntry::ADDRESS_MASK + 1,
+        };
+        match pa.checked_add(remaining) {
+            Some(end) if end <= limit => (),
+            _ => return Err(MapError::Overflow),
+        }
+
+        // The range may end at the very top of the address space
+        if remaining > 0 && va.checked_add(remaining - 1).is_none() {
+            return Err(MapError::Overflow);
+        }
+
+        while remaining > 0 {
+            let level = [Level::Pdpt, Level::Pd, Level::Pt]
+                .iter()
+                .copied()
+                .filter(|level| *level <= self.largest || *level == Level::Pt)
+                .find(|level| {
+                    let mask = level.size() - 1;
+                    va & mask == 0 && pa & mask == 0 && remaining >= level.size()
+                })
+                .unwrap_or(Level::Pt);
+
+            let table = self.descend(va, level)?;
+            let index = level.index(Address::<u64, ()>::from(va));
+            let entry = self.table_mut(table)?[index];
+            if entry.is_present() {
+                // A huge page may replace page tables left empty by `unmap()`
+                let empty = match level.next() {
+                    Some(next) if !entry.is_huge() => {
+                        self.is_empty(self.config.address(entry), next)?
+                    }
+                    _ => false,
+                };
+
+                if !empty {
+                    return Err(MapError::AlreadyMapped(Address::from(va)));
+                }
+            }
+
+            let flags = match level {
+                Level::Pt => flags,
+                _ => flags | Flags::HUGE,
+            };
+
+            let entry = PageTableEntry::new(Address(pa, PhantomData), flags);
+            self.table_mut(table)?[index] = match c_bit {
+                Some(c_bit) => entry.encrypted(c_bit),
+                None => entry,
+            };
+
+            va = va.wrapping_add(level.size());
+            pa += level.size();
+            remaining -= level.size();
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the leaf entries mapping `virt` by `f(entry)`
+    fn update(
+        &mut self,
+        virt: Span<u64, Page>,
+        f: impl Fn(PageTableEntry) -> PageTableEntry,
+    ) -> Result<(), MapError> {
+        let mut remaining = virt.count.checked_bytes().ok_or(MapError::Overflow)?;
+        let mut va = virt.start.raw();
+
+        if remaining > 0 && va.checked_add(remaining - 1).is_none() {
+            return Err(MapError::Overflow);
+        }
+
+        while remaining > 0 {
+            let slot = self.locate(va)?;
+            let size = slot.level.size();
+            let skip = size - (va & (size - 1));
+
+            if slot.entry.is_present() {
+                if skip != size || remaining < size {
+                    self.split(slot)?;
+                    continue;
+                }
+
+                self.table_mut(slot.table)?[slot.index] = f(slot.entry);
+            }
+
+            va = va.wrapping_add(skip);
+            remaining = remaining.saturating_sub(skip);
+        }
+
+        Ok(())
+    }
+
+    /// Finds the leaf or non-present entry for `va`
+    fn locate(&mut self, va: u64) -> Result<Slot, MapError> {
+        let addr = self.canonical(va)?;
+        let mut table = self.root;
+        let mut level = Level::root(self.config.paging);
+
+        loop {
+            let index = level.index(addr);
+            let entry = self.table_mut(table)?[index];
+
+            match level.next() {
+                Some(next) if entry.is_present() && !entry.is_huge() => {
+                    table = self.config.address(entry);
+                    level = next;
+                }
+
+                _ => {
+                    return Ok(Slot {
+                        table,
+                        index,
+                        level,
+                        entry,
+                    })
+                }
+            }
+        }
+    }
+
+    /// Returns the table at `level` for `va`, creating missing tables on the way
+    fn descend(&mut self, va: u64, target: Level) -> Result<Address<u64, Page>, MapError> {
+        let addr = self.canonical(va)?;
+        let mut table = self.root;
+        let mut level = Level::root(self.config.paging);
+
+        while level > target {
+            let index = level.index(addr);
+            let entry = self.table_mut(table)?[index];
+
+            table = if !entry.is_present() {
+                let frame = self.allocate()?;
+                self.table_mut(table)?[index] = self.table_entry(frame);
+                frame
+            } else if entry.is_huge() {
+                return Err(MapError::AlreadyMapped(addr));
+            } else {
+                self.config.address(entry)
+            };
+
+            level = match level.next() {
+                Some(next) => next,
+                None => break,
+            };
+        }
+
+        Ok(table)
+    }
+
+    /// Returns `true` if the table at `level` and the tables below it map nothing
+    fn is_empty(&mut self, table: Address<u64, Page>, level: Level) -> Result<bool, MapError> {
+        let entries = *self.table_mut(table)?;
+
+        for entry in entries.iter().filter(|entry| entry.is_present()) {
+            let empty = match level.next() {
+                Some(next) if !entry.is_huge() => {
+                    self.is_empty(self.config.address(*entry), next)?
+                }
+                _ => false,
+            };
+
+            if !empty {
+                return Ok(false);
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Replaces a huge page by a table of pages of the next smaller size
+    fn split(&mut self, slot: Slot) -> Result<(), MapError> {
+        let next = match slot.level.next() {
+            Some(next) => next,
+            None => return Ok(()),
+        };
+
+        let frame = self.allocate()?;
+        let base = self.config.address(slot.entry).raw() & !(slot.level.size() - 1);
+        let flags = match next {
+            Level::Pt => slot.entry.flags().difference(Flags::HUGE),
+            _ => slot.entry.flags(),
+        };
+        let c_bit = if self.config.is_encrypted(slot.entry) {
+            self.config.c_bit
+        } else {
+            None
+        };
+
+        for (i, entry) in self.table_mut(frame)?.iter_mut().enumerate() {
+            let addr = Address(base + i as u64 * next.size(), PhantomData);
+            *entry = PageTableEntry::new(addr, flags);
+            if let Some(c_bit) = c_bit {
+                *entry = entry.encrypted(c_bit);
+            }
+        }
+
+        self.table_mut(slot.table)?[slot.index] = self.table_entry(frame);
+        Ok(())
+    }
+
+    /// Allocates a zeroed page table
+    fn allocate(&mut self) -> Result<Address<u64, Page>, MapError> {
+        let frame = self.frames.allocate().ok_or(MapError::OutOfFrames)?;
+        *self.table_mut(frame)? = PageTable::new();
+        Ok(frame)
+    }
+
+    fn table_entry(&self, frame: Address<u64, Page>) -> PageTableEntry {
+        let entry = PageTableEntry::new(frame, TABLE);
+
+        match self.config.c_bit {
+            Some(c_bit) => entry.encrypted(c_bit),
+            None => entry,
+        }
+    }
+
+    fn table_mut(&mut self, frame: Address<u64, Page>) -> Result<&mut PageTable, MapError> {
+        let page = self
+            .memory
+            .frame_mut(frame)
+            .ok_or(MapError::MissingTable(frame))?;
+        Ok(PageTable::from_page_mut(page))
+    }
+
+    fn canonical(&self, va: u64) -> Result<Address<u64, ()>, MapError> {
+        let addr = Address::from(va);
+
+        if !addr.is_canonical(self.config.paging) {
+            return Err(MapError::NonCanonical(addr));
+        }
+
+        Ok(addr)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::super::{Translation, WalkError};
+    use super::*;
+    use crate::testing::BASE;
+    use crate::{Contiguous, Line, Offset, Paging};
+    use std::vec::Vec;
+
+    const FRAMES: u64 = 16;
+
+    const KB4: u64 = 0x1000;
+    const MB2: u64 = 0x20_0000;
+    const GB1: u64 = 0x4000_0000;
+
+    type Memory = Contiguous<Vec<Page>>;
+    type Frames = crate::Addresses<u64, Page>;
+
+    fn builder(config: Config) -> Builder<Memory, Frames> {
+        let base = Address::from(BASE).lower();
+        let memory = Contiguous::new(base, std::vec![Page::zeroed(); FRAMES as usize]);
+        let frames = Line::new(base, base + Offset::from_items(FRAMES)).iter();
+        Builder::new(config, memory, frames).unwrap()
+    }
+
+    fn span(start: u64, bytes: u64) -> Span<u64, Page> {
+        Span::new(
+            Address::from(start).lower(),
+            Offset::from_items(bytes / KB4),
+        )
+    }
+
+    fn walk(builder: &Builder<Memory, Frames>, va: u64) -> Result<Translation, WalkError> {
+        builder.walker().walk(Address::from(va))
+    }
+
+    fn used(builder: Builder<Memory, Frames>) -> u64 {
+        let (_, frames) = builder.into_inner();
+        FRAMES - frames.count() as u64
+    }
+
+    #[test]
+    fn map() {
+        let mut builder = builder(Config::new(Paging::Level4));
+        let flags = Flags::WRITABLE | Flags::NO_EXECUTE;
+
+        // 4 KiB pages up to 2 MiB alignment, 2 MiB pages up to 1 GiB
+        // alignment, a 1 GiB page, then 2 MiB and 4 KiB pages again
+        let start = GB1 - MB2 - KB4;
+        let bytes = KB4 + MB2 + GB1 + MB2 + KB4;
+        builder
+            .map(span(start, bytes), Address::from(start).lower(), flags)
+            .unwrap();
+
+        let expected = [
+            (start, Level::Pt),
+            (GB1 - MB2, Level::Pd),
+            (GB1, Level::Pdpt),
+            (2 * GB1 - 1, Level::Pdpt),
+            (2 * GB1, Level::Pd),
+            (2 * GB1 + MB2, Level::Pt),
+        ];
+
+        for (va, level) in expected {
+            let t = walk(&builder, va).unwrap();
+            assert_eq!(t.address.raw(), va);
+            assert_eq!(t.level, level);
+            assert!(t.flags.contains(Flags::PRESENT | flags));
+            assert!(!t.flags.contains(Flags::USER));
+        }
+
+        assert_eq!(
+            walk(&builder, start - KB4),
+            Err(WalkError::NotPresent(Level::Pt))
+        );
+        assert_eq!(
+            walk(&builder, start + bytes),
+            Err(WalkError::NotPresent(Level::Pt))
+        );
+
+        // PML4, PDPT, two PDs and two PTs
+        assert_eq!(used(builder), 6);
+    }
+
+    #[test]
+    fn map_misaligned() {
+        let mut builder = builder(Config::new(Paging::Level4));
+
+        // The physical address prevents the use of huge pages
+        builder
+            .map(span(0, MB2), Address::from(KB4).lower(), Flags::empty())
+            .unwrap();
+
+        let t = walk(&builder, MB2 - 1).unwrap();
+        assert_eq!(t.address.raw(), MB2 + KB4 - 1);
+        assert_eq!(t.level, Level::Pt);
+        assert_eq!(used(builder), 4);
+    }
+
+    #[test]
+    fn map_largest() {
+        let mut builder = builder(Config::new(Paging::Level4)).with_largest(Level::Pd);
+        builder
+            .map(span(0, GB1), Address::NULL, Flags::empty())
+            .unwrap();
+
+        assert_eq!(walk(&builder, GB1 - 1).unwrap().level, Level::Pd);
+    }
+
+    #[test]
+    fn map_errors() {
+        let mut builder = builder(Config::new(Paging::Level4));
+        builder
+            .map(span(0, MB2), Address::NULL, Flags::empty())
+            .unwrap();
+
+        assert_eq!(
+            builder.map(span(MB2 - KB4, 2 * KB4), Address::NULL, Flags::empty()),
+            Err(MapError::AlreadyMapped(Address::from(MB2 - KB4)))
+        );
+        assert_eq!(
+            builder.map(span(KB4, KB4), Address::NULL, Flags::empty()),
+            Err(MapError::AlreadyMapped(Address::from(KB4)))
+        );
+        assert_eq!(
+            builder.map(span(0x8000_0000_0000, KB4), Address::NULL, Flags::empty()),
+            Err(MapError::NonCanonical(Address::from(0x8000_0000_0000u64)))
+        );
+        assert_eq!(
+            builder.map(
+                span(GB1, 2 * KB4),
+                Address::from(PageTableEntry::ADDRESS_MASK).lower(),
+                Flags::empty()
+            ),
+            Err(MapError::Overflow)
+        );
+        assert_eq!(
+            builder.map(
+                span(0xffff_ffff_ffff_f000, 2 * KB4),
+                Address::from(GB1).lower(),
+                Flags::empty()
+            ),
+            Err(MapError::Overflow)
+        );
+        assert_eq!(walk(&builder, 0).unwrap().address.raw(), 0);
+
+        // Mapping 4 KiB pages needs more tables than are available
+        let mut builder = builder.with_largest(Level::Pt);
+        assert_eq!(
+            builder.map(span(GB1, 16 * MB2), Address::NULL, Flags::empty()),
+            Err(MapError::OutOfFrames)
+        );
+    }
+
+    #[test]
+    fn unmap() {
+        let mut builder = builder(Config::new(Paging::Level4));
+        builder
+            .map(
+                span(0, 2 * GB1),
+                Address::from(GB1).lower(),
+                Flags::WRITABLE,
+            )
+            .unwrap();
+
+        // Unmapping a single page splits a 1 GiB and a 2 MiB page
+        builder.unmap(span(GB1 + MB2, KB4)).unwrap();
+        assert_eq!(
+            walk(&builder, GB1 + MB2),
+            Err(WalkError::NotPresent(Level::Pt))
+        );
+
+        let t = walk(&builder, GB1 + MB2 + KB4).unwrap();
+        assert_eq!(t.address.raw(), 2 * GB1 + MB2 + KB4);
+        assert_eq!(t.level, Level::Pt);
+        assert!(t.flags.contains(Flags::WRITABLE));
+
+        let t = walk(&builder, GB1 + 2 * MB2).unwrap();
+        assert_eq!(t.address.raw(), 2 * GB1 + 2 * MB2);
+        assert_eq!(t.level, Level::Pd);
+
+        assert_eq!(walk(&builder, 0).unwrap().level, Level::Pdpt);
+
+        // Unmapping whole pages doesn't split, and holes are skipped
+        builder.unmap(span(0, GB1 + 2 * MB2)).unwrap();
+        assert_eq!(
+            walk(&builder, GB1 - 1),
+            Err(WalkError::NotPresent(Level::Pdpt))
+        );
+        assert_eq!(
+            walk(&builder, GB1 + MB2 + KB4),
+            Err(WalkError::NotPresent(Level::Pt))
+        );
+        assert_eq!(walk(&builder, GB1 + 2 * MB2).unwrap().level, Level::Pd);
+
+        // PML4, PDPT, and the tables from both splits
+        assert_eq!(used(builder), 4);
+    }
+
+    #[test]
+    fn remap_huge() {
+        let mut builder = builder(Config::new(Paging::Level4));
+        builder
+            .map(span(MB2, MB2), Address::from(KB4).lower(), Flags::WRITABLE)
+            .unwrap();
+        assert_eq!(walk(&builder, MB2).unwrap().level, Level::Pt);
+
+        // The empty page table is replaced by a 2 MiB page
+        builder.unmap(span(MB2, MB2)).unwrap();
+        builder
+            .map(span(MB2, MB2), Address::from(MB2).lower(), Flags::WRITABLE)
+            .unwrap();
+
+        let t = walk(&builder, MB2 + KB4).unwrap();
+        assert_eq!(t.address.raw(), MB2 + KB4);
+        assert_eq!(t.level, Level::Pd);
+
+        // Tables which still map something are not replaced
+        builder
+            .map(span(0, KB4), Address::NULL, Flags::WRITABLE)
+            .unwrap();
+        assert_eq!(
+            builder.map(span(0, GB1), Address::NULL, Flags::WRITABLE),
+            Err(MapError::AlreadyMapped(Address::from(0u64)))
+        );
+    }
+
+    #[test]
+    fn protect() {
+        let mut builder = builder(Config::new(Paging::Level4));
+        builder
+            .map(span(0, 3 * MB2), Address::NULL, Flags::WRITABLE)
+            .unwrap();
+
+        builder
+            .protect(span(MB2 - KB4, 2 * KB4), Flags::NO_EXECUTE)
+            .unwrap();
+
+        let t = walk(&builder, MB2 - KB4).unwrap();
+        assert_eq!(t.level, Level::Pt);
+        assert_eq!(t.flags, Flags::PRESENT | Flags::NO_EXECUTE);
+
+        let t = walk(&builder, MB2).unwrap();
+        assert_eq!(t.level, Level::Pt);
+        assert_eq!(t.flags, Flags::PRESENT | Flags::NO_EXECUTE);
+
+        let t = walk(&builder, MB2 - 2 * KB4).unwrap();
+        assert_eq!(t.flags, Flags::PRESENT | Flags::WRITABLE);
+
+        let t = walk(&builder, MB2 + KB4).unwrap();
+        assert_eq!(t.address.raw(), MB2 + KB4);
+        assert_eq!(t.flags, Flags::PRESENT | Flags::WRITABLE);
+
+        // Whole huge pages keep their size
+        builder.protect(span(2 * MB2, MB2), Flags::USER).unwrap();
+        let t = walk(&builder, 2 * MB2).unwrap();
+        assert_eq!(t.level, Level::Pd);
+        assert_eq!(t.flags, Flags::PRESENT | Flags::HUGE | Flags::USER);
+    }
+
+    #[test]
+    fn encrypted() {
+        let c_bit = CBit::new(47).unwrap();
+        let config = Config::new(Paging::Level5).with_c_bit(c_bit);
+        let mut builder = builder(config);
+
+        let top = 0xffff_ffff_ffe0_0000;
+        builder
+            .map(span(top, MB2), Address::NULL, Flags::empty())
+            .unwrap();
+        builder
+            .map_shared(span(MB2, MB2), Address::from(MB2).lower(), Flags::empty())
+            .unwrap();
+
+        let t = walk(&builder, top + 0x1234).unwrap();
+        assert_eq!(t.address.raw(), 0x1234);
+        assert_eq!(t.level, Level::Pd);
+        assert!(t.encrypted);
+
+        let t = walk(&builder, MB2).unwrap();
+        assert_eq!(t.address.raw(), MB2);
+        assert!(!t.encrypted);
+
+        // Splitting keeps the encryption
+        builder.unmap(span(top, KB4)).unwrap();
+        let t = walk(&builder, top + KB4).unwrap();
+        assert_eq!(t.level, Level::Pt);
+        assert!(t.encrypted);
+
+        // Physical ranges must end below the C-bit
+        let below = Address::from(c_bit.mask() - KB4).lower();
+        builder
+            .map(span(4 * MB2, KB4), below, Flags::empty())
+            .unwrap();
+        assert_eq!(walk(&builder, 4 * MB2).unwrap().address.raw(), below.raw());
+        assert_eq!(
+            builder.map(span(6 * MB2, 2 * KB4), below, Flags::empty()),
+            Err(MapError::Overflow)
+        );
+        assert_eq!(
+            builder.map_shared(
+                span(6 * MB2, KB4),
+                Address::from(c_bit.mask()).lower(),
+                Flags::empty()
+            ),
+            Err(MapError::Overflow)
+        );
+        assert!(walk(&builder, 6 * MB2).is_err());
+    }
+}
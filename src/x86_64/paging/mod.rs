@@ -0,0 +1,796 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! x86_64 page tables
+//!
+//! This module describes the 4-level and 5-level (LA57) page table formats.
+//! It provides a software walker which translates virtual addresses through
+//! a page table hierarchy and a builder which creates such hierarchies. The
+//! hierarchy is accessed through the `Frames` trait, so it can live anywhere:
+//! in physical memory, in guest memory being prepared by a loader or in a
+//! plain buffer in a test.
+
+mod builder;
+
+pub use builder::{Builder, MapError};
+
+use crate::{Address, Frames, Page, Paging, SpaceAddress, Translate, Virtual};
+
+use core::marker::PhantomData;
+use core::ops::{Index, IndexMut};
+
+flags! {
+    /// The flags of a page table entry
+    pub struct Flags {
+        /// The entry is present
+        PRESENT = 0;
+
+        /// The mapped memory is writable
+        WRITABLE = 1;
+
+        /// The mapped memory is accessible from user mode
+        USER = 2;
+
+        /// Write-through caching is used for the mapped memory
+        WRITE_THROUGH = 3;
+
+        /// Caching is disabled for the mapped memory
+        CACHE_DISABLE = 4;
+
+        /// The entry has been used for a translation
+        ACCESSED = 5;
+
+        /// The mapped memory has been written to
+        DIRTY = 6;
+
+        /// The entry maps a huge page rather than referencing a table
+        HUGE = 7;
+
+        /// The translation is global
+        GLOBAL = 8;
+
+        /// The mapped memory is not executable
+        NO_EXECUTE = 63;
+    }
+}
+
+impl Flags {
+    /// Creates flags from raw bits, discarding the physical address bits
+    #[inline]
+    pub const fn from_bits_truncate(bits: u64) -> Self {
+        Self(bits & !PageTableEntry::ADDRESS_MASK)
+    }
+}
+
+/// The position of the SEV memory encryption bit (C-bit)
+///
+/// On AMD SEV, a bit within the physical address field of page table entries
+/// marks the mapped memory as encrypted. Its position is reported by CPUID.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct CBit(u32);
+
+impl CBit {
+    /// Creates a C-bit at the specified bit position
+    ///
+    /// Returns `None` if the position is outside of the physical address
+    /// field of page table entries.
+    #[inline]
+    pub const fn new(position: u32) -> Option<Self> {
+        if position < 12 || position >= 52 {
+            return None;
+        }
+
+        Some(Self(position))
+    }
+
+    /// Returns the bit position
+    #[inline]
+    pub const fn position(self) -> u32 {
+        self.0
+    }
+
+    /// Returns the mask selecting the bit in a page table entry
+    #[inline]
+    pub const fn mask(self) -> u64 {
+        1 << self.0
+    }
+}
+
+/// A page table entry
+#[derive(Copy, Clone, Default, PartialEq, Eq, Hash)]
+#[repr(transparent)]
+pub struct PageTableEntry(u64);
+
+impl core::fmt::Debug for PageTableEntry {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_tuple("PageTableEntry")
+            .field(&self.address())
+            .field(&self.flags())
+            .finish()
+    }
+}
+
+impl PageTableEntry {
+    /// The mask selecting the physical address bits of an entry
+    pub const ADDRESS_MASK: u64 = 0x000f_ffff_ffff_f000;
+
+    /// An unused (all zero) entry
+    pub const UNUSED: Self = Self(0);
+
+    /// Creates an entry referencing `addr` with the specified flags
+    #[inline]
+    pub const fn new(addr: Address<u64, Page>, flags: Flags) -> Self {
+        Self((addr.0 & Self::ADDRESS_MASK) | flags.0)
+    }
+
+    /// Creates an entry from its raw value
+    #[inline]
+    pub const fn from_raw(value: u64) -> Self {
+        Self(value)
+    }
+
+    /// Returns the raw value of the entry
+    #[inline]
+    pub const fn raw(self) -> u64 {
+        self.0
+    }
+
+    /// Returns the physical address bits of the entry
+    ///
+    /// Note that this includes the C-bit if it is set. See `Config::address`.
+    #[inline]
+    pub const fn address(self) -> Address<u64, Page> {
+        Address(self.0 & Self::ADDRESS_MASK, PhantomData)
+    }
+
+    /// Returns the flags of the entry
+    #[inline]
+    pub const fn flags(self) -> Flags {
+        Flags::from_bits_truncate(self.0)
+    }
+
+    /// Returns the entry with its flags replaced
+    #[inline]
+    pub const fn with_flags(self, flags: Flags) -> Self {
+        Self((self.0 & Self::ADDRESS_MASK) | flags.0)
+    }
+
+    /// Returns `true` if all bits of the entry are zero
+    #[inline]
+    pub const fn is_unused(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns `true` if the entry is present
+    #[inline]
+    pub const fn is_present(self) -> bool {
+        self.flags().contains(Flags::PRESENT)
+    }
+
+    /// Returns `true` if the mapped memory is writable
+    #[inline]
+    pub const fn is_writable(self) -> bool {
+        self.flags().contains(Flags::WRITABLE)
+    }
+
+    /// Returns `true` if the mapped memory is accessible from user mode
+    #[inline]
+    pub const fn is_user(self) -> bool {
+        self.flags().contains(Flags::USER)
+    }
+
+    /// Returns `true` if the entry maps a huge page
+    #[inline]
+    pub const fn is_huge(self) -> bool {
+        self.flags().contains(Flags::HUGE)
+    }
+
+    /// Returns `true` if the mapped memory is not executable
+    #[inline]
+    pub const fn is_no_execute(self) -> bool {
+        self.flags().contains(Flags::NO_EXECUTE)
+    }
+
+    /// Returns `true` if the C-bit is set in the entry
+    #[inline]
+    pub const fn is_encrypted(self, c_bit: CBit) -> bool {
+        self.0 & c_bit.mask() != 0
+    }
+
+    /// Returns the entry with the C-bit set
+    #[inline]
+    pub const fn encrypted(self, c_bit: CBit) -> Self {
+        Self(self.0 | c_bit.mask())
+    }
+}
+
+/// A level in the page table hierarchy
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// The page table, whose entries map 4 KiB pages
+    Pt = 1,
+
+    /// The page directory, whose entries may map 2 MiB pages
+    Pd = 2,
+
+    /// The page directory pointer table, whose entries may map 1 GiB pages
+    Pdpt = 3,
+
+    /// The page map level 4
+    Pml4 = 4,
+
+    /// The page map level 5, only used with 5-level paging
+    Pml5 = 5,
+}
+
+impl Level {
+    /// Returns the top level of the hierarchy for the paging mode
+    #[inline]
+    pub const fn root(paging: Paging) -> Self {
+        match paging {
+            Paging::Level4 => Self::Pml4,
+            Paging::Level5 => Self::Pml5,
+        }
+    }
+
+    /// Returns the next level down the hierarchy
+    #[inline]
+    pub const fn next(self) -> Option<Self> {
+        match self {
+            Self::Pml5 => Some(Self::Pml4),
+            Self::Pml4 => Some(Self::Pdpt),
+            Self::Pdpt => Some(Self::Pd),
+            Self::Pd => Some(Self::Pt),
+            Self::Pt => None,
+        }
+    }
+
+    /// Returns the position of the lowest virtual address bit indexing this level
+    #[inline]
+    pub const fn shift(self) -> u32 {
+        12 + 9 * (self as u32 - 1)
+    }
+
+    /// Returns the size of the memory region covered by one entry
+    #[inline]
+    pub const fn size(self) -> u64 {
+        1 << self.shift()
+    }
+
+    /// Returns `true` if entries at this level can map pages
+    #[inline]
+    pub const fn can_map(self) -> bool {
+        matches!(self, Self::Pt | Self::Pd | Self::Pdpt)
+    }
+
+    /// Returns the index into a table at this level for a virtual address
+    #[inline]
+    pub const fn index<U>(self, addr: Address<u64, U>) -> usize {
+        ((addr.0 >> self.shift()) as usize) % PageTable::ENTRIES
+    }
+}
+
+/// A page table
+///
+/// A page table is page-sized and page-aligned, and may be converted to
+/// and from a `Page`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C, align(4096))]
+pub struct PageTable([PageTableEntry; PageTable::ENTRIES]);
+
+impl Default for PageTable {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PageTable {
+    /// The number of entries in a page table
+    pub const ENTRIES: usize = 512;
+
+    /// Creates a page table with all entries unused
+    #[inline]
+    pub const fn new() -> Self {
+        Self([PageTableEntry::UNUSED; Self::ENTRIES])
+    }
+
+    /// Interprets a page as a page table
+    #[inline]
+    pub fn from_page(page: &Page) -> &Self {
+        // SAFETY: both types have the same size and alignment and every
+        // bit pattern is a valid page table.
+        unsafe { &*(page as *const Page as *const Self) }
+    }
+
+    /// Interprets a page as a mutable page table
+    #[inline]
+    pub fn from_page_mut(page: &mut Page) -> &mut Self {
+        // SAFETY: both types have the same size and alignment and every
+        // bit pattern is a valid page table and a valid page.
+        unsafe { &mut *(page as *mut Page as *mut Self) }
+    }
+
+    /// Returns an iterator over the entries
+    #[inline]
+    pub fn iter(&self) -> core::slice::Iter<'_, PageTableEntry> {
+        self.0.iter()
+    }
+
+    /// Returns an iterator over the entries which allows modifying them
+    #[inline]
+    pub fn iter_mut(&mut self) -> core::slice::IterMut<'_, PageTableEntry> {
+        self.0.iter_mut()
+    }
+}
+
+impl Index<usize> for PageTable {
+    type Output = PageTableEntry;
+
+    #[inline]
+    fn index(&self, index: usize) -> &Self::Output {
+        &self.0[index]
+    }
+}
+
+impl IndexMut<usize> for PageTable {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut Self::Output {
+        &mut self.0[index]
+    }
+}
+
+impl From<Page> for PageTable {
+    #[inline]
+    fn from(value: Page) -> Self {
+        *Self::from_page(&value)
+    }
+}
+
+impl From<PageTable> for Page {
+    #[inline]
+    fn from(value: PageTable) -> Self {
+        // SAFETY: both types have the same size and every bit pattern is a
+        // valid page.
+        unsafe { core::mem::transmute(value) }
+    }
+}
+
+impl AsRef<Page> for PageTable {
+    #[inline]
+    fn as_ref(&self) -> &Page {
+        // SAFETY: both types have the same size and alignment and every
+        // bit pattern is a valid page.
+        unsafe { &*(self as *const Self as *const Page) }
+    }
+}
+
+/// The page table format in use
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// The paging mode
+    pub paging: Paging,
+
+    /// The position of the SEV C-bit, if memory encryption is in use
+    pub c_bit: Option<CBit>,
+}
+
+impl Config {
+    /// Creates a configuration for the paging mode without memory encryption
+    #[inline]
+    pub const fn new(paging: Paging) -> Self {
+        Self {
+            paging,
+            c_bit: None,
+        }
+    }
+
+    /// Returns the configuration with the SEV C-bit at the specified position
+    #[inline]
+    pub const fn with_c_bit(self, c_bit: CBit) -> Self {
+        Self {
+            paging: self.paging,
+            c_bit: Some(c_bit),
+        }
+    }
+
+    /// Returns the physical address referenced by an entry, excluding the C-bit
+    #[inline]
+    pub const fn address(self, entry: PageTableEntry) -> Address<u64, Page> {
+        let mask = match self.c_bit {
+            Some(c_bit) => c_bit.mask(),
+            None => 0,
+        };
+
+        Address(entry.address().0 & !mask, PhantomData)
+    }
+
+    /// Returns `true` if the entry maps encrypted memory
+    #[inline]
+    pub const fn is_encrypted(self, entry: PageTableEntry) -> bool {
+        match self.c_bit {
+            Some(c_bit) => entry.is_encrypted(c_bit),
+            None => false,
+        }
+    }
+}
+
+/// The result of translating a virtual address
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Translation {
+    /// The physical address
+    pub address: Address<u64, ()>,
+
+    /// The level of the entry which mapped the address
+    ///
+    /// This determines the size of the mapped page (see `Level::size`).
+    pub level: Level,
+
+    /// The effective flags of the mapping
+    ///
+    /// These are the flags of the mapping entry, except that `WRITABLE` and
+    /// `USER` are only set if they are set at all levels and `NO_EXECUTE` is
+    /// set if it is set at any level.
+    pub flags: Flags,
+
+    /// Whether the mapped memory is encrypted
+    pub encrypted: bool,
+}
+
+/// An error encountered while walking the page tables
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WalkError {
+    /// The virtual address is not canonical
+    NonCanonical(Address<u64, ()>),
+
+    /// The entry at the specified level is not present
+    NotPresent(Level),
+
+    /// The entry at the specified level has the huge bit set where it is reserved
+    Reserved(Level),
+
+    /// A page table is located at a physical address which is not accessible
+    MissingTable(Address<u64, Page>),
+}
+
+/// A software page table walker
+pub struct Walker<'a, M: ?Sized> {
+    config: Config,
+    root: Address<u64, Page>,
+    memory: &'a M,
+}
+
+impl<'a, M: Frames + ?Sized> Walker<'a, M> {
+    /// Creates a walker for the hierarchy with the top-level table at `root`
+    #[inline]
+    pub fn new(config: Config, root: Address<u64, Page>, memory: &'a M) -> Self {
+        Self {
+            config,
+            root,
+            memory,
+        }
+    }
+
+    /// Returns the entry mapping `addr` along with its level
+    pub fn entry<U>(&self, addr: Address<u64, U>) -> Result<(PageTableEntry, Level), WalkError> {
+        self.descend(Address(addr.0, PhantomData), |_| ())
+    }
+
+    /// Translates a virtual address
+    pub fn walk<U>(&self, addr: Address<u64, U>) -> Result<Translation, WalkError> {
+        let addr: Address<u64, ()> = Address(addr.0, PhantomData);
+        let mut writable = true;
+        let mut user = true;
+        let mut nx = false;
+
+        let (entry, level) = self.descend(addr, |entry| {
+            writable &= entry.is_writable();
+            user &= entry.is_user();
+            nx |= entry.is_no_execute();
+        })?;
+
+        let mut flags = entry
+            .flags()
+            .difference(Flags::WRITABLE | Flags::USER | Flags::NO_EXECUTE);
+        if writable {
+            flags |= Flags::WRITABLE;
+        }
+        if user {
+            flags |= Flags::USER;
+        }
+        if nx {
+            flags |= Flags::NO_EXECUTE;
+        }
+
+        let mask = level.size() - 1;
+        let base = self.config.address(entry).0 & !mask;
+
+        Ok(Translation {
+            address: Address(base | (addr.0 & mask), PhantomData),
+            level,
+            flags,
+            encrypted: self.config.is_encrypted(entry),
+        })
+    }
+
+    /// Walks down the hierarchy, calling `visit` for each present entry
+    fn descend(
+        &self,
+        addr: Address<u64, ()>,
+        mut visit: impl FnMut(PageTableEntry),
+    ) -> Result<(PageTableEntry, Level), WalkError> {
+        if !addr.is_canonical(self.config.paging) {
+            return Err(WalkError::NonCanonical(addr));
+        }
+
+        let mut table = self.root;
+        let mut level = Level::root(self.config.paging);
+
+        loop {
+            let page = self
+                .memory
+                .frame(table)
+                .ok_or(WalkError::MissingTable(table))?;
+            let entry = PageTable::from_page(page)[level.index(addr)];
+
+            if !entry.is_present() {
+                return Err(WalkError::NotPresent(level));
+            }
+
+            if entry.is_huge() && !level.can_map() {
+                return Err(WalkError::Reserved(level));
+            }
+
+            visit(entry);
+
+            match level.next() {
+                Some(next) if !entry.is_huge() => {
+                    table = self.config.address(entry);
+                    level = next;
+                }
+
+                _ => return Ok((entry, level)),
+            }
+        }
+    }
+}
+
+impl<'a, M: Frames + ?Sized, D> Translate<Virtual, D, u64> for Walker<'a, M> {
+    #[inline]
+    fn translate(&self, addr: SpaceAddress<Virtual, u64, ()>) -> Option<SpaceAddress<D, u64, ()>> {
+        let translation = self.walk(addr.address()).ok()?;
+        Some(SpaceAddress::new(translation.address))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::testing::{frame, set};
+    use crate::{Contiguous, VirtAddr};
+
+    /// Builds a 4-level hierarchy with a 4 KiB, a 2 MiB and a 1 GiB mapping
+    fn hierarchy() -> [Page; 4] {
+        let table = Flags::PRESENT | Flags::WRITABLE | Flags::USER;
+        let mut pages = [Page::zeroed(); 4];
+
+        // PML4[1] -> PDPT
+        set(&mut pages, 0, 1, PageTableEntry::new(frame(1), table));
+
+        // PDPT[2] -> PD, PDPT[3] -> 1 GiB at 0xc000_0000
+        set(&mut pages, 1, 2, PageTableEntry::new(frame(2), table));
+        set(
+            &mut pages,
+            1,
+            3,
+            PageTableEntry::new(
+                Address::from(0xc000_0000u64).lower(),
+                Flags::PRESENT | Flags::HUGE | Flags::WRITABLE,
+            ),
+        );
+
+        // PD[4] -> PT, PD[5] -> 2 MiB at 0x60_0000
+        set(&mut pages, 2, 4, PageTableEntry::new(frame(3), table));
+        set(
+            &mut pages,
+            2,
+            5,
+            PageTableEntry::new(
+                Address::from(0x60_0000u64).lower(),
+                Flags::PRESENT | Flags::HUGE | Flags::NO_EXECUTE,
+            ),
+        );
+
+        // PT[6] -> 4 KiB at 0x7000
+        set(
+            &mut pages,
+            3,
+            6,
+            PageTableEntry::new(
+                Address::from(0x7000u64).lower(),
+                Flags::PRESENT | Flags::WRITABLE,
+            ),
+        );
+
+        pages
+    }
+
+    fn va(pml4: u64, pdpt: u64, pd: u64, pt: u64, offset: u64) -> Address<u64, ()> {
+        Address::from(pml4 << 39 | pdpt << 30 | pd << 21 | pt << 12 | offset)
+    }
+
+    #[test]
+    fn index() {
+        let addr = va(1, 2, 3, 4, 5);
+        assert_eq!(Level::Pml4.index(addr), 1);
+        assert_eq!(Level::Pdpt.index(addr), 2);
+        assert_eq!(Level::Pd.index(addr), 3);
+        assert_eq!(Level::Pt.index(addr), 4);
+        assert_eq!(Level::Pml5.index(Address::from(7u64 << 48)), 7);
+    }
+
+    #[test]
+    fn entry() {
+        let c_bit = CBit::new(47).unwrap();
+        let entry = PageTableEntry::new(Address::from(0x1234_5000u64).lower(), Flags::PRESENT)
+            .encrypted(c_bit);
+
+        assert!(entry.is_present());
+        assert!(!entry.is_writable());
+        assert!(entry.is_encrypted(c_bit));
+        assert_eq!(entry.address().raw(), 0x8000_1234_5000);
+        assert_eq!(entry.flags(), Flags::PRESENT);
+
+        let config = Config::new(Paging::Level4).with_c_bit(c_bit);
+        assert_eq!(config.address(entry).raw(), 0x1234_5000);
+        assert!(config.is_encrypted(entry));
+
+        assert_eq!(CBit::new(11), None);
+        assert_eq!(CBit::new(52), None);
+    }
+
+    #[test]
+    fn walk() {
+        let memory = Contiguous::new(frame(0), hierarchy());
+        let walker = Walker::new(Config::new(Paging::Level4), frame(0), &memory);
+
+        let t = walker.walk(va(1, 2, 4, 6, 0x123)).unwrap();
+        assert_eq!(t.address.raw(), 0x7123);
+        assert_eq!(t.level, Level::Pt);
+        assert_eq!(t.flags, Flags::PRESENT | Flags::WRITABLE);
+        assert!(!t.encrypted);
+
+        let t = walker.walk(va(1, 2, 5, 6, 0x123)).unwrap();
+        assert_eq!(t.address.raw(), 0x60_6123);
+        assert_eq!(t.level, Level::Pd);
+        assert_eq!(t.flags, Flags::PRESENT | Flags::HUGE | Flags::NO_EXECUTE);
+
+        let t = walker.walk(va(1, 3, 5, 6, 0x123)).unwrap();
+        assert_eq!(t.address.raw(), 0xc0a0_6123);
+        assert_eq!(t.level, Level::Pdpt);
+
+        assert_eq!(
+            walker.walk(va(1, 2, 4, 7, 0)),
+            Err(WalkError::NotPresent(Level::Pt))
+        );
+        assert_eq!(
+            walker.walk(va(2, 0, 0, 0, 0)),
+            Err(WalkError::NotPresent(Level::Pml4))
+        );
+        assert_eq!(
+            walker.walk(Address::from(0x8000_0000_0000u64)),
+            Err(WalkError::NonCanonical(Address::from(0x8000_0000_0000u64)))
+        );
+
+        let (entry, level) = walker.entry(va(1, 2, 5, 0, 0)).unwrap();
+        assert_eq!(level, Level::Pd);
+        assert!(entry.is_huge());
+    }
+
+    #[test]
+    fn missing_table() {
+        let mut pages = hierarchy();
+        set(
+            &mut pages,
+            0,
+            1,
+            PageTableEntry::new(frame(9), Flags::PRESENT),
+        );
+
+        let memory = Contiguous::new(frame(0), pages);
+        let walker = Walker::new(Config::new(Paging::Level4), frame(0), &memory);
+        assert_eq!(
+            walker.walk(va(1, 2, 4, 6, 0)),
+            Err(WalkError::MissingTable(frame(9)))
+        );
+    }
+
+    #[test]
+    fn reserved() {
+        let mut pages = hierarchy();
+        set(
+            &mut pages,
+            0,
+            0,
+            PageTableEntry::new(frame(1), Flags::PRESENT | Flags::HUGE),
+        );
+
+        let memory = Contiguous::new(frame(0), pages);
+        let walker = Walker::new(Config::new(Paging::Level4), frame(0), &memory);
+        assert_eq!(
+            walker.walk(va(0, 0, 0, 0, 0)),
+            Err(WalkError::Reserved(Level::Pml4))
+        );
+    }
+
+    #[test]
+    fn level5_encrypted() {
+        let c_bit = CBit::new(51).unwrap();
+        let flags = Flags::PRESENT | Flags::WRITABLE;
+        let mut pages = [Page::zeroed(); 5];
+
+        // PML5[0x1ff] -> PML4 and so on, all tables encrypted
+        set(
+            &mut pages,
+            0,
+            0x1ff,
+            PageTableEntry::new(frame(1), flags).encrypted(c_bit),
+        );
+        set(
+            &mut pages,
+            1,
+            0x1ff,
+            PageTableEntry::new(frame(2), flags).encrypted(c_bit),
+        );
+        set(
+            &mut pages,
+            2,
+            0x1ff,
+            PageTableEntry::new(frame(3), flags).encrypted(c_bit),
+        );
+        set(
+            &mut pages,
+            3,
+            0x1ff,
+            PageTableEntry::new(frame(4), flags).encrypted(c_bit),
+        );
+        set(
+            &mut pages,
+            4,
+            0x1ff,
+            PageTableEntry::new(Address::from(0xabc_d000u64).lower(), flags).encrypted(c_bit),
+        );
+
+        let memory = Contiguous::new(frame(0), pages);
+        let config = Config::new(Paging::Level5).with_c_bit(c_bit);
+        let walker = Walker::new(config, frame(0), &memory);
+
+        let t = walker
+            .walk(Address::from(0xffff_ffff_ffff_f008u64))
+            .unwrap();
+        assert_eq!(t.address.raw(), 0xabc_d008);
+        assert!(t.encrypted);
+
+        // Without the C-bit configured, the table addresses are bogus
+        let walker = Walker::new(Config::new(Paging::Level5), frame(0), &memory);
+        assert!(matches!(
+            walker.walk(Address::from(0xffff_ffff_ffff_f008u64)),
+            Err(WalkError::MissingTable(..))
+        ));
+
+        // And 4-level paging considers the address non-canonical
+        let walker = Walker::new(Config::new(Paging::Level4), frame(0), &memory);
+        assert!(walker
+            .walk(Address::from(0x00ff_ffff_ffff_f008u64))
+            .is_err());
+    }
+
+    #[test]
+    fn translate() {
+        let memory = Contiguous::new(frame(0), hierarchy());
+        let walker = Walker::new(Config::new(Paging::Level4), frame(0), &memory);
+
+        let va: VirtAddr<u64, u64> = VirtAddr::new(va(1, 2, 4, 6, 0x10)).lower();
+        let pa: crate::GuestPhysAddr<u64, u64> = va.translate(&walker).unwrap();
+        assert_eq!(pa.raw(), 0x7010);
+    }
+}
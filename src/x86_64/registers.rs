@@ -0,0 +1,950 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Control registers, flags and model-specific registers
+//!
+//! The types in this module are plain encodings of the register values and
+//! check the reserved bits. With the `asm` feature, which needs Rust 1.59,
+//! they can also be read and written on x86_64 hardware.
+
+use crate::{Address, Page, Register};
+
+/// An error produced when reserved bits are set or fixed bits are clear
+///
+/// The value holds the offending bits.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ReservedBits(pub u64);
+
+impl core::fmt::Display for ReservedBits {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "invalid reserved bits: {:#x}", self.0)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ReservedBits {}
+
+/// The mask of the physical address bits in CR3 and the APIC base
+const ADDRESS_MASK: u64 = 0x000f_ffff_ffff_f000;
+
+macro_rules! reserved {
+    ($($name:ident: $reserved:expr, $fixed:expr;)+) => {
+        $(
+            impl $name {
+                /// The bits which must be clear
+                pub const RESERVED: u64 = $reserved;
+
+                /// The bits which must be set
+                pub const FIXED: u64 = $fixed;
+
+                /// Returns `true` if no reserved bit is set and all fixed bits are
+                #[inline]
+                pub fn is_valid(self) -> bool {
+                    Self::try_from_bits(self.bits()).is_ok()
+                }
+
+                /// Creates the register from the raw bits, checking the reserved bits
+                #[inline]
+                pub fn try_from_bits(bits: u64) -> Result<Self, ReservedBits> {
+                    match bits & Self::RESERVED | !bits & Self::FIXED {
+                        0 => Ok(Self::from_bits(bits)),
+                        invalid => Err(ReservedBits(invalid)),
+                    }
+                }
+            }
+        )+
+    };
+}
+
+crate::bitfields! {
+    /// The CR0 control register
+    pub struct Cr0(u64) {
+        /// Protected mode enable
+        pe, set_pe: 0;
+
+        /// Monitor coprocessor
+        mp, set_mp: 1;
+
+        /// x87 emulation
+        em, set_em: 2;
+
+        /// Task switched
+        ts, set_ts: 3;
+
+        /// Extension type
+        et, set_et: 4;
+
+        /// Numeric error reporting
+        ne, set_ne: 5;
+
+        /// Write protect for supervisor accesses
+        wp, set_wp: 16;
+
+        /// Alignment mask
+        am, set_am: 18;
+
+        /// Not write-through
+        nw, set_nw: 29;
+
+        /// Cache disable
+        cd, set_cd: 30;
+
+        /// Paging
+        pg, set_pg: 31;
+    }
+}
+
+crate::bitfields! {
+    /// The CR3 control register
+    ///
+    /// The `pcid` field overlaps `pwt` and `pcd`; which one applies depends
+    /// on `Cr4::pcide`.
+    pub struct Cr3(u64) {
+        /// Page-level write-through
+        pwt, set_pwt: 3;
+
+        /// Page-level cache disable
+        pcd, set_pcd: 4;
+
+        /// The process-context identifier
+        pcid, set_pcid: 0..12;
+
+        /// Keeps the TLB entries of the PCID when written
+        no_flush, set_no_flush: 63;
+    }
+}
+
+impl Cr3 {
+    /// Creates a CR3 value pointing to the root page table at `root`
+    #[inline]
+    pub fn new(root: Address<u64, Page>) -> Self {
+        Self::from_bits(root.raw() & ADDRESS_MASK)
+    }
+
+    /// Returns the address of the root page table
+    #[inline]
+    pub fn address(self) -> Address<u64, Page> {
+        Address::from(self.bits() & ADDRESS_MASK).lower()
+    }
+
+    /// Sets the address of the root page table
+    #[inline]
+    pub fn set_address(&mut self, root: Address<u64, Page>) -> &mut Self {
+        *self = Self::from_bits(self.bits() & !ADDRESS_MASK | root.raw() & ADDRESS_MASK);
+        self
+    }
+}
+
+crate::bitfields! {
+    /// The CR4 control register
+    pub struct Cr4(u64) {
+        /// Virtual-8086 mode extensions
+        vme, set_vme: 0;
+
+        /// Protected-mode virtual interrupts
+        pvi, set_pvi: 1;
+
+        /// Time stamp disable
+        tsd, set_tsd: 2;
+
+        /// Debugging extensions
+        de, set_de: 3;
+
+        /// Page size extensions
+        pse, set_pse: 4;
+
+        /// Physical address extension
+        pae, set_pae: 5;
+
+        /// Machine check enable
+        mce, set_mce: 6;
+
+        /// Page global enable
+        pge, set_pge: 7;
+
+        /// Performance counter enable
+        pce, set_pce: 8;
+
+        /// `FXSAVE` and `FXRSTOR` support
+        osfxsr, set_osfxsr: 9;
+
+        /// Unmasked SIMD floating-point exceptions
+        osxmmexcpt, set_osxmmexcpt: 10;
+
+        /// User-mode instruction prevention
+        umip, set_umip: 11;
+
+        /// 5-level paging
+        la57, set_la57: 12;
+
+        /// VMX enable
+        vmxe, set_vmxe: 13;
+
+        /// SMX enable
+        smxe, set_smxe: 14;
+
+        /// `RDFSBASE` and friends
+        fsgsbase, set_fsgsbase: 16;
+
+        /// Process-context identifiers
+        pcide, set_pcide: 17;
+
+        /// `XSAVE` and processor extended states
+        osxsave, set_osxsave: 18;
+
+        /// Key locker
+        kl, set_kl: 19;
+
+        /// Supervisor mode execution prevention
+        smep, set_smep: 20;
+
+        /// Supervisor mode access prevention
+        smap, set_smap: 21;
+
+        /// Protection keys for user pages
+        pke, set_pke: 22;
+
+        /// Control-flow enforcement technology
+        cet, set_cet: 23;
+
+        /// Protection keys for supervisor pages
+        pks, set_pks: 24;
+
+        /// User interrupts
+        uintr, set_uintr: 25;
+
+        /// Linear address masking for supervisor pointers
+        lam_sup, set_lam_sup: 28;
+    }
+}
+
+crate::bitfields! {
+    /// The extended feature enable register (`IA32_EFER`)
+    pub struct Efer(u64) {
+        /// `SYSCALL` enable
+        sce, set_sce: 0;
+
+        /// Long mode enable
+        lme, set_lme: 8;
+
+        /// Long mode active
+        lma, set_lma: 10;
+
+        /// No-execute enable
+        nxe, set_nxe: 11;
+
+        /// Secure virtual machine enable
+        svme, set_svme: 12;
+
+        /// Long mode segment limit enable
+        lmsle, set_lmsle: 13;
+
+        /// Fast `FXSAVE` and `FXRSTOR`
+        ffxsr, set_ffxsr: 14;
+
+        /// Translation cache extension
+        tce, set_tce: 15;
+    }
+}
+
+crate::bitfields! {
+    /// The extended control register 0 (`XCR0`)
+    ///
+    /// Each bit enables an XSAVE state component.
+    pub struct Xcr0(u64) {
+        /// x87 state
+        x87, set_x87: 0;
+
+        /// SSE state
+        sse, set_sse: 1;
+
+        /// AVX state
+        avx, set_avx: 2;
+
+        /// MPX bound registers
+        bndregs, set_bndregs: 3;
+
+        /// MPX bound configuration and status
+        bndcsr, set_bndcsr: 4;
+
+        /// AVX-512 opmask registers
+        opmask, set_opmask: 5;
+
+        /// The upper halves of ZMM0-ZMM15
+        zmm_hi256, set_zmm_hi256: 6;
+
+        /// ZMM16-ZMM31
+        hi16_zmm, set_hi16_zmm: 7;
+
+        /// Protection key rights for user pages
+        pkru, set_pkru: 9;
+
+        /// AMX tile configuration
+        tilecfg, set_tilecfg: 17;
+
+        /// AMX tile data
+        tiledata, set_tiledata: 18;
+    }
+}
+
+impl Xcr0 {
+    /// Returns `true` if the enabled components are allowed together
+    ///
+    /// `XSETBV` requires that AVX comes with SSE, the AVX-512 components
+    /// come all together and with AVX, and that the MPX and AMX components
+    /// come in pairs.
+    pub fn is_consistent(self) -> bool {
+        let avx512 = [self.opmask(), self.zmm_hi256(), self.hi16_zmm()];
+
+        (!self.avx() || self.sse())
+            && (avx512 == [false; 3] || avx512 == [true; 3] && self.avx())
+            && self.bndregs() == self.bndcsr()
+            && self.tilecfg() == self.tiledata()
+    }
+}
+
+crate::bitfields! {
+    /// The flags register (`RFLAGS`)
+    ///
+    /// Bit 1 is reserved and always set in hardware.
+    pub struct Rflags(u64) {
+        /// Carry flag
+        carry, set_carry: 0;
+
+        /// Parity flag
+        parity, set_parity: 2;
+
+        /// Auxiliary carry flag
+        adjust, set_adjust: 4;
+
+        /// Zero flag
+        zero, set_zero: 6;
+
+        /// Sign flag
+        sign, set_sign: 7;
+
+        /// Trap flag
+        trap, set_trap: 8;
+
+        /// Interrupt enable flag
+        interrupt, set_interrupt: 9;
+
+        /// Direction flag
+        direction, set_direction: 10;
+
+        /// Overflow flag
+        overflow, set_overflow: 11;
+
+        /// I/O privilege level
+        iopl, set_iopl: 12..14;
+
+        /// Nested task
+        nested_task, set_nested_task: 14;
+
+        /// Resume flag
+        resume, set_resume: 16;
+
+        /// Virtual-8086 mode
+        virtual_8086, set_virtual_8086: 17;
+
+        /// Alignment check or access control
+        alignment_check, set_alignment_check: 18;
+
+        /// Virtual interrupt flag
+        virtual_interrupt, set_virtual_interrupt: 19;
+
+        /// Virtual interrupt pending
+        virtual_interrupt_pending, set_virtual_interrupt_pending: 20;
+
+        /// `CPUID` is supported, if the flag can be toggled
+        id, set_id: 21;
+    }
+}
+
+/// A model-specific register number
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Msr(pub u32);
+
+impl Msr {
+    /// The time stamp counter
+    pub const IA32_TSC: Msr = Msr(0x10);
+
+    /// The local APIC base, see `ApicBase`
+    pub const IA32_APIC_BASE: Msr = Msr(0x1b);
+
+    /// The feature control register
+    pub const IA32_FEATURE_CONTROL: Msr = Msr(0x3a);
+
+    /// The speculation control register
+    pub const IA32_SPEC_CTRL: Msr = Msr(0x48);
+
+    /// The `SYSENTER` code segment
+    pub const IA32_SYSENTER_CS: Msr = Msr(0x174);
+
+    /// The `SYSENTER` stack pointer
+    pub const IA32_SYSENTER_ESP: Msr = Msr(0x175);
+
+    /// The `SYSENTER` instruction pointer
+    pub const IA32_SYSENTER_EIP: Msr = Msr(0x176);
+
+    /// The miscellaneous feature enables
+    pub const IA32_MISC_ENABLE: Msr = Msr(0x1a0);
+
+    /// The page attribute table, see `Pat`
+    pub const IA32_PAT: Msr = Msr(0x277);
+
+    /// The supervisor state components of `XSAVES`
+    pub const IA32_XSS: Msr = Msr(0xda0);
+
+    /// The extended feature enables, see `Efer`
+    pub const IA32_EFER: Msr = Msr(0xc000_0080);
+
+    /// The `SYSCALL` and `SYSRET` segment selectors
+    pub const STAR: Msr = Msr(0xc000_0081);
+
+    /// The 64-bit `SYSCALL` target
+    pub const LSTAR: Msr = Msr(0xc000_0082);
+
+    /// The compatibility mode `SYSCALL` target
+    pub const CSTAR: Msr = Msr(0xc000_0083);
+
+    /// The `RFLAGS` mask applied by `SYSCALL`
+    pub const FMASK: Msr = Msr(0xc000_0084);
+
+    /// The FS segment base
+    pub const FS_BASE: Msr = Msr(0xc000_0100);
+
+    /// The GS segment base
+    pub const GS_BASE: Msr = Msr(0xc000_0101);
+
+    /// The GS segment base swapped in by `SWAPGS`
+    pub const KERNEL_GS_BASE: Msr = Msr(0xc000_0102);
+
+    /// The value returned by `RDTSCP` and `RDPID`
+    pub const TSC_AUX: Msr = Msr(0xc000_0103);
+
+    /// The GHCB physical address of an SEV-ES guest
+    pub const SEV_GHCB: Msr = Msr(0xc001_0130);
+
+    /// The SEV features active in the guest, see `SevStatus`
+    pub const SEV_STATUS: Msr = Msr(0xc001_0131);
+}
+
+crate::bitfields! {
+    /// The local APIC base register (`IA32_APIC_BASE`)
+    pub struct ApicBase(u64) {
+        /// The processor is the bootstrap processor
+        bsp, set_bsp: 8;
+
+        /// x2APIC mode is enabled
+        x2apic, set_x2apic: 10;
+
+        /// The local APIC is enabled
+        enable, set_enable: 11;
+    }
+}
+
+impl ApicBase {
+    /// Returns the physical address of the local APIC registers
+    #[inline]
+    pub fn address(self) -> Address<u64, Page> {
+        Address::from(self.bits() & ADDRESS_MASK).lower()
+    }
+
+    /// Sets the physical address of the local APIC registers
+    #[inline]
+    pub fn set_address(&mut self, base: Address<u64, Page>) -> &mut Self {
+        *self = Self::from_bits(self.bits() & !ADDRESS_MASK | base.raw() & ADDRESS_MASK);
+        self
+    }
+}
+
+crate::bitfields! {
+    /// The SEV features active in an AMD SEV guest (`SEV_STATUS`)
+    ///
+    /// This register is read-only.
+    pub struct SevStatus(u64) {
+        /// SEV is active
+        sev, set_sev: 0;
+
+        /// SEV-ES is active
+        sev_es, set_sev_es: 1;
+
+        /// SEV-SNP is active
+        snp, set_snp: 2;
+
+        /// The virtual top of memory is enabled
+        vtom, set_vtom: 3;
+
+        /// All `#VC` exceptions are reflected to the hypervisor
+        reflect_vc, set_reflect_vc: 4;
+
+        /// Only `#HV` may be injected
+        restricted_injection, set_restricted_injection: 5;
+
+        /// Interrupts are injected through the VMSA of another VMPL
+        alternate_injection, set_alternate_injection: 6;
+
+        /// Debug registers are swapped on world switches
+        debug_swap, set_debug_swap: 7;
+
+        /// The host may not use IBS on the guest
+        prevent_host_ibs, set_prevent_host_ibs: 8;
+
+        /// Branch target buffer isolation
+        btb_isolation, set_btb_isolation: 9;
+
+        /// Secure TSC
+        secure_tsc, set_secure_tsc: 11;
+
+        /// VMSA register protection
+        vmsa_reg_prot, set_vmsa_reg_prot: 16;
+    }
+}
+
+/// A memory type of the page attribute table
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum MemoryType {
+    /// Uncacheable (UC)
+    Uncacheable,
+
+    /// Write combining (WC)
+    WriteCombining,
+
+    /// Write-through (WT)
+    WriteThrough,
+
+    /// Write-protected (WP)
+    WriteProtected,
+
+    /// Write-back (WB)
+    WriteBack,
+
+    /// Uncacheable, overridable by MTRRs (UC-)
+    UncachedMinus,
+}
+
+impl MemoryType {
+    /// Decodes a memory type, if the encoding is valid
+    #[inline]
+    pub fn from_bits(bits: u8) -> Option<Self> {
+        match bits {
+            0 => Some(Self::Uncacheable),
+            1 => Some(Self::WriteCombining),
+            4 => Some(Self::WriteThrough),
+            5 => Some(Self::WriteProtected),
+            6 => Some(Self::WriteBack),
+            7 => Some(Self::UncachedMinus),
+            _ => None,
+        }
+    }
+
+    /// Returns the encoding of the memory type
+    #[inline]
+    pub fn bits(self) -> u8 {
+        match self {
+            Self::Uncacheable => 0,
+            Self::WriteCombining => 1,
+            Self::WriteThrough => 4,
+            Self::WriteProtected => 5,
+            Self::WriteBack => 6,
+            Self::UncachedMinus => 7,
+        }
+    }
+}
+
+/// The page attribute table (`IA32_PAT`)
+///
+/// The table has eight entries, which are selected by the PAT, PCD and PWT
+/// bits of a page table entry. The default is the power-on value.
+#[derive(Copy, Clone, PartialEq, Eq)]
+#[repr(transparent)]
+pub struct Pat(Register<u64>);
+
+impl Default for Pat {
+    #[inline]
+    fn default() -> Self {
+        Self::from_bits(0x0007_0406_0007_0406)
+    }
+}
+
+impl core::fmt::Debug for Pat {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_list()
+            .entries((0..Self::ENTRIES).map(|index| self.get(index)))
+            .finish()
+    }
+}
+
+impl From<Register<u64>> for Pat {
+    #[inline]
+    fn from(value: Register<u64>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<Pat> for Register<u64> {
+    #[inline]
+    fn from(value: Pat) -> Self {
+        value.0
+    }
+}
+
+impl Pat {
+    /// The number of entries
+    pub const ENTRIES: usize = 8;
+
+    /// The bits which must be clear
+    pub const RESERVED: u64 = 0xf8f8_f8f8_f8f8_f8f8;
+
+    /// Creates the table from the raw bits
+    #[inline]
+    pub fn from_bits(bits: u64) -> Self {
+        Self(Register::from(bits))
+    }
+
+    /// Returns the raw bits
+    #[inline]
+    pub fn bits(self) -> u64 {
+        self.0.into()
+    }
+
+    /// Returns the memory type of the entry at `index`, if it is valid
+    #[inline]
+    pub fn get(self, index: usize) -> Option<MemoryType> {
+        assert!(index < Self::ENTRIES, "invalid PAT index");
+        MemoryType::from_bits((self.bits() >> (index * 8)) as u8)
+    }
+
+    /// Sets the memory type of the entry at `index`
+    #[inline]
+    pub fn set(&mut self, index: usize, kind: MemoryType) -> &mut Self {
+        assert!(index < Self::ENTRIES, "invalid PAT index");
+        let shift = index as u32 * 8;
+        self.0.set_bits(shift..shift + 8, kind.bits().into());
+        self
+    }
+
+    /// Returns `true` if every entry holds a valid memory type
+    #[inline]
+    pub fn is_valid(self) -> bool {
+        Self::try_from_bits(self.bits()).is_ok()
+    }
+
+    /// Creates the table from the raw bits, checking the entries
+    ///
+    /// The error holds the bits of the invalid entries.
+    pub fn try_from_bits(bits: u64) -> Result<Self, ReservedBits> {
+        let invalid = (0..Self::ENTRIES)
+            .map(|index| 0xff << (index * 8))
+            .filter(|mask| {
+                MemoryType::from_bits(((bits & mask) >> mask.trailing_zeros()) as u8).is_none()
+            })
+            .fold(0, |invalid, mask| invalid | bits & mask);
+
+        match invalid {
+            0 => Ok(Self::from_bits(bits)),
+            invalid => Err(ReservedBits(invalid)),
+        }
+    }
+}
+
+reserved! {
+    Cr0: !0xe005_003f, 0;
+    Cr3: 0x1ff0_0000_0000_0000, 0;
+    Cr4: !0x13ff_7fff, 0;
+    Efer: !0xfd01, 0;
+    Xcr0: !0x6_02ff, 0x1;
+    Rflags: !0x3f_7fd7, 0x2;
+    ApicBase: !0x000f_ffff_ffff_fd00, 0;
+}
+
+#[cfg(all(feature = "asm", target_arch = "x86_64"))]
+mod hardware {
+    use super::*;
+    use core::arch::asm;
+
+    macro_rules! control {
+        ($($name:ident: $reg:literal;)+) => {
+            $(
+                impl $name {
+                    #[doc = concat!("Reads ", $reg, " from the processor")]
+                    ///
+                    /// # Safety
+                    /// This is only allowed at privilege level 0.
+                    #[inline]
+                    pub unsafe fn read() -> Self {
+                        let bits: u64;
+                        asm!(concat!("mov {}, ", $reg), out(reg) bits, options(nomem, nostack, preserves_flags));
+                        Self::from_bits(bits)
+                    }
+
+                    #[doc = concat!("Writes ", $reg, " to the processor")]
+                    ///
+                    /// # Safety
+                    /// This is only allowed at privilege level 0 and changes
+                    /// the processor state, which may break memory safety.
+                    #[inline]
+                    pub unsafe fn write(self) {
+                        asm!(concat!("mov ", $reg, ", {}"), in(reg) self.bits(), options(nostack, preserves_flags));
+                    }
+                }
+            )+
+        };
+    }
+
+    control! {
+        Cr0: "cr0";
+        Cr3: "cr3";
+        Cr4: "cr4";
+    }
+
+    macro_rules! model {
+        ($($name:ident: $msr:ident;)+) => {
+            $(
+                impl $name {
+                    #[doc = concat!("Reads `", stringify!($msr), "` from the processor")]
+                    ///
+                    /// # Safety
+                    /// This is only allowed at privilege level 0.
+                    #[inline]
+                    pub unsafe fn read() -> Self {
+                        Self::from(Msr::$msr.read())
+                    }
+
+                    #[doc = concat!("Writes `", stringify!($msr), "` to the processor")]
+                    ///
+                    /// # Safety
+                    /// This is only allowed at privilege level 0 and changes
+                    /// the processor state, which may break memory safety.
+                    #[inline]
+                    pub unsafe fn write(self) {
+                        Msr::$msr.write(self.into())
+                    }
+                }
+            )+
+        };
+    }
+
+    model! {
+        Efer: IA32_EFER;
+        Pat: IA32_PAT;
+        ApicBase: IA32_APIC_BASE;
+    }
+
+    impl SevStatus {
+        /// Reads `SEV_STATUS` from the processor
+        ///
+        /// # Safety
+        /// This is only allowed at privilege level 0 in an SEV guest.
+        #[inline]
+        pub unsafe fn read() -> Self {
+            Self::from(Msr::SEV_STATUS.read())
+        }
+    }
+
+    impl Msr {
+        /// Reads the MSR with `RDMSR`
+        ///
+        /// # Safety
+        /// This is only allowed at privilege level 0 and faults if the MSR
+        /// does not exist.
+        #[inline]
+        pub unsafe fn read(self) -> Register<u64> {
+            let (low, high): (u32, u32);
+            asm!("rdmsr", in("ecx") self.0, out("eax") low, out("edx") high, options(nomem, nostack, preserves_flags));
+            Register::from(u64::from(high) << 32 | u64::from(low))
+        }
+
+        /// Writes the MSR with `WRMSR`
+        ///
+        /// # Safety
+        /// This is only allowed at privilege level 0, faults if the MSR does
+        /// not exist or the value is invalid and may break memory safety.
+        #[inline]
+        pub unsafe fn write(self, value: Register<u64>) {
+            let value = u64::from(value);
+            asm!("wrmsr", in("ecx") self.0, in("eax") value as u32, in("edx") (value >> 32) as u32, options(nostack, preserves_flags));
+        }
+    }
+
+    impl Xcr0 {
+        /// Reads `XCR0` with `XGETBV`
+        ///
+        /// # Safety
+        /// `CR4.OSXSAVE` must be set.
+        #[inline]
+        pub unsafe fn read() -> Self {
+            let (low, high): (u32, u32);
+            asm!("xgetbv", in("ecx") 0, out("eax") low, out("edx") high, options(nomem, nostack, preserves_flags));
+            Self::from_bits(u64::from(high) << 32 | u64::from(low))
+        }
+
+        /// Writes `XCR0` with `XSETBV`
+        ///
+        /// # Safety
+        /// This is only allowed at privilege level 0 and faults unless the
+        /// value is valid, consistent and supported.
+        #[inline]
+        pub unsafe fn write(self) {
+            let value = self.bits();
+            asm!("xsetbv", in("ecx") 0, in("eax") value as u32, in("edx") (value >> 32) as u32, options(nostack, preserves_flags));
+        }
+    }
+
+    impl Rflags {
+        /// Reads `RFLAGS`
+        #[inline]
+        pub fn read() -> Self {
+            let bits: u64;
+            // SAFETY: reading the flags has no side effects.
+            unsafe { asm!("pushfq", "pop {}", out(reg) bits, options(nomem, preserves_flags)) };
+            Self::from_bits(bits)
+        }
+
+        /// Writes `RFLAGS`
+        ///
+        /// # Safety
+        /// Some flags, such as the interrupt and trap flags, change how code
+        /// is executed.
+        #[inline]
+        pub unsafe fn write(self) {
+            asm!("push {}", "popfq", in(reg) self.bits(), options(nomem));
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    extern crate std;
+
+    use super::*;
+    use crate::Register;
+    use std::format;
+
+    #[test]
+    fn cr0() {
+        let mut cr0 = Cr0::from_bits(0x8005_0033);
+        assert!(cr0.pe() && cr0.pg() && cr0.wp() && cr0.ne());
+        assert!(!cr0.cd());
+
+        cr0.set_cd(true).set_pe(false);
+        assert_eq!(cr0.bits(), 0xc005_0032);
+        assert_eq!(Register::from(cr0), Register::from(0xc005_0032u64));
+    }
+
+    #[test]
+    fn rflags() {
+        let mut rflags = Rflags::from(Register::from(0x0000_3202u64));
+        assert!(rflags.interrupt());
+        assert_eq!(rflags.iopl(), 3);
+
+        rflags.set_iopl(1).set_zero(true);
+        assert_eq!(rflags.bits(), 0x1242);
+    }
+
+    #[test]
+    fn reserved() {
+        assert!(Cr0::from_bits(0x8005_0033).is_valid());
+        assert_eq!(
+            Cr0::try_from_bits(0x1_8000_0001),
+            Err(ReservedBits(0x1_0000_0000))
+        );
+        assert_eq!(Cr4::try_from_bits(1 << 15), Err(ReservedBits(1 << 15)));
+        assert_eq!(Efer::try_from_bits(0x200), Err(ReservedBits(0x200)));
+        assert!(Efer::from_bits(0xd01).is_valid());
+
+        // RFLAGS bit 1 and XCR0 bit 0 must be set
+        assert_eq!(Rflags::try_from_bits(0x200), Err(ReservedBits(0x2)));
+        assert_eq!(Rflags::try_from_bits(0x22), Err(ReservedBits(0x20)));
+        assert!(Rflags::from_bits(0x202).is_valid());
+        assert_eq!(Xcr0::try_from_bits(0x6), Err(ReservedBits(0x1)));
+        assert_eq!(Xcr0::try_from_bits(0x107), Err(ReservedBits(0x100)));
+    }
+
+    #[test]
+    fn cr3() {
+        let root = Address::from(0x12_3456_7000u64).lower();
+        let mut cr3 = Cr3::new(root);
+        cr3.set_pcid(0x42).set_no_flush(true);
+
+        assert_eq!(cr3.bits(), 0x8000_0012_3456_7042);
+        assert_eq!(cr3.address(), root);
+        assert!(!cr3.pwt());
+        assert!(cr3.is_valid());
+
+        cr3.set_address(Address::from(0x1000u64).lower());
+        assert_eq!(cr3.bits(), 0x8000_0000_0000_1042);
+        assert!(!Cr3::from_bits(1 << 52).is_valid());
+    }
+
+    #[test]
+    fn xcr0() {
+        assert!(Xcr0::from_bits(0x7).is_consistent());
+        assert!(Xcr0::from_bits(0x2e7).is_consistent());
+        assert!(!Xcr0::from_bits(0x5).is_consistent());
+        assert!(!Xcr0::from_bits(0x27).is_consistent());
+        assert!(!Xcr0::from_bits(0xe3).is_consistent());
+        assert!(!Xcr0::from_bits(0x2_0007).is_consistent());
+    }
+
+    #[test]
+    fn msr() {
+        let mut base = ApicBase::from_bits(0xfee0_0900);
+        assert!(base.bsp() && base.enable() && !base.x2apic());
+        assert_eq!(base.address().raw(), 0xfee0_0000);
+        assert!(base.is_valid());
+
+        base.set_address(Address::from(0xfec0_0000u64).lower());
+        assert_eq!(base.bits(), 0xfec0_0900);
+        assert!(!ApicBase::from_bits(0xfee0_0001).is_valid());
+
+        let status = SevStatus::from_bits(0x7);
+        assert!(status.sev() && status.sev_es() && status.snp());
+        assert_eq!(Msr::IA32_EFER, Msr(0xc000_0080));
+    }
+
+    #[test]
+    fn pat() {
+        let mut pat = Pat::default();
+        assert_eq!(pat.get(0), Some(MemoryType::WriteBack));
+        assert_eq!(pat.get(3), Some(MemoryType::Uncacheable));
+        assert!(pat.is_valid());
+
+        pat.set(1, MemoryType::WriteCombining);
+        assert_eq!(pat.bits(), 0x0007_0406_0007_0106);
+
+        assert_eq!(
+            Pat::try_from_bits(0x0007_0406_0002_0406),
+            Err(ReservedBits(0x0002_0000))
+        );
+        assert_eq!(Pat::try_from_bits(0x10), Err(ReservedBits(0x10)));
+        assert_eq!(
+            format!("{:?}", Pat::from_bits(0x0201)),
+            "[Some(WriteCombining), None, Some(Uncacheable), Some(Uncacheable), \
+             Some(Uncacheable), Some(Uncacheable), Some(Uncacheable), Some(Uncacheable)]"
+        );
+    }
+
+    #[cfg(all(feature = "asm", target_arch = "x86_64"))]
+    #[test]
+    fn hardware() {
+        let rflags = Rflags::read();
+        assert!(rflags.is_valid());
+        assert!(rflags.interrupt());
+    }
+
+    #[test]
+    fn debug() {
+        let efer = Efer::from_bits(0xd01);
+        assert_eq!(
+            format!("{:?}", efer),
+            "Efer { sce: true, lme: true, lma: true, nxe: true, svme: false, \
+             lmsle: false, ffxsr: false, tce: false }"
+        );
+
+        let rflags = format!("{:?}", Rflags::from_bits(0x3000));
+        assert!(rflags.contains("iopl: 3,"));
+        assert!(rflags.starts_with("Rflags { carry: false"));
+        assert!(!format!("{:?}", Cr4::default()).contains("true"));
+    }
+}
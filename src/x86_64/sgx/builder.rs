@@ -0,0 +1,221 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::SecInfo;
+use crate::memmap::{CapacityError, Fixed, Region, RegionSet, Storage};
+use crate::{Address, Line, Offset, Page, Span};
+
+/// An error produced when describing the pages of an enclave
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuildError {
+    /// The pages are outside of the image
+    OutOfRange,
+
+    /// The storage has no room for more ranges
+    Capacity,
+}
+
+impl From<CapacityError> for BuildError {
+    #[inline]
+    fn from(_: CapacityError) -> Self {
+        Self::Capacity
+    }
+}
+
+impl core::fmt::Display for BuildError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::OutOfRange => write!(f, "pages outside of the enclave image"),
+            Self::Capacity => write!(f, "storage capacity exceeded"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BuildError {}
+
+/// A page to add to an enclave
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Record<'a> {
+    /// The address of the page in the enclave
+    pub address: Address<u64, Page>,
+
+    /// The contents of the page
+    pub page: &'a Page,
+
+    /// The security information for `EADD`
+    pub secinfo: SecInfo,
+
+    /// The page is measured with `EEXTEND`
+    pub measure: bool,
+}
+
+impl core::fmt::Debug for Record<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Record")
+            .field("address", &self.address)
+            .field("secinfo", &self.secinfo)
+            .field("measure", &self.measure)
+            .finish()
+    }
+}
+
+/// Describes the pages of an enclave image
+///
+/// The image is a sequence of pages loaded at `base`. Ranges of it are
+/// given their security information and measurement flag with `add()`,
+/// later ranges replacing earlier ones. Pages outside of every range are
+/// not added to the enclave.
+pub struct Builder<'a, S> {
+    base: Address<u64, Page>,
+    image: &'a [Page],
+    ranges: RegionSet<Page, (SecInfo, bool), S>,
+}
+
+/// A builder with a fixed capacity of `N` ranges
+pub type FixedBuilder<'a, const N: usize> = Builder<'a, Fixed<Region<Page, (SecInfo, bool)>, N>>;
+
+impl<'a, S: Storage<Region<Page, (SecInfo, bool)>> + Default> Builder<'a, S> {
+    /// Creates a builder for an image loaded at `base`
+    #[inline]
+    pub fn new(base: Address<u64, Page>, image: &'a [Page]) -> Self {
+        Self {
+            base,
+            image,
+            ranges: RegionSet::new(),
+        }
+    }
+}
+
+impl<'a, S: Storage<Region<Page, (SecInfo, bool)>>> Builder<'a, S> {
+    /// Returns the pages covered by the image
+    #[inline]
+    pub fn span(&self) -> Span<u64, Page> {
+        Span::new(self.base, Offset::from_items(self.image.len() as u64))
+    }
+
+    /// Adds the pages in `span` with their security information
+    ///
+    /// The pages must lie within the image, and must not reach the top of
+    /// the address space.
+    pub fn add(
+        &mut self,
+        span: Span<u64, Page>,
+        secinfo: SecInfo,
+        measure: bool,
+    ) -> Result<(), BuildError> {
+        let image = self.span();
+        let fits = span
+            .start
+            .checked_offset_from(image.start)
+            .and_then(|offset| offset.checked_add(span.count))
+            .map_or(false, |end| end <= image.count);
+        if !fits {
+            return Err(BuildError::OutOfRange);
+        }
+
+        let line = Line::try_from(span).map_err(|_| BuildError::OutOfRange)?;
+        self.ranges.insert(line, (secinfo, measure))?;
+        Ok(())
+    }
+
+    /// Returns the pages to add, in ascending order of address
+    pub fn records(&self) -> impl Iterator<Item = Record<'a>> + '_ {
+        let base = self.base;
+        let image = self.image;
+
+        self.ranges.iter().flat_map(move |region| {
+            let (secinfo, measure) = region.tag;
+            region.line.iter().map(move |address| {
+                let index = address.raw() - base.raw();
+                Record {
+                    address,
+                    page: &image[(index / Page::SIZE as u64) as usize],
+                    secinfo,
+                    measure,
+                }
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{Permissions, Tcs};
+    use super::*;
+
+    #[test]
+    fn builder() {
+        let tcs = Tcs::new(Offset::from_items(0x3000), Offset::from_items(1), 1);
+        let mut image = [Page::default(); 4];
+        image[0] = *tcs.as_ref();
+        image[3][0] = 0xc3;
+
+        let base = Address::from(0x10_0000u64).lower();
+        let span = |page: u64, count: u64| {
+            Span::new(
+                Address::from(0x10_0000 + page * 0x1000).lower(),
+                Offset::from_items(count),
+            )
+        };
+
+        let rw = SecInfo::reg(Permissions::READ | Permissions::WRITE);
+        let rx = SecInfo::reg(Permissions::READ | Permissions::EXECUTE);
+
+        let mut builder = FixedBuilder::<'_, 4>::new(base, &image);
+        builder.add(span(0, 1), SecInfo::tcs(), true).unwrap();
+        builder.add(span(1, 3), rw, false).unwrap();
+        builder.add(span(3, 1), rx, true).unwrap();
+
+        let records: [_; 4] = {
+            let mut records = builder.records();
+            [(); 4].map(|_| records.next().unwrap())
+        };
+        assert_eq!(builder.records().count(), 4);
+
+        assert_eq!(records[0].address, base);
+        assert_eq!(records[0].secinfo, SecInfo::tcs());
+        assert_eq!(Tcs::from(*records[0].page), tcs);
+
+        assert_eq!(records[2].address.raw(), 0x10_2000);
+        assert_eq!(records[2].secinfo, rw);
+        assert!(!records[2].measure);
+
+        assert_eq!(records[3].secinfo, rx);
+        assert_eq!(records[3].page[0], 0xc3);
+        assert!(records[3].measure);
+
+        assert_eq!(
+            builder.add(span(3, 2), rw, false),
+            Err(BuildError::OutOfRange)
+        );
+        assert_eq!(
+            builder.add(span(3, u64::MAX), rw, false),
+            Err(BuildError::OutOfRange)
+        );
+
+        let top = Span::new(
+            Address::from(0xffff_ffff_ffff_f000u64).lower(),
+            Offset::from_items(1),
+        );
+        assert_eq!(builder.add(top, rw, false), Err(BuildError::OutOfRange));
+    }
+
+    #[test]
+    fn gaps() {
+        let image = [Page::default(); 8];
+        let base = Address::from(0u64).lower();
+        let mut builder = FixedBuilder::<'_, 1>::new(base, &image);
+
+        let first = Span::new(Address::from(0x1000u64).lower(), Offset::from_items(2));
+        let second = Span::new(Address::from(0x5000u64).lower(), Offset::from_items(1));
+        let rw = SecInfo::reg(Permissions::READ | Permissions::WRITE);
+
+        builder.add(first, rw, true).unwrap();
+        assert_eq!(builder.add(second, rw, false), Err(BuildError::Capacity));
+        assert_eq!(builder.span().count, Offset::from_items(8));
+
+        let addresses = builder.records().map(|record| record.address.raw());
+        assert!(addresses.eq([0x1000, 0x2000]));
+    }
+}
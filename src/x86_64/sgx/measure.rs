@@ -0,0 +1,215 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{BuildError, Record, SecInfo, Secs};
+use crate::{Address, Page};
+
+use sha2::{Digest, Sha256};
+
+/// The software equivalent of the enclave measurement (MRENCLAVE)
+///
+/// The processor measures an enclave by hashing a 64-byte block for every
+/// `ECREATE`, `EADD` and `EEXTEND` with SHA-256. This replays the same
+/// blocks, so the measurement of an enclave can be computed without SGX
+/// hardware. All addresses are measured relative to the enclave base and
+/// must lie inside the enclave described by the `Secs`.
+#[derive(Clone)]
+pub struct Measurement {
+    sha: Sha256,
+    base: u64,
+    size: u64,
+}
+
+impl core::fmt::Debug for Measurement {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Measurement")
+            .field("base", &format_args!("{:#x}", self.base))
+            .field("size", &format_args!("{:#x}", self.size))
+            .finish()
+    }
+}
+
+impl Measurement {
+    /// The number of bytes measured by one `EEXTEND`
+    pub const CHUNK: usize = 256;
+
+    /// Starts the measurement of an enclave, as `ECREATE` does
+    pub fn new(secs: &Secs) -> Self {
+        let mut block = [0; 64];
+        block[..8].copy_from_slice(b"ECREATE\0");
+        block[8..12].copy_from_slice(&secs.ssa_frame_size.to_le_bytes());
+        block[12..20].copy_from_slice(&secs.size.to_le_bytes());
+
+        Self {
+            sha: Sha256::new_with_prefix(block),
+            base: secs.base,
+            size: secs.size,
+        }
+    }
+
+    /// Returns the offset of `len` bytes at `address` in the enclave
+    fn offset(&self, address: u64, len: u64) -> Result<u64, BuildError> {
+        match address.checked_sub(self.base) {
+            Some(offset) if offset < self.size && len <= self.size - offset => Ok(offset),
+            _ => Err(BuildError::OutOfRange),
+        }
+    }
+
+    /// Measures the addition of a page, as `EADD` does
+    ///
+    /// Fails if the page is outside of the enclave.
+    pub fn add(
+        &mut self,
+        address: Address<u64, Page>,
+        secinfo: &SecInfo,
+    ) -> Result<(), BuildError> {
+        let offset = self.offset(address.raw(), Page::SIZE as u64)?;
+
+        let mut block = [0; 64];
+        block[..8].copy_from_slice(b"EADD\0\0\0\0");
+        block[8..16].copy_from_slice(&offset.to_le_bytes());
+        block[16..].copy_from_slice(&secinfo.as_bytes()[..48]);
+        self.sha.update(block);
+        Ok(())
+    }
+
+    /// Measures a 256-byte chunk of a page, as `EEXTEND` does
+    ///
+    /// Fails if the chunk is outside of the enclave.
+    pub fn extend(
+        &mut self,
+        address: Address<u64, ()>,
+        chunk: &[u8; Self::CHUNK],
+    ) -> Result<(), BuildError> {
+        let offset = self.offset(address.raw(), Self::CHUNK as u64)?;
+
+        let mut block = [0; 64];
+        block[..8].copy_from_slice(b"EEXTEND\0");
+        block[8..16].copy_from_slice(&offset.to_le_bytes());
+        self.sha.update(block);
+        self.sha.update(chunk);
+        Ok(())
+    }
+
+    /// Measures a page added by the `Builder`
+    ///
+    /// The page is added and, if requested, measured in full. Fails if the
+    /// page is outside of the enclave, which happens when the `Builder` and
+    /// the `Secs` disagree on the enclave base.
+    pub fn page(&mut self, record: &Record<'_>) -> Result<(), BuildError> {
+        self.add(record.address, &record.secinfo)?;
+
+        if record.measure {
+            let start = record.address.raw();
+            for (offset, chunk) in (0..)
+                .step_by(Self::CHUNK)
+                .zip(record.page.chunks_exact(Self::CHUNK))
+            {
+                let mut bytes = [0; Self::CHUNK];
+                bytes.copy_from_slice(chunk);
+                self.extend(Address::from(start + offset), &bytes)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the measurement (MRENCLAVE)
+    #[inline]
+    pub fn finish(self) -> [u8; 32] {
+        self.sha.finalize().into()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::super::{FixedBuilder, Permissions, Tcs};
+    use super::*;
+    use crate::{Offset, Span};
+
+    fn span(start: u64, count: u64) -> Span<u64, Page> {
+        Span::new(Address::from(start).lower(), Offset::from_items(count))
+    }
+
+    #[test]
+    fn empty() {
+        // SHA-256 of the ECREATE block alone
+        let secs = Secs::new(span(0, 0x10), 1);
+        assert_eq!(
+            Measurement::new(&secs).finish(),
+            [
+                0x03, 0xc7, 0x99, 0x54, 0x89, 0x5f, 0xe9, 0x8c, 0x9b, 0xd1, 0xb8, 0x6e, 0xbe, 0x8b,
+                0x7b, 0x17, 0x48, 0xb6, 0x5b, 0xfc, 0x30, 0x08, 0x5d, 0xd3, 0x87, 0xcb, 0x43, 0x51,
+                0xe7, 0xde, 0x51, 0xe2,
+            ]
+        );
+    }
+
+    #[test]
+    fn enclave() {
+        let base = 0x10_0000_0000;
+        let secs = Secs::new(span(base, 0x20), 1);
+
+        let tcs = Tcs::new(Offset::from_items(0x2000), Offset::from_items(4), 1);
+        let mut image = [Page::default(); 5];
+        image[0] = *tcs.as_ref();
+        for (i, byte) in image[1].iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+        image[2][..4].copy_from_slice(&[0x0f, 0x01, 0xd7, 0xf4]);
+
+        let text = SecInfo::reg(Permissions::READ | Permissions::EXECUTE);
+        let data = SecInfo::reg(Permissions::READ | Permissions::WRITE);
+
+        let mut builder = FixedBuilder::<'_, 4>::new(Address::from(base).lower(), &image);
+        builder.add(span(base, 1), SecInfo::tcs(), true).unwrap();
+        builder.add(span(base + 0x1000, 2), text, true).unwrap();
+        builder.add(span(base + 0x3000, 2), data, false).unwrap();
+
+        let mut measurement = Measurement::new(&secs);
+        for record in builder.records() {
+            measurement.page(&record).unwrap();
+        }
+
+        // Computed independently from the instruction pseudocode
+        assert_eq!(
+            measurement.finish(),
+            [
+                0x84, 0xc7, 0xdd, 0x7f, 0x2a, 0x46, 0xcb, 0x8b, 0x9b, 0xe2, 0xf7, 0x9b, 0xc3, 0x4d,
+                0x29, 0xbe, 0xc3, 0x25, 0x43, 0x7c, 0xf8, 0x48, 0x51, 0x0d, 0x43, 0x68, 0x8e, 0xe4,
+                0x36, 0xe5, 0x34, 0x67,
+            ]
+        );
+    }
+
+    #[test]
+    fn out_of_range() {
+        let base = 0x10_0000_0000;
+        let secs = Secs::new(span(base, 0x10), 1);
+        let mut measurement = Measurement::new(&secs);
+        let secinfo = SecInfo::reg(Permissions::READ);
+
+        assert_eq!(
+            measurement.add(Address::from(base - 0x1000).lower(), &secinfo),
+            Err(BuildError::OutOfRange)
+        );
+        assert_eq!(
+            measurement.add(Address::from(base + 0x10000).lower(), &secinfo),
+            Err(BuildError::OutOfRange)
+        );
+        assert_eq!(
+            measurement.extend(Address::from(base + 0xff80), &[0; Measurement::CHUNK]),
+            Err(BuildError::OutOfRange)
+        );
+        assert_eq!(
+            measurement.add(Address::from(base + 0xf000).lower(), &secinfo),
+            Ok(())
+        );
+
+        // Rejected operations are not measured
+        let mut expected = Measurement::new(&secs);
+        expected
+            .add(Address::from(base + 0xf000).lower(), &secinfo)
+            .unwrap();
+        assert_eq!(measurement.finish(), expected.finish());
+    }
+}
@@ -0,0 +1,218 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! Intel SGX enclave structures
+//!
+//! An SGX enclave is created from a `Secs` page (`ECREATE`) and filled page
+//! by page (`EADD`), each page with a `SecInfo` giving its type and
+//! permissions. Pages may additionally be measured (`EEXTEND`). The
+//! `Builder` turns a page image and per-range page metadata into the
+//! sequence of pages to add, so the loader only has to issue the
+//! instructions.
+//!
+//! With the `sha2` feature, `Measurement` computes the enclave measurement
+//! (MRENCLAVE) of those pages in software.
+
+mod builder;
+#[cfg(feature = "sha2")]
+mod measure;
+mod secs;
+mod tcs;
+
+pub use builder::{BuildError, Builder, FixedBuilder, Record};
+#[cfg(feature = "sha2")]
+pub use measure::Measurement;
+pub use secs::{Attributes, MiscSelect, Secs};
+pub use tcs::{Tcs, TcsFlags};
+
+flags! {
+    /// The access permissions of an enclave page
+    pub struct Permissions(u8) {
+        /// The page is readable
+        READ = 0;
+
+        /// The page is writable
+        WRITE = 1;
+
+        /// The page is executable
+        EXECUTE = 2;
+    }
+}
+
+impl Permissions {
+    /// Creates permissions from raw bits, discarding unknown bits
+    #[inline]
+    pub const fn from_bits_truncate(bits: u8) -> Self {
+        Self(bits & 0b111)
+    }
+}
+
+flags! {
+    /// The status of an enclave page, as kept in its security information
+    pub struct Status {
+        /// The page is pending acceptance (`EAUG`)
+        PENDING = 3;
+
+        /// The page permissions or type are being modified
+        MODIFIED = 4;
+
+        /// The page permissions are being restricted
+        PR = 5;
+    }
+}
+
+impl Status {
+    /// Creates a status from raw SECINFO flags, discarding the other fields
+    #[inline]
+    pub const fn from_bits_truncate(bits: u64) -> Self {
+        Self(bits & 0b11_1000)
+    }
+}
+
+/// The type of an enclave page
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Class {
+    /// The SGX enclave control structure
+    Secs = 0,
+
+    /// A thread control structure
+    Tcs = 1,
+
+    /// A regular page
+    Reg = 2,
+
+    /// A version array
+    Va = 3,
+
+    /// A page being removed
+    Trim = 4,
+}
+
+impl Class {
+    /// Returns the page type with the given value, if it is known
+    #[inline]
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        Some(match bits {
+            0 => Self::Secs,
+            1 => Self::Tcs,
+            2 => Self::Reg,
+            3 => Self::Va,
+            4 => Self::Trim,
+            _ => return None,
+        })
+    }
+}
+
+/// The security information of an enclave page (SECINFO)
+///
+/// This is the type and the permissions handed to `EADD`, `EACCEPT` and
+/// related instructions. TCS pages carry no permissions.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[repr(C, align(64))]
+pub struct SecInfo {
+    flags: u64,
+    reserved: [u64; 7],
+}
+
+impl SecInfo {
+    /// Creates the security information of a page
+    #[inline]
+    pub const fn new(class: Class, permissions: Permissions) -> Self {
+        Self {
+            flags: (class as u64) << 8 | permissions.bits() as u64,
+            reserved: [0; 7],
+        }
+    }
+
+    /// Creates the security information of a regular page
+    #[inline]
+    pub const fn reg(permissions: Permissions) -> Self {
+        Self::new(Class::Reg, permissions)
+    }
+
+    /// Creates the security information of a thread control structure
+    #[inline]
+    pub const fn tcs() -> Self {
+        Self::new(Class::Tcs, Permissions::empty())
+    }
+
+    /// Returns the security information with its status replaced
+    #[inline]
+    pub const fn with_status(self, status: Status) -> Self {
+        Self {
+            flags: (self.flags & !Status::from_bits_truncate(u64::MAX).bits()) | status.bits(),
+            reserved: self.reserved,
+        }
+    }
+
+    /// Returns the raw flags
+    #[inline]
+    pub const fn flags(&self) -> u64 {
+        self.flags
+    }
+
+    /// Returns the page type, if it is known
+    #[inline]
+    pub const fn class(&self) -> Option<Class> {
+        Class::from_bits((self.flags >> 8) as u8)
+    }
+
+    /// Returns the permissions
+    #[inline]
+    pub const fn permissions(&self) -> Permissions {
+        Permissions::from_bits_truncate(self.flags as u8)
+    }
+
+    /// Returns the status
+    #[inline]
+    pub const fn status(&self) -> Status {
+        Status::from_bits_truncate(self.flags)
+    }
+
+    /// Returns the SECINFO as bytes, as it is measured by `EADD`
+    #[inline]
+    pub fn as_bytes(&self) -> [u8; 64] {
+        let mut bytes = [0; 64];
+        bytes[..8].copy_from_slice(&self.flags.to_le_bytes());
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use core::mem::{align_of, size_of};
+
+    #[test]
+    fn secinfo() {
+        assert_eq!(size_of::<SecInfo>(), 64);
+        assert_eq!(align_of::<SecInfo>(), 64);
+
+        let text = SecInfo::reg(Permissions::READ | Permissions::EXECUTE);
+        assert_eq!(text.flags(), 0x205);
+        assert_eq!(text.class(), Some(Class::Reg));
+        assert!(text.permissions().contains(Permissions::EXECUTE));
+        assert!(!text.permissions().contains(Permissions::WRITE));
+        assert_eq!(text.as_bytes()[..2], [0x05, 0x02]);
+
+        let tcs = SecInfo::tcs();
+        assert_eq!(tcs.flags(), 0x100);
+        assert_eq!(tcs.permissions(), Permissions::empty());
+
+        assert_eq!(
+            SecInfo::new(Class::Trim, Permissions::empty()).flags(),
+            0x400
+        );
+        assert_eq!(Class::from_bits(5), None);
+
+        let pending = text.with_status(Status::PENDING | Status::PR);
+        assert_eq!(pending.flags(), 0x22d);
+        assert_eq!(pending.status(), Status::PENDING | Status::PR);
+        assert_eq!(pending.permissions(), text.permissions());
+        assert_eq!(pending.with_status(Status::empty()), text);
+        assert_eq!(text.status(), Status::empty());
+        assert_eq!(
+            Permissions::from_bits_truncate(0xff),
+            Permissions::READ | Permissions::WRITE | Permissions::EXECUTE
+        );
+    }
+}
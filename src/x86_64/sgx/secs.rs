@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::x86_64::registers::Xcr0;
+use crate::{Offset, Page, Span};
+
+crate::bitfields! {
+    /// The attributes of an enclave
+    pub struct Attributes(u64) {
+        /// The enclave has been initialized (`EINIT`)
+        init, set_init: 0;
+
+        /// The enclave may be debugged
+        debug, set_debug: 1;
+
+        /// The enclave runs in 64-bit mode
+        mode64bit, set_mode64bit: 2;
+
+        /// The enclave may request the provisioning key
+        provision_key, set_provision_key: 4;
+
+        /// The enclave may request the launch token key
+        einittoken_key, set_einittoken_key: 5;
+
+        /// Control-flow enforcement is enabled
+        cet, set_cet: 6;
+
+        /// Key separation and sharing is enabled
+        kss, set_kss: 7;
+
+        /// Threads are notified of asynchronous exits
+        aex_notify, set_aex_notify: 10;
+    }
+}
+
+crate::bitfields! {
+    /// The extended information saved in the SSA on exceptions
+    pub struct MiscSelect(u32) {
+        /// Page fault and protection exception information
+        exinfo, set_exinfo: 0;
+
+        /// Control protection exception information
+        cpinfo, set_cpinfo: 1;
+    }
+}
+
+/// The SGX enclave control structure (SECS)
+///
+/// This is the page handed to `ECREATE`. It describes the address range,
+/// the attributes and the identity of the enclave.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C, align(4096))]
+pub struct Secs {
+    /// The size of the enclave in bytes, a power of two
+    pub size: u64,
+
+    /// The base address of the enclave, aligned to its size
+    pub base: u64,
+
+    /// The size of a state save area frame in pages
+    pub ssa_frame_size: u32,
+
+    /// The extended information saved in the SSA
+    pub misc_select: MiscSelect,
+
+    reserved0: [u8; 24],
+
+    /// The attributes
+    pub attributes: Attributes,
+
+    /// The XSAVE feature request mask
+    pub xfrm: Xcr0,
+
+    /// The enclave measurement, filled by the processor
+    pub mrenclave: [u8; 32],
+
+    reserved1: [u8; 32],
+
+    /// The hash of the signer key, filled by the processor
+    pub mrsigner: [u8; 32],
+
+    reserved2: [u8; 32],
+
+    /// The configuration identifier (with `Attributes::kss`)
+    pub config_id: [u8; 64],
+
+    /// The product identifier
+    pub isv_prod_id: u16,
+
+    /// The security version number
+    pub isv_svn: u16,
+
+    /// The configuration security version number
+    pub config_svn: u16,
+
+    reserved3: [u8; 3834],
+}
+
+impl Secs {
+    /// Creates the SECS of a 64-bit enclave covering `span`
+    ///
+    /// The XSAVE feature request mask enables the x87 and SSE state.
+    pub fn new(span: Span<u64, Page>, ssa_frame_size: u32) -> Self {
+        let mut attributes = Attributes::default();
+        attributes.set_mode64bit(true);
+
+        let mut xfrm = Xcr0::default();
+        xfrm.set_x87(true).set_sse(true);
+
+        Self {
+            size: span.count.bytes(),
+            base: span.start.raw(),
+            ssa_frame_size,
+            misc_select: MiscSelect::default(),
+            reserved0: [0; 24],
+            attributes,
+            xfrm,
+            mrenclave: [0; 32],
+            reserved1: [0; 32],
+            mrsigner: [0; 32],
+            reserved2: [0; 32],
+            config_id: [0; 64],
+            isv_prod_id: 0,
+            isv_svn: 0,
+            config_svn: 0,
+            reserved3: [0; 3834],
+        }
+    }
+
+    /// Returns the pages of the enclave
+    #[inline]
+    pub fn span(&self) -> Span<u64, Page> {
+        Span::new(
+            crate::Address::from(self.base).lower(),
+            Offset::from_items(self.size >> 12),
+        )
+    }
+
+    /// Returns `true` if the size is a power of two and the base is aligned
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.size.is_power_of_two() && self.size >= 2 << 12 && self.base % self.size == 0
+    }
+}
+
+page_overlay!(Secs);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Address;
+
+    #[test]
+    fn secs() {
+        let span = Span::new(
+            Address::from(0x8000_0000u64).lower(),
+            Offset::from_items(0x1000),
+        );
+
+        let mut secs = Secs::new(span, 1);
+        assert!(secs.is_valid());
+        assert_eq!(secs.span(), span);
+        assert_eq!(secs.size, 0x100_0000);
+        assert_eq!(secs.attributes.bits(), 0x4);
+        assert_eq!(secs.xfrm.bits(), 0x3);
+
+        secs.attributes.set_debug(true);
+        secs.isv_svn = 2;
+
+        let page: &Page = secs.as_ref();
+        assert_eq!(page[..8], 0x100_0000u64.to_le_bytes());
+        assert_eq!(page[8..16], 0x8000_0000u64.to_le_bytes());
+        assert_eq!(page[16..20], 1u32.to_le_bytes());
+        assert_eq!(page[48], 0x6);
+        assert_eq!(page[56], 0x3);
+        assert_eq!(page[258], 2);
+        assert_eq!(Secs::from(*page), secs);
+
+        secs.base += 0x1000;
+        assert!(!secs.is_valid());
+    }
+}
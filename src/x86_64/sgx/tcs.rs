@@ -0,0 +1,96 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{Offset, Page};
+
+crate::bitfields! {
+    /// The flags of a thread control structure
+    pub struct TcsFlags(u64) {
+        /// Debug exceptions are delivered inside a debug enclave
+        dbgoptin, set_dbgoptin: 0;
+
+        /// The thread is notified of asynchronous exits
+        aex_notify, set_aex_notify: 1;
+    }
+}
+
+/// The thread control structure (TCS)
+///
+/// Every thread entering an enclave does so through a TCS page. The offsets
+/// are relative to the enclave base.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C, align(4096))]
+pub struct Tcs {
+    state: u64,
+
+    /// The flags
+    pub flags: TcsFlags,
+
+    /// The offset of the first state save area frame
+    pub ossa: u64,
+
+    /// The current state save area frame, maintained by the processor
+    pub cssa: u32,
+
+    /// The number of state save area frames
+    pub nssa: u32,
+
+    /// The offset of the entry point
+    pub oentry: u64,
+
+    aep: u64,
+
+    /// The offset of the FS base
+    pub ofsbase: u64,
+
+    /// The offset of the GS base
+    pub ogsbase: u64,
+
+    /// The FS limit, ignored in 64-bit mode
+    pub fslimit: u32,
+
+    /// The GS limit, ignored in 64-bit mode
+    pub gslimit: u32,
+
+    reserved: [u8; 4024],
+}
+
+impl Tcs {
+    /// Creates a TCS entering at `entry` with `nssa` frames at `ssa`
+    pub fn new(entry: Offset<u64, ()>, ssa: Offset<u64, Page>, nssa: u32) -> Self {
+        Self {
+            state: 0,
+            flags: TcsFlags::default(),
+            ossa: ssa.bytes(),
+            cssa: 0,
+            nssa,
+            oentry: entry.bytes(),
+            aep: 0,
+            ofsbase: 0,
+            ogsbase: 0,
+            fslimit: 0xfff,
+            gslimit: 0xfff,
+            reserved: [0; 4024],
+        }
+    }
+}
+
+page_overlay!(Tcs);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn tcs() {
+        let mut tcs = Tcs::new(Offset::from_items(0x1234), Offset::from_items(2), 3);
+        tcs.flags.set_dbgoptin(true);
+
+        let page: &Page = tcs.as_ref();
+        assert_eq!(page[8], 1);
+        assert_eq!(page[16..24], 0x2000u64.to_le_bytes());
+        assert_eq!(page[28..32], 3u32.to_le_bytes());
+        assert_eq!(page[32..40], 0x1234u64.to_le_bytes());
+        assert_eq!(page[64..72], [0xff, 0xf, 0, 0, 0xff, 0xf, 0, 0]);
+        assert_eq!(Tcs::from(*page), tcs);
+    }
+}
@@ -0,0 +1,158 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::memmap::CapacityError;
+use crate::x86_64::cpuid::{Cpuid, Source};
+use crate::Page;
+
+/// A `CPUID` result in the CPUID page
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct CpuidFunction {
+    /// The leaf (EAX input)
+    pub eax_in: u32,
+
+    /// The subleaf (ECX input)
+    pub ecx_in: u32,
+
+    /// The XCR0 input, for the XSAVE leaf
+    pub xcr0_in: u64,
+
+    /// The XSS input, for the XSAVE leaf
+    pub xss_in: u64,
+
+    /// The value of EAX
+    pub eax: u32,
+
+    /// The value of EBX
+    pub ebx: u32,
+
+    /// The value of ECX
+    pub ecx: u32,
+
+    /// The value of EDX
+    pub edx: u32,
+
+    reserved: u64,
+}
+
+impl CpuidFunction {
+    /// Creates the entry of `leaf` and `subleaf`
+    #[inline]
+    pub fn new(leaf: u32, subleaf: u32, result: Cpuid) -> Self {
+        Self {
+            eax_in: leaf,
+            ecx_in: subleaf,
+            eax: result.eax.into(),
+            ebx: result.ebx.into(),
+            ecx: result.ecx.into(),
+            edx: result.edx.into(),
+            ..Self::default()
+        }
+    }
+
+    /// Returns the result
+    #[inline]
+    pub fn result(&self) -> Cpuid {
+        Cpuid::new(self.eax, self.ebx, self.ecx, self.edx)
+    }
+}
+
+/// The SEV-SNP CPUID page
+///
+/// The hypervisor fills this page with the `CPUID` results of the guest and
+/// the firmware validates them at launch, so the guest can trust them
+/// instead of asking the hypervisor.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C, align(4096))]
+pub struct CpuidPage {
+    count: u32,
+    reserved0: u32,
+    reserved1: u64,
+    functions: [CpuidFunction; CpuidPage::MAX],
+    reserved2: [u8; 1008],
+}
+
+impl Default for CpuidPage {
+    #[inline]
+    fn default() -> Self {
+        Self::from(Page::default())
+    }
+}
+
+impl CpuidPage {
+    /// The maximum number of entries
+    pub const MAX: usize = 64;
+
+    /// Returns the valid entries
+    ///
+    /// A count beyond the maximum is truncated.
+    #[inline]
+    pub fn functions(&self) -> &[CpuidFunction] {
+        let count = (self.count as usize).min(Self::MAX);
+        &self.functions[..count]
+    }
+
+    /// Appends an entry
+    pub fn push(&mut self, function: CpuidFunction) -> Result<(), CapacityError> {
+        let count = self.functions().len();
+        if count == Self::MAX {
+            return Err(CapacityError);
+        }
+
+        self.functions[count] = function;
+        self.count = count as u32 + 1;
+        Ok(())
+    }
+}
+
+/// Looks up the entry matching both the leaf and the subleaf
+///
+/// Missing entries yield zeros, as `CPUID` does for unknown leaves.
+impl Source for CpuidPage {
+    fn cpuid(&self, leaf: u32, subleaf: u32) -> Cpuid {
+        self.functions()
+            .iter()
+            .find(|function| function.eax_in == leaf && function.ecx_in == subleaf)
+            .map(CpuidFunction::result)
+            .unwrap_or_default()
+    }
+}
+
+// Any bytes are a valid `CpuidPage`, the count is bounded when it is read
+page_overlay!(CpuidPage);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::x86_64::cpuid::Vendor;
+
+    use core::mem::size_of;
+
+    #[test]
+    fn cpuid_page() {
+        assert_eq!(size_of::<CpuidFunction>(), 48);
+
+        let mut page = CpuidPage::default();
+        let vendor = Cpuid::new(0x10, 0x6874_7541, 0x444d_4163, 0x6974_6e65);
+        page.push(CpuidFunction::new(0, 0, vendor)).unwrap();
+        page.push(CpuidFunction::new(7, 1, Cpuid::new(1, 2, 3, 4)))
+            .unwrap();
+
+        assert_eq!(page.functions().len(), 2);
+        assert_eq!(page.cpuid(0, 0), vendor);
+        assert_eq!(page.cpuid(7, 1).ebx, 2.into());
+        assert_eq!(page.cpuid(7, 0), Cpuid::default());
+        assert_eq!(Vendor::from(page.cpuid(0, 0)), Vendor::Amd);
+
+        let bytes: &Page = page.as_ref();
+        assert_eq!(bytes[..4], [2, 0, 0, 0]);
+        assert_eq!(bytes[0x40..0x48], [7, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(bytes[0x64..0x68], 4u32.to_le_bytes());
+        assert_eq!(CpuidPage::from(*bytes), page);
+
+        for _ in 2..CpuidPage::MAX {
+            page.push(CpuidFunction::default()).unwrap();
+        }
+        assert_eq!(page.push(CpuidFunction::default()), Err(CapacityError));
+    }
+}
@@ -0,0 +1,356 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Page;
+
+/// A field of the GHCB save area
+///
+/// The value is the index of the field's quadword in the save area, which
+/// is also its bit in the valid bitmap.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum GhcbField {
+    /// The current privilege level
+    Cpl = 0x19,
+
+    /// The XSS MSR
+    Xss = 0x28,
+
+    /// DR7
+    Dr7 = 0x2c,
+
+    /// RIP
+    Rip = 0x2f,
+
+    /// RSP
+    Rsp = 0x3b,
+
+    /// RAX
+    Rax = 0x3f,
+
+    /// RCX
+    Rcx = 0x61,
+
+    /// RDX
+    Rdx = 0x62,
+
+    /// RBX
+    Rbx = 0x63,
+
+    /// RBP
+    Rbp = 0x65,
+
+    /// RSI
+    Rsi = 0x66,
+
+    /// RDI
+    Rdi = 0x67,
+
+    /// R8
+    R8 = 0x68,
+
+    /// R9
+    R9 = 0x69,
+
+    /// R10
+    R10 = 0x6a,
+
+    /// R11
+    R11 = 0x6b,
+
+    /// R12
+    R12 = 0x6c,
+
+    /// R13
+    R13 = 0x6d,
+
+    /// R14
+    R14 = 0x6e,
+
+    /// R15
+    R15 = 0x6f,
+
+    /// The exit code of the request
+    SwExitCode = 0x72,
+
+    /// The first exit information of the request
+    SwExitInfo1 = 0x73,
+
+    /// The second exit information of the request
+    SwExitInfo2 = 0x74,
+
+    /// The address of the scratch area
+    SwScratch = 0x75,
+
+    /// XCR0
+    Xcr0 = 0x7d,
+
+    /// The address of the x87 state
+    X87StateGpa = 0x80,
+}
+
+impl GhcbField {
+    /// Returns the byte offset of the field in the GHCB
+    #[inline]
+    pub const fn offset(self) -> usize {
+        match self {
+            Self::Cpl => 0xcb,
+            _ => self as usize * 8,
+        }
+    }
+}
+
+/// The guest-hypervisor communication block (GHCB)
+///
+/// The GHCB is a shared page through which an SEV-ES or SEV-SNP guest hands
+/// register state to the hypervisor on `VMGEXIT`. Only the fields marked in
+/// the valid bitmap are read by the hypervisor, so the setters mark them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C, align(4096))]
+pub struct Ghcb {
+    reserved0: [u8; 203],
+
+    /// The current privilege level
+    pub cpl: u8,
+
+    reserved1: [u8; 116],
+
+    /// The XSS MSR
+    pub xss: u64,
+
+    reserved2: [u8; 24],
+
+    /// DR7
+    pub dr7: u64,
+
+    reserved3: [u8; 16],
+
+    /// RIP
+    pub rip: u64,
+
+    reserved4: [u8; 88],
+
+    /// RSP
+    pub rsp: u64,
+
+    reserved5: [u8; 24],
+
+    /// RAX
+    pub rax: u64,
+
+    reserved6: [u8; 264],
+
+    /// RCX
+    pub rcx: u64,
+
+    /// RDX
+    pub rdx: u64,
+
+    /// RBX
+    pub rbx: u64,
+
+    reserved7: u64,
+
+    /// RBP
+    pub rbp: u64,
+
+    /// RSI
+    pub rsi: u64,
+
+    /// RDI
+    pub rdi: u64,
+
+    /// R8 to R15
+    pub r: [u64; 8],
+
+    reserved8: [u8; 16],
+
+    /// The exit code of the request
+    pub sw_exit_code: u64,
+
+    /// The first exit information of the request
+    pub sw_exit_info_1: u64,
+
+    /// The second exit information of the request
+    pub sw_exit_info_2: u64,
+
+    /// The address of the scratch area
+    pub sw_scratch: u64,
+
+    reserved9: [u8; 56],
+
+    /// XCR0
+    pub xcr0: u64,
+
+    valid_bitmap: [u8; 16],
+
+    /// The address of the x87 state
+    pub x87_state_gpa: u64,
+
+    reserved10: [u8; 1016],
+
+    /// The buffer shared with the hypervisor
+    pub shared_buffer: [u8; 2032],
+
+    reserved11: [u8; 10],
+
+    /// The version of the GHCB protocol
+    pub protocol_version: u16,
+
+    /// The usage of the GHCB, zero for the standard format
+    pub usage: u32,
+}
+
+impl Default for Ghcb {
+    #[inline]
+    fn default() -> Self {
+        Self::from(Page::default())
+    }
+}
+
+impl Ghcb {
+    /// The version of the GHCB protocol defined here
+    pub const PROTOCOL_VERSION: u16 = 2;
+
+    /// Returns `true` if the field is marked valid
+    #[inline]
+    pub const fn is_valid(&self, field: GhcbField) -> bool {
+        let bit = field as usize;
+        self.valid_bitmap[bit / 8] & 1 << (bit % 8) != 0
+    }
+
+    /// Marks the field valid
+    #[inline]
+    pub fn set_valid(&mut self, field: GhcbField) -> &mut Self {
+        let bit = field as usize;
+        self.valid_bitmap[bit / 8] |= 1 << (bit % 8);
+        self
+    }
+
+    /// Marks every field invalid
+    ///
+    /// This is done before every request, so that the hypervisor only sees
+    /// the state of that request.
+    #[inline]
+    pub fn clear_valid(&mut self) -> &mut Self {
+        self.valid_bitmap = [0; 16];
+        self
+    }
+
+    /// Sets RAX and marks it valid
+    #[inline]
+    pub fn set_rax(&mut self, value: u64) -> &mut Self {
+        self.rax = value;
+        self.set_valid(GhcbField::Rax)
+    }
+
+    /// Sets RBX and marks it valid
+    #[inline]
+    pub fn set_rbx(&mut self, value: u64) -> &mut Self {
+        self.rbx = value;
+        self.set_valid(GhcbField::Rbx)
+    }
+
+    /// Sets RCX and marks it valid
+    #[inline]
+    pub fn set_rcx(&mut self, value: u64) -> &mut Self {
+        self.rcx = value;
+        self.set_valid(GhcbField::Rcx)
+    }
+
+    /// Sets RDX and marks it valid
+    #[inline]
+    pub fn set_rdx(&mut self, value: u64) -> &mut Self {
+        self.rdx = value;
+        self.set_valid(GhcbField::Rdx)
+    }
+
+    /// Sets XCR0 and marks it valid
+    #[inline]
+    pub fn set_xcr0(&mut self, value: u64) -> &mut Self {
+        self.xcr0 = value;
+        self.set_valid(GhcbField::Xcr0)
+    }
+
+    /// Sets the exit code and information of a request and marks them valid
+    pub fn set_exit(&mut self, code: u64, info1: u64, info2: u64) -> &mut Self {
+        self.sw_exit_code = code;
+        self.sw_exit_info_1 = info1;
+        self.sw_exit_info_2 = info2;
+        self.set_valid(GhcbField::SwExitCode)
+            .set_valid(GhcbField::SwExitInfo1)
+            .set_valid(GhcbField::SwExitInfo2)
+    }
+
+    /// Sets the address of the scratch area and marks it valid
+    #[inline]
+    pub fn set_sw_scratch(&mut self, value: u64) -> &mut Self {
+        self.sw_scratch = value;
+        self.set_valid(GhcbField::SwScratch)
+    }
+}
+
+page_overlay!(Ghcb);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn offsets() {
+        let mut ghcb = Ghcb::from(Page::default());
+        ghcb.cpl = 3;
+        ghcb.xss = 0x11;
+        ghcb.dr7 = 0x12;
+        ghcb.rip = 0x13;
+        ghcb.rsp = 0x14;
+        ghcb.rax = 0x15;
+        ghcb.rcx = 0x16;
+        ghcb.rbp = 0x17;
+        ghcb.r[7] = 0x18;
+        ghcb.sw_exit_code = 0x19;
+        ghcb.sw_scratch = 0x1a;
+        ghcb.xcr0 = 0x1b;
+        ghcb.x87_state_gpa = 0x1c;
+
+        let page: &Page = ghcb.as_ref();
+        let fields = [
+            (GhcbField::Cpl, 3),
+            (GhcbField::Xss, 0x11),
+            (GhcbField::Dr7, 0x12),
+            (GhcbField::Rip, 0x13),
+            (GhcbField::Rsp, 0x14),
+            (GhcbField::Rax, 0x15),
+            (GhcbField::Rcx, 0x16),
+            (GhcbField::Rbp, 0x17),
+            (GhcbField::R15, 0x18),
+            (GhcbField::SwExitCode, 0x19),
+            (GhcbField::SwScratch, 0x1a),
+            (GhcbField::Xcr0, 0x1b),
+            (GhcbField::X87StateGpa, 0x1c),
+        ];
+        for (field, value) in fields {
+            assert_eq!(page[field.offset()], value, "{:?}", field);
+        }
+    }
+
+    #[test]
+    fn valid_bitmap() {
+        let mut ghcb = Ghcb::from(Page::default());
+        ghcb.protocol_version = Ghcb::PROTOCOL_VERSION;
+        ghcb.set_rax(0x8000_001f).set_rcx(0).set_exit(0x72, 0, 0);
+
+        assert!(ghcb.is_valid(GhcbField::Rax));
+        assert!(ghcb.is_valid(GhcbField::Rcx));
+        assert!(ghcb.is_valid(GhcbField::SwExitInfo2));
+        assert!(!ghcb.is_valid(GhcbField::Rbx));
+
+        let page: &Page = ghcb.as_ref();
+        assert_eq!(page[0x3f7], 0x80);
+        assert_eq!(page[0x3fc], 0x02);
+        assert_eq!(page[0x3fe], 0x1c);
+        assert_eq!(page[0xffa..0xffc], [2, 0]);
+        assert_eq!(Ghcb::from(*page), ghcb);
+
+        ghcb.clear_valid();
+        assert!(!ghcb.is_valid(GhcbField::Rax));
+    }
+}
@@ -0,0 +1,120 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use super::{PageInfo, PageType};
+use crate::{Address, Page};
+
+use sha2::{Digest, Sha384};
+
+/// The software equivalent of the SEV-SNP launch digest
+///
+/// The firmware starts the launch digest at zero and, for every page added
+/// at launch, replaces it with the SHA-384 of the page's `PageInfo`. This
+/// replays the same chain, so the measurement of a guest can be computed
+/// without SEV-SNP hardware.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Measurement {
+    digest: [u8; 48],
+}
+
+impl Default for Measurement {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Measurement {
+    /// Starts the measurement of a guest, as `SNP_LAUNCH_START` does
+    #[inline]
+    pub const fn new() -> Self {
+        Self { digest: [0; 48] }
+    }
+
+    /// Measures the page at `address`, as `SNP_LAUNCH_UPDATE` does
+    ///
+    /// The contents of `page` are only used by the page types that measure
+    /// them.
+    pub fn page(&mut self, address: Address<u64, Page>, page_type: PageType, page: &Page) {
+        let mut contents = [0; 48];
+        if page_type.is_measured() {
+            contents.copy_from_slice(&Sha384::digest(&page[..]));
+        }
+
+        let info = PageInfo::new(self.digest, address, page_type, contents);
+        self.update(&info);
+    }
+
+    /// Extends the launch digest with a `PageInfo` block
+    ///
+    /// The previous digest in the block is replaced with the current one.
+    pub fn update(&mut self, info: &PageInfo) {
+        let mut bytes = info.as_bytes();
+        bytes[..48].copy_from_slice(&self.digest);
+        self.digest.copy_from_slice(&Sha384::digest(bytes));
+    }
+
+    /// Returns the launch digest
+    #[inline]
+    pub const fn digest(&self) -> [u8; 48] {
+        self.digest
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn guest() {
+        let mut data = Page::default();
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = i as u8;
+        }
+
+        let mut vmsa = Page::default();
+        vmsa[..8].copy_from_slice(&0xfff0u64.to_le_bytes());
+
+        let zero = Page::default();
+        let page = |address: u64| Address::from(address).lower();
+
+        let mut measurement = Measurement::new();
+        measurement.page(page(0x1000), PageType::Normal, &data);
+        measurement.page(page(0x2000), PageType::Zero, &zero);
+        measurement.page(page(0x3000), PageType::Unmeasured, &data);
+        measurement.page(page(0x4000), PageType::Secrets, &zero);
+        measurement.page(page(0x5000), PageType::Cpuid, &data);
+        measurement.page(page(0xffff_ffff_f000), PageType::Vmsa, &vmsa);
+
+        // Reproduced outside of this crate, by hashing the `PAGE_INFO`
+        // blocks laid out by hand from the firmware ABI specification
+        assert_eq!(
+            measurement.digest(),
+            [
+                0xc3, 0x0a, 0x50, 0xc3, 0x1d, 0x98, 0x7b, 0x75, 0x7e, 0x99, 0x85, 0x47, 0xc4, 0x39,
+                0xf0, 0xb1, 0xfb, 0x99, 0x20, 0xd6, 0xf1, 0xed, 0xcf, 0x2f, 0xae, 0x29, 0x43, 0x7e,
+                0xf5, 0xa2, 0x4a, 0x00, 0x82, 0xed, 0x7c, 0x6f, 0x01, 0x3d, 0xb2, 0x02, 0x9f, 0x1f,
+                0xf4, 0x69, 0x06, 0xac, 0x47, 0x87,
+            ]
+        );
+    }
+
+    #[test]
+    fn zero_page() {
+        let mut measurement = Measurement::new();
+        measurement.page(
+            Address::from(0x8000_1000u64).lower(),
+            PageType::Zero,
+            &Page::default(),
+        );
+
+        // The `PAGE_INFO` block of the first page: the initial digest and
+        // the contents are zeros, then the length, the page type, the
+        // `IMI_PAGE` flag, the VMPL permissions, a reserved byte and the GPA
+        let mut block = [0; 0x70];
+        block[0x60..].copy_from_slice(&[
+            0x70, 0x00, 0x03, 0x00, 0x00, 0x00, 0x00, 0x00, //
+            0x00, 0x10, 0x00, 0x80, 0x00, 0x00, 0x00, 0x00,
+        ]);
+        assert_eq!(measurement.digest()[..], Sha384::digest(block)[..]);
+    }
+}
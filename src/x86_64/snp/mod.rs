@@ -0,0 +1,181 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! AMD SEV-SNP guest structures
+//!
+//! An SEV-SNP guest is launched by handing its initial pages to the firmware
+//! (`SNP_LAUNCH_UPDATE`), each with a `PageType`. The firmware folds every
+//! page into the launch digest through a `PageInfo` block, and the digest is
+//! later reported in the attestation report.
+//!
+//! Besides ordinary pages, a guest is given firmware-defined pages: the
+//! `Vmsa` of each virtual CPU, the `CpuidPage` and the `Secrets` page. At
+//! runtime it talks to the hypervisor through the `Ghcb`.
+//!
+//! With the `sha2` feature, `Measurement` computes the launch digest of
+//! those pages in software.
+
+mod cpuid;
+mod ghcb;
+#[cfg(feature = "sha2")]
+mod measure;
+mod secrets;
+mod vmsa;
+
+pub use cpuid::{CpuidFunction, CpuidPage};
+pub use ghcb::{Ghcb, GhcbField};
+#[cfg(feature = "sha2")]
+pub use measure::Measurement;
+pub use secrets::Secrets;
+pub use vmsa::{Segment, Vmsa, VmsaFeatures};
+
+use crate::{Address, Page};
+
+use core::mem::size_of;
+
+/// The type of a page added to an SEV-SNP guest at launch
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PageType {
+    /// A page of data, measured by its contents
+    Normal = 1,
+
+    /// The save area of a virtual CPU, measured by its contents
+    Vmsa = 2,
+
+    /// A page of zeros
+    Zero = 3,
+
+    /// A page whose contents are not measured
+    Unmeasured = 4,
+
+    /// The secrets page, filled by the firmware
+    Secrets = 5,
+
+    /// The CPUID page, validated by the firmware
+    Cpuid = 6,
+}
+
+impl PageType {
+    /// Returns the page type with the given value, if it is known
+    #[inline]
+    pub const fn from_bits(bits: u8) -> Option<Self> {
+        Some(match bits {
+            1 => Self::Normal,
+            2 => Self::Vmsa,
+            3 => Self::Zero,
+            4 => Self::Unmeasured,
+            5 => Self::Secrets,
+            6 => Self::Cpuid,
+            _ => return None,
+        })
+    }
+
+    /// Returns `true` if the contents of the page are measured
+    #[inline]
+    pub const fn is_measured(self) -> bool {
+        matches!(self, Self::Normal | Self::Vmsa)
+    }
+}
+
+/// The block hashed into the launch digest for every page (`PAGE_INFO`)
+///
+/// The new launch digest is the SHA-384 of this block, which includes the
+/// previous digest.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct PageInfo {
+    /// The launch digest before this page
+    pub digest: [u8; 48],
+
+    /// The SHA-384 of the page contents, or zeros if they are not measured
+    pub contents: [u8; 48],
+
+    /// The size of this block in bytes
+    pub length: u16,
+
+    /// The page type
+    pub page_type: u8,
+
+    /// The page belongs to an incoming migration image
+    pub imi_page: u8,
+
+    /// The permissions granted to VMPL3
+    pub vmpl3_perms: u8,
+
+    /// The permissions granted to VMPL2
+    pub vmpl2_perms: u8,
+
+    /// The permissions granted to VMPL1
+    pub vmpl1_perms: u8,
+
+    reserved: u8,
+
+    /// The guest physical address of the page
+    pub gpa: u64,
+}
+
+const _: () = assert!(size_of::<PageInfo>() == 0x70);
+
+impl PageInfo {
+    /// Creates the block of a page at `address`, extending `digest`
+    #[inline]
+    pub fn new(
+        digest: [u8; 48],
+        address: Address<u64, Page>,
+        page_type: PageType,
+        contents: [u8; 48],
+    ) -> Self {
+        Self {
+            digest,
+            contents,
+            length: size_of::<Self>() as u16,
+            page_type: page_type as u8,
+            imi_page: 0,
+            vmpl3_perms: 0,
+            vmpl2_perms: 0,
+            vmpl1_perms: 0,
+            reserved: 0,
+            gpa: address.raw(),
+        }
+    }
+
+    /// Returns the block as bytes, as it is hashed by the firmware
+    pub fn as_bytes(&self) -> [u8; 0x70] {
+        let mut bytes = [0; 0x70];
+        bytes[..0x30].copy_from_slice(&self.digest);
+        bytes[0x30..0x60].copy_from_slice(&self.contents);
+        bytes[0x60..0x62].copy_from_slice(&self.length.to_le_bytes());
+        bytes[0x62] = self.page_type;
+        bytes[0x63] = self.imi_page;
+        bytes[0x64] = self.vmpl3_perms;
+        bytes[0x65] = self.vmpl2_perms;
+        bytes[0x66] = self.vmpl1_perms;
+        bytes[0x68..].copy_from_slice(&self.gpa.to_le_bytes());
+        bytes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn page_type() {
+        assert_eq!(PageType::from_bits(2), Some(PageType::Vmsa));
+        assert_eq!(PageType::from_bits(0), None);
+        assert_eq!(PageType::from_bits(7), None);
+        assert!(PageType::Normal.is_measured());
+        assert!(!PageType::Secrets.is_measured());
+    }
+
+    #[test]
+    fn page_info() {
+        let address = Address::from(0x8000_1000u64).lower();
+        let info = PageInfo::new([0xaa; 48], address, PageType::Cpuid, [0; 48]);
+
+        let bytes = info.as_bytes();
+        assert_eq!(bytes[..0x30], [0xaa; 48]);
+        assert_eq!(bytes[0x30..0x60], [0; 48]);
+        assert_eq!(bytes[0x60..0x64], [0x70, 0, 6, 0]);
+        assert_eq!(bytes[0x68..], 0x8000_1000u64.to_le_bytes());
+    }
+}
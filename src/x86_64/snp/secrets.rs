@@ -0,0 +1,127 @@
+// SPDX-License-Identifier: Apache-2.0
+
+/// The SEV-SNP secrets page
+///
+/// The firmware fills this page at launch with the keys the guest uses to
+/// talk to the firmware (VMPCKs), one per VMPL. The OS area is reserved for
+/// the guest, which keeps its message sequence numbers there.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C, align(4096))]
+pub struct Secrets {
+    /// The version of the page layout
+    pub version: u32,
+
+    /// The flags; bit 0 marks a guest launched from a migration image
+    pub flags: u32,
+
+    /// The family, model and stepping of the processor
+    pub fms: u32,
+
+    reserved0: u32,
+
+    /// The guest OS-visible workarounds
+    pub gosvw: [u8; 16],
+
+    /// The VM platform communication keys of VMPL0 to VMPL3
+    pub vmpck: [[u8; 32]; Secrets::VMPCKS],
+
+    /// The message sequence numbers of VMPL0 to VMPL3, kept by the guest
+    pub msg_seqno: [u32; Secrets::VMPCKS],
+
+    /// The physical address of the AP jump table, kept by the guest
+    pub ap_jump_table_pa: u64,
+
+    reserved1: [u8; 40],
+
+    /// Free for guest use
+    pub guest_usage: [u8; 32],
+
+    /// The VMSA tweak bitmap
+    pub vmsa_tweak_bitmap: [u8; 64],
+
+    /// The base address of the SVSM
+    pub svsm_base: u64,
+
+    /// The size of the SVSM in bytes
+    pub svsm_size: u64,
+
+    /// The address of the SVSM calling area
+    pub svsm_caa: u64,
+
+    /// The maximum SVSM protocol version
+    pub svsm_max_version: u32,
+
+    /// The VMPL of the guest under the SVSM
+    pub svsm_guest_vmpl: u8,
+
+    reserved2: [u8; 3],
+
+    /// The decrease from nominal to mean TSC frequency, in tenths of a
+    /// percent
+    pub tsc_factor: u32,
+
+    reserved3: [u8; 3740],
+}
+
+impl Secrets {
+    /// The number of VMPCKs
+    pub const VMPCKS: usize = 4;
+
+    /// Returns `true` if the guest was launched from a migration image
+    #[inline]
+    pub const fn imien(&self) -> bool {
+        self.flags & 1 != 0
+    }
+
+    /// Returns the VMPCK of `vmpl`, unless it is out of range or erased
+    ///
+    /// A guest erases the keys it must not use with zeros.
+    pub fn vmpck(&self, vmpl: usize) -> Option<&[u8; 32]> {
+        self.vmpck
+            .get(vmpl)
+            .filter(|key| key.iter().any(|byte| *byte != 0))
+    }
+
+    /// Erases the VMPCK of `vmpl`
+    #[inline]
+    pub fn erase_vmpck(&mut self, vmpl: usize) {
+        if let Some(key) = self.vmpck.get_mut(vmpl) {
+            *key = [0; 32];
+        }
+    }
+}
+
+page_overlay!(Secrets);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Page;
+
+    #[test]
+    fn secrets() {
+        let mut page = Page::default();
+        page[0] = 3;
+        page[4] = 1;
+        page[0x40..0x60].copy_from_slice(&[0x5a; 32]);
+        page[0xa4] = 7;
+        page[0x160] = 2;
+
+        let mut secrets = Secrets::from(page);
+        assert_eq!(secrets.version, 3);
+        assert!(secrets.imien());
+        assert_eq!(secrets.vmpck(0), None);
+        assert_eq!(secrets.vmpck(1), Some(&[0x5a; 32]));
+        assert_eq!(secrets.vmpck(4), None);
+        assert_eq!(secrets.msg_seqno[1], 7);
+        assert_eq!(secrets.tsc_factor, 2);
+
+        secrets.erase_vmpck(1);
+        secrets.ap_jump_table_pa = 0x9000;
+        assert_eq!(secrets.vmpck(1), None);
+
+        let page: &Page = secrets.as_ref();
+        assert_eq!(page[0x40..0x60], [0; 32]);
+        assert_eq!(page[0xb0..0xb8], 0x9000u64.to_le_bytes());
+    }
+}
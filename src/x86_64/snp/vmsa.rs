@@ -0,0 +1,379 @@
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::Page;
+
+/// A segment register as saved in the VMSA
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[repr(C)]
+pub struct Segment {
+    /// The selector
+    pub selector: u16,
+
+    /// The attributes, in the compressed VMCB format
+    pub attrib: u16,
+
+    /// The limit
+    pub limit: u32,
+
+    /// The base address
+    pub base: u64,
+}
+
+crate::bitfields! {
+    /// The SEV features enabled for a virtual CPU (`SEV_FEATURES`)
+    pub struct VmsaFeatures(u64) {
+        /// The guest runs with SEV-SNP
+        snp_active, set_snp_active: 0;
+
+        /// The virtual top of memory is used for the C-bit
+        vtom, set_vtom: 1;
+
+        /// Exceptions are reflected as #VC
+        reflect_vc, set_reflect_vc: 2;
+
+        /// Restricted injection
+        restricted_injection, set_restricted_injection: 3;
+
+        /// Alternate injection
+        alternate_injection, set_alternate_injection: 4;
+
+        /// Debug registers are swapped on world switches
+        debug_swap, set_debug_swap: 5;
+
+        /// The host may not use IBS on the guest
+        prevent_host_ibs, set_prevent_host_ibs: 6;
+
+        /// Branch target buffer isolation
+        btb_isolation, set_btb_isolation: 7;
+
+        /// VMPL supervisor shadow stacks
+        vmpl_sss, set_vmpl_sss: 8;
+
+        /// Secure TSC
+        secure_tsc, set_secure_tsc: 9;
+
+        /// `VMGEXIT` parameters are passed in RAX
+        vmgexit_parameter, set_vmgexit_parameter: 10;
+
+        /// IBS virtualization
+        ibs_virtualization, set_ibs_virtualization: 12;
+
+        /// VMSA register protection
+        vmsa_reg_prot, set_vmsa_reg_prot: 14;
+
+        /// SMT protection
+        smt_protection, set_smt_protection: 15;
+    }
+}
+
+/// The encrypted save area of a virtual CPU (VMSA)
+///
+/// This is the state of a virtual CPU of an SEV-ES or SEV-SNP guest. The
+/// layout follows the AMD64 architecture manual; the reserved fields keep
+/// every register at its architectural offset.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C, align(4096))]
+pub struct Vmsa {
+    /// The ES segment
+    pub es: Segment,
+
+    /// The CS segment
+    pub cs: Segment,
+
+    /// The SS segment
+    pub ss: Segment,
+
+    /// The DS segment
+    pub ds: Segment,
+
+    /// The FS segment
+    pub fs: Segment,
+
+    /// The GS segment
+    pub gs: Segment,
+
+    /// The global descriptor table
+    pub gdtr: Segment,
+
+    /// The local descriptor table
+    pub ldtr: Segment,
+
+    /// The interrupt descriptor table
+    pub idtr: Segment,
+
+    /// The task register
+    pub tr: Segment,
+
+    /// The shadow stack pointers of CPL 0 to 3 (`PL0_SSP` to `PL3_SSP`)
+    pub pl_ssp: [u64; 4],
+
+    /// The user-mode `U_CET` MSR
+    pub u_cet: u64,
+
+    reserved0: [u8; 2],
+
+    /// The VMPL of the virtual CPU
+    pub vmpl: u8,
+
+    /// The current privilege level
+    pub cpl: u8,
+
+    reserved1: [u8; 4],
+
+    /// The EFER MSR
+    pub efer: u64,
+
+    reserved2: [u8; 104],
+
+    /// The XSS MSR
+    pub xss: u64,
+
+    /// CR4
+    pub cr4: u64,
+
+    /// CR3
+    pub cr3: u64,
+
+    /// CR0
+    pub cr0: u64,
+
+    /// DR7
+    pub dr7: u64,
+
+    /// DR6
+    pub dr6: u64,
+
+    /// RFLAGS
+    pub rflags: u64,
+
+    /// RIP
+    pub rip: u64,
+
+    /// DR0 to DR3
+    pub dr: [u64; 4],
+
+    /// The address masks of DR0 to DR3
+    pub dr_addr_mask: [u64; 4],
+
+    reserved3: [u8; 24],
+
+    /// RSP
+    pub rsp: u64,
+
+    /// The supervisor `S_CET` MSR
+    pub s_cet: u64,
+
+    /// The shadow stack pointer
+    pub ssp: u64,
+
+    /// The interrupt shadow stack table address
+    pub isst_addr: u64,
+
+    /// RAX
+    pub rax: u64,
+
+    /// The STAR MSR
+    pub star: u64,
+
+    /// The LSTAR MSR
+    pub lstar: u64,
+
+    /// The CSTAR MSR
+    pub cstar: u64,
+
+    /// The SFMASK MSR
+    pub sfmask: u64,
+
+    /// The `KERNEL_GS_BASE` MSR
+    pub kernel_gs_base: u64,
+
+    /// The `SYSENTER_CS` MSR
+    pub sysenter_cs: u64,
+
+    /// The `SYSENTER_ESP` MSR
+    pub sysenter_esp: u64,
+
+    /// The `SYSENTER_EIP` MSR
+    pub sysenter_eip: u64,
+
+    /// CR2
+    pub cr2: u64,
+
+    reserved4: [u8; 32],
+
+    /// The guest PAT MSR
+    pub g_pat: u64,
+
+    /// The `DEBUGCTL` MSR
+    pub dbgctl: u64,
+
+    /// The last branch source
+    pub br_from: u64,
+
+    /// The last branch target
+    pub br_to: u64,
+
+    /// The source of the last exception
+    pub last_excp_from: u64,
+
+    /// The target of the last exception
+    pub last_excp_to: u64,
+
+    reserved5: [u8; 80],
+
+    /// The protection key rights
+    pub pkru: u32,
+
+    /// The `TSC_AUX` MSR
+    pub tsc_aux: u32,
+
+    reserved6: [u8; 24],
+
+    /// RCX
+    pub rcx: u64,
+
+    /// RDX
+    pub rdx: u64,
+
+    /// RBX
+    pub rbx: u64,
+
+    reserved7: u64,
+
+    /// RBP
+    pub rbp: u64,
+
+    /// RSI
+    pub rsi: u64,
+
+    /// RDI
+    pub rdi: u64,
+
+    /// R8 to R15
+    pub r: [u64; 8],
+
+    reserved8: [u8; 16],
+
+    /// The first exit information of the last exit
+    pub guest_exit_info_1: u64,
+
+    /// The second exit information of the last exit
+    pub guest_exit_info_2: u64,
+
+    /// The interrupt information of the last exit
+    pub guest_exit_int_info: u64,
+
+    /// The next RIP of the last exit
+    pub guest_nrip: u64,
+
+    /// The SEV features of the virtual CPU
+    pub sev_features: VmsaFeatures,
+
+    /// The virtual interrupt control
+    pub vintr_ctrl: u64,
+
+    /// The exit code of the last exit
+    pub guest_exit_code: u64,
+
+    /// The virtual top of memory (with `VmsaFeatures::vtom`)
+    pub virtual_tom: u64,
+
+    /// The TLB identifier
+    pub tlb_id: u64,
+
+    /// The physical CPU identifier
+    pub pcpu_id: u64,
+
+    /// The event to inject
+    pub event_inj: u64,
+
+    /// XCR0
+    pub xcr0: u64,
+
+    reserved9: [u8; 16],
+
+    /// The x87 data pointer
+    pub x87_dp: u64,
+
+    /// MXCSR
+    pub mxcsr: u32,
+
+    /// The x87 tag word
+    pub x87_ftw: u16,
+
+    /// The x87 status word
+    pub x87_fsw: u16,
+
+    /// The x87 control word
+    pub x87_fcw: u16,
+
+    /// The x87 last opcode
+    pub x87_fop: u16,
+
+    /// The x87 data segment
+    pub x87_ds: u16,
+
+    /// The x87 code segment
+    pub x87_cs: u16,
+
+    /// The x87 instruction pointer
+    pub x87_rip: u64,
+
+    /// The x87 registers, 10 bytes each
+    pub fpreg_x87: [u8; 80],
+
+    /// The XMM registers
+    pub fpreg_xmm: [u8; 256],
+
+    /// The upper halves of the YMM registers
+    pub fpreg_ymm: [u8; 256],
+
+    reserved10: [u8; 2448],
+}
+
+impl Default for Vmsa {
+    #[inline]
+    fn default() -> Self {
+        Self::from(Page::default())
+    }
+}
+
+page_overlay!(Vmsa);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn vmsa() {
+        let mut vmsa = Vmsa::from(Page::default());
+        vmsa.cs.selector = 0x8;
+        vmsa.efer = 0x1000;
+        vmsa.rip = 0xfff0;
+        vmsa.rsp = 0x2000;
+        vmsa.rax = 1;
+        vmsa.rcx = 2;
+        vmsa.r[7] = 3;
+        vmsa.sev_features.set_snp_active(true);
+        vmsa.xcr0 = 1;
+        vmsa.mxcsr = 0x1f80;
+        vmsa.x87_fcw = 0x37f;
+        vmsa.pl_ssp[3] = 0x3000;
+
+        let page: &Page = vmsa.as_ref();
+        assert_eq!(page[0x10..0x12], [0x8, 0]);
+        assert_eq!(page[0xb8..0xc0], 0x3000u64.to_le_bytes());
+        assert_eq!(page[0xd0..0xd8], 0x1000u64.to_le_bytes());
+        assert_eq!(page[0x178..0x180], 0xfff0u64.to_le_bytes());
+        assert_eq!(page[0x1d8], 0x00);
+        assert_eq!(page[0x1d9], 0x20);
+        assert_eq!(page[0x1f8], 1);
+        assert_eq!(page[0x308], 2);
+        assert_eq!(page[0x378], 3);
+        assert_eq!(page[0x3b0], 1);
+        assert_eq!(page[0x3e8], 1);
+        assert_eq!(page[0x408..0x40c], 0x1f80u32.to_le_bytes());
+        assert_eq!(page[0x410..0x412], 0x37fu16.to_le_bytes());
+        assert_eq!(Vmsa::from(*page), vmsa);
+    }
+}
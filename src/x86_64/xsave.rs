@@ -0,0 +1,874 @@
+// SPDX-License-Identifier: Apache-2.0
+
+//! The XSAVE area
+//!
+//! `XSAVE` and its variants store the extended processor state in an area
+//! made of the 512-byte legacy region (the `FXSAVE` layout, holding the x87
+//! and SSE state), the 64-byte XSAVE header and the extended region holding
+//! the remaining state components. The extended region comes in two formats:
+//!
+//!   * In the standard format (`XSAVE`, `XSAVEOPT`), every component has a
+//!     fixed offset.
+//!   * In the compacted format (`XSAVEC`, `XSAVES`), only the components in
+//!     `XCOMP_BV` are stored, back to back in the order of their index.
+//!
+//! The sizes and standard offsets of the extended components are model
+//! specific and reported by `CPUID` leaf 0xD, so every `Area` carries the
+//! `Layout` of the processor it belongs to.
+//!
+//! `Area` holds an XSAVE area in a `Page`, which is aligned as `XSAVE`
+//! requires and large enough for every component up to CET. AMX state does
+//! not fit and is rejected.
+
+use super::cpuid::{Source, Xsave, XsaveComponent};
+use crate::Page;
+
+use core::mem::size_of;
+
+/// An error produced when parsing an XSAVE area
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParseError {
+    /// The input ends before the last component
+    Truncated,
+
+    /// The input is larger than a page
+    TooLarge,
+
+    /// The header has reserved bits set or contradicts itself
+    InvalidHeader,
+
+    /// A state component with an unknown layout is present
+    UnknownComponent(u32),
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Truncated => write!(f, "XSAVE area truncated"),
+            Self::TooLarge => write!(f, "XSAVE area larger than a page"),
+            Self::InvalidHeader => write!(f, "invalid XSAVE header"),
+            Self::UnknownComponent(index) => write!(f, "unknown XSAVE component {}", index),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ParseError {}
+
+/// An XSAVE state component
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Component {
+    /// The x87 state, in the legacy region
+    X87 = 0,
+
+    /// The SSE state (XMM0 to XMM15), in the legacy region
+    Sse = 1,
+
+    /// The upper halves of YMM0 to YMM15
+    Avx = 2,
+
+    /// The MPX bound registers
+    BndRegs = 3,
+
+    /// The MPX configuration and status registers
+    BndCsr = 4,
+
+    /// The AVX-512 opmask registers K0 to K7
+    Opmask = 5,
+
+    /// The upper halves of ZMM0 to ZMM15
+    ZmmHi256 = 6,
+
+    /// ZMM16 to ZMM31
+    Hi16Zmm = 7,
+
+    /// The processor trace state (supervisor)
+    Pt = 8,
+
+    /// The protection key rights register
+    Pkru = 9,
+
+    /// The PASID state (supervisor)
+    Pasid = 10,
+
+    /// The user-mode CET state (supervisor)
+    CetUser = 11,
+
+    /// The supervisor-mode CET state (supervisor)
+    CetSupervisor = 12,
+}
+
+impl Component {
+    /// Returns the component with the given index, if it is known
+    pub const fn from_index(index: u32) -> Option<Self> {
+        Some(match index {
+            0 => Self::X87,
+            1 => Self::Sse,
+            2 => Self::Avx,
+            3 => Self::BndRegs,
+            4 => Self::BndCsr,
+            5 => Self::Opmask,
+            6 => Self::ZmmHi256,
+            7 => Self::Hi16Zmm,
+            8 => Self::Pt,
+            9 => Self::Pkru,
+            10 => Self::Pasid,
+            11 => Self::CetUser,
+            12 => Self::CetSupervisor,
+            _ => return None,
+        })
+    }
+
+    /// Returns the index, which is the bit of the component in XCR0
+    #[inline]
+    pub const fn index(self) -> u32 {
+        self as u32
+    }
+
+    /// Returns the mask of the component in XCR0, `IA32_XSS` and the header
+    #[inline]
+    pub const fn mask(self) -> u64 {
+        1 << self as u32
+    }
+}
+
+/// The legacy region of the XSAVE area, which is the `FXSAVE` layout
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(C, align(16))]
+pub struct Legacy {
+    /// The x87 control word
+    pub fcw: u16,
+
+    /// The x87 status word
+    pub fsw: u16,
+
+    /// The abridged x87 tag word
+    pub ftw: u8,
+
+    reserved0: u8,
+
+    /// The last x87 opcode
+    pub fop: u16,
+
+    /// The last x87 instruction pointer
+    pub fip: u64,
+
+    /// The last x87 data pointer
+    pub fdp: u64,
+
+    /// The SSE control and status register
+    pub mxcsr: u32,
+
+    /// The supported bits of MXCSR
+    pub mxcsr_mask: u32,
+
+    /// ST0 to ST7 (MM0 to MM7), 80 bits each, padded to 16 bytes
+    pub st: [[u8; 16]; 8],
+
+    /// XMM0 to XMM15
+    pub xmm: [[u8; 16]; 16],
+
+    reserved1: [u8; 48],
+
+    /// Bytes which the processor never writes
+    pub available: [u8; 48],
+}
+
+impl Default for Legacy {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            fcw: 0,
+            fsw: 0,
+            ftw: 0,
+            reserved0: 0,
+            fop: 0,
+            fip: 0,
+            fdp: 0,
+            mxcsr: 0,
+            mxcsr_mask: 0,
+            st: Default::default(),
+            xmm: Default::default(),
+            reserved1: [0; 48],
+            available: [0; 48],
+        }
+    }
+}
+
+impl Legacy {
+    /// The x87 control word in the initial state
+    pub const FCW_INIT: u16 = 0x037f;
+
+    /// MXCSR in the initial state
+    pub const MXCSR_INIT: u32 = 0x1f80;
+}
+
+/// The XSAVE header
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[repr(C)]
+pub struct Header {
+    /// The components which are not in their initial state
+    pub xstate_bv: u64,
+
+    /// The components stored in the compacted format, with bit 63 set
+    ///
+    /// This is zero in the standard format.
+    pub xcomp_bv: u64,
+
+    reserved: [u64; 6],
+}
+
+impl Header {
+    /// The bit of `XCOMP_BV` selecting the compacted format
+    pub const COMPACTED: u64 = 1 << 63;
+
+    /// Returns `true` if the area is in the compacted format
+    #[inline]
+    pub const fn is_compacted(&self) -> bool {
+        self.xcomp_bv & Self::COMPACTED != 0
+    }
+}
+
+const _: () = assert!(size_of::<Legacy>() == 512 && size_of::<Header>() == 64);
+
+/// The offset of the header
+const HEADER: usize = size_of::<Legacy>();
+
+/// The offset of the extended region
+const EXTENDED: usize = HEADER + size_of::<Header>();
+
+/// The state components stored in the legacy region
+const LEGACY: u64 = 0b11;
+
+/// Iterates over the indices of the extended components in `bits`
+fn extended(bits: u64) -> impl Iterator<Item = u32> {
+    (2..63).filter(move |index| bits >> index & 1 != 0)
+}
+
+/// The number of state components with a known meaning
+const COMPONENTS: usize = 13;
+
+const fn user(size: u32, offset: u32) -> Option<XsaveComponent> {
+    Some(XsaveComponent {
+        size,
+        offset,
+        supervisor: false,
+        aligned: false,
+    })
+}
+
+const fn supervisor(size: u32) -> Option<XsaveComponent> {
+    Some(XsaveComponent {
+        size,
+        offset: 0,
+        supervisor: true,
+        aligned: false,
+    })
+}
+
+/// The sizes and offsets of the extended state components
+///
+/// These differ between vendors: AMD processors, for example, store PKRU
+/// right after the AVX state in the standard format. `Layout::read()` reads
+/// the layout from `CPUID` leaf 0xD and `Layout::INTEL` is the layout of
+/// Intel processors.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Layout([Option<XsaveComponent>; COMPONENTS]);
+
+impl Layout {
+    /// The layout of Intel processors
+    pub const INTEL: Self = Self([
+        None,
+        None,
+        user(256, 576),
+        user(64, 960),
+        user(64, 1024),
+        user(64, 1088),
+        user(512, 1152),
+        user(1024, 1664),
+        supervisor(128),
+        user(8, 2688),
+        supervisor(8),
+        supervisor(16),
+        supervisor(24),
+    ]);
+
+    /// Reads the layout of the components supported by a processor
+    ///
+    /// Returns `None` if the processor does not support `XSAVE`.
+    pub fn read(source: &(impl Source + ?Sized)) -> Option<Self> {
+        let xsave = Xsave::read(source)?;
+
+        let mut layout = Self([None; COMPONENTS]);
+        for (index, slot) in layout.0.iter_mut().enumerate() {
+            *slot = xsave
+                .component(source, index as u32)
+                .filter(|component| component.size != 0);
+        }
+
+        Some(layout)
+    }
+
+    /// Returns the layout of an extended component, if it is supported
+    #[inline]
+    pub fn get(&self, component: Component) -> Option<XsaveComponent> {
+        self.0[component.index() as usize]
+    }
+
+    /// Returns the offset and size of every component in `bits` and the end
+    /// of the last
+    ///
+    /// In the compacted format, `bits` is `XCOMP_BV`; in the standard format,
+    /// it is the set of components to place.
+    fn visit(
+        &self,
+        bits: u64,
+        compacted: bool,
+        mut visit: impl FnMut(Component, usize, usize),
+    ) -> Result<usize, ParseError> {
+        let mut end = EXTENDED;
+
+        for index in extended(bits) {
+            let unknown = ParseError::UnknownComponent(index);
+            let component = Component::from_index(index).ok_or(unknown)?;
+            let layout = self.get(component).ok_or(unknown)?;
+
+            let offset = match compacted {
+                true if layout.aligned => (end + 63) & !63,
+                true => end,
+                false if layout.supervisor => return Err(unknown),
+                false => layout.offset as usize,
+            };
+
+            let size = layout.size as usize;
+            if offset + size > Page::SIZE {
+                return Err(ParseError::TooLarge);
+            }
+
+            visit(component, offset, size);
+            end = end.max(offset + size);
+        }
+
+        Ok(end)
+    }
+}
+
+/// An XSAVE area in the standard or compacted format
+///
+/// The area always has a valid header and only contains components with a
+/// known layout. Components which are not in `XSTATE_BV` are in their
+/// initial state, regardless of the bytes stored for them.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Area {
+    page: Page,
+    layout: Layout,
+}
+
+impl core::fmt::Debug for Area {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("Area")
+            .field("legacy", self.legacy())
+            .field("header", self.header())
+            .finish()
+    }
+}
+
+impl AsRef<Page> for Area {
+    #[inline]
+    fn as_ref(&self) -> &Page {
+        &self.page
+    }
+}
+
+impl Area {
+    /// Creates an area in the standard format with every component in its
+    /// initial state
+    #[inline]
+    pub fn new(layout: Layout) -> Self {
+        Self {
+            page: Page::default(),
+            layout,
+        }
+    }
+
+    /// Parses an area saved by `XSAVE`, `XSAVEC` or a related instruction
+    ///
+    /// The input must hold at least the legacy region, the header and every
+    /// component present in the header, laid out as in `layout`.
+    pub fn from_bytes(bytes: &[u8], layout: Layout) -> Result<Self, ParseError> {
+        if bytes.len() < EXTENDED {
+            return Err(ParseError::Truncated);
+        }
+
+        if bytes.len() > Page::SIZE {
+            return Err(ParseError::TooLarge);
+        }
+
+        let mut area = Self::new(layout);
+        area.page[..bytes.len()].copy_from_slice(bytes);
+
+        let header = area.header();
+        let compacted = header.is_compacted();
+        let stored = match compacted {
+            true => header.xcomp_bv & !Header::COMPACTED,
+            false => header.xstate_bv & !LEGACY,
+        };
+
+        if header.reserved != [0; 6]
+            || compacted && header.xstate_bv & !header.xcomp_bv != 0
+            || !compacted && header.xcomp_bv != 0
+        {
+            return Err(ParseError::InvalidHeader);
+        }
+
+        if layout.visit(stored, compacted, |_, _, _| ())? > bytes.len() {
+            return Err(ParseError::Truncated);
+        }
+
+        Ok(area)
+    }
+
+    /// Returns the area up to the end of the last stored component
+    #[inline]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.page[..self.size()]
+    }
+
+    /// Returns the size up to the end of the last stored component
+    ///
+    /// In the compacted format, this is the size `XSAVEC` writes. In the
+    /// standard format, it covers the components in `XSTATE_BV`.
+    #[inline]
+    pub fn size(&self) -> usize {
+        let header = self.header();
+        let stored = match header.is_compacted() {
+            true => header.xcomp_bv,
+            false => header.xstate_bv,
+        };
+
+        // The header was validated, so every component is known.
+        self.layout
+            .visit(
+                stored & !Header::COMPACTED,
+                header.is_compacted(),
+                |_, _, _| (),
+            )
+            .unwrap_or(Page::SIZE)
+    }
+
+    /// Returns the legacy region
+    #[inline]
+    pub fn legacy(&self) -> &Legacy {
+        // SAFETY: the page is aligned and any bytes are a valid `Legacy`.
+        unsafe { &*self.page.as_ptr().cast() }
+    }
+
+    /// Returns the legacy region, mutably
+    #[inline]
+    pub fn legacy_mut(&mut self) -> &mut Legacy {
+        // SAFETY: the page is aligned and any bytes are a valid `Legacy`.
+        unsafe { &mut *self.page.as_mut_ptr().cast() }
+    }
+
+    /// Returns the header
+    #[inline]
+    pub fn header(&self) -> &Header {
+        // SAFETY: the header is aligned and any bytes are a valid `Header`.
+        unsafe { &*self.page[HEADER..].as_ptr().cast() }
+    }
+
+    #[inline]
+    fn header_mut(&mut self) -> &mut Header {
+        // SAFETY: the header is aligned and any bytes are a valid `Header`.
+        unsafe { &mut *self.page[HEADER..].as_mut_ptr().cast() }
+    }
+
+    /// Returns `true` if the area is in the compacted format
+    #[inline]
+    pub fn is_compacted(&self) -> bool {
+        self.header().is_compacted()
+    }
+
+    /// Returns the layout of the extended components
+    #[inline]
+    pub fn layout(&self) -> &Layout {
+        &self.layout
+    }
+
+    /// Returns the offset of a component, if it is stored in the area
+    #[inline]
+    pub fn offset(&self, component: Component) -> Option<usize> {
+        self.locate(component).map(|(offset, _)| offset)
+    }
+
+    /// Returns the offset and the size of a component
+    fn locate(&self, component: Component) -> Option<(usize, usize)> {
+        let header = self.header();
+        match component {
+            Component::X87 => return Some((0, 160)),
+            Component::Sse => return Some((160, 256)),
+            _ if header.is_compacted() && header.xcomp_bv & component.mask() == 0 => return None,
+            _ => (),
+        }
+
+        if !header.is_compacted() {
+            return match self.layout.get(component)? {
+                layout if layout.supervisor => None,
+                layout => Some((layout.offset as usize, layout.size as usize)),
+            };
+        }
+
+        let mut found = None;
+        let _ = self.layout.visit(
+            header.xcomp_bv & !Header::COMPACTED,
+            true,
+            |c, offset, size| {
+                if c == component {
+                    found = Some((offset, size));
+                }
+            },
+        );
+
+        found
+    }
+
+    /// Returns the bytes of a component, unless it is in its initial state
+    pub fn component(&self, component: Component) -> Option<&[u8]> {
+        if self.header().xstate_bv & component.mask() == 0 {
+            return None;
+        }
+
+        let (offset, size) = self.locate(component)?;
+        Some(&self.page[offset..][..size])
+    }
+
+    /// Returns the bytes of a component for writing
+    ///
+    /// The component is marked as modified in `XSTATE_BV`. Returns `None` if
+    /// the area has no room for the component.
+    pub fn component_mut(&mut self, component: Component) -> Option<&mut [u8]> {
+        let (offset, size) = self.locate(component)?;
+        self.header_mut().xstate_bv |= component.mask();
+        Some(&mut self.page[offset..][..size])
+    }
+
+    /// Returns a component as an array, if it has the size of the array
+    fn array<T>(&self, component: Component) -> Option<&T> {
+        let bytes = self.component(component)?;
+
+        match bytes.len() == size_of::<T>() {
+            // SAFETY: the slice has the size of `T`, an array of bytes.
+            true => Some(unsafe { &*bytes.as_ptr().cast() }),
+            false => None,
+        }
+    }
+
+    /// Resets a component to its initial state
+    #[inline]
+    pub fn clear(&mut self, component: Component) {
+        self.header_mut().xstate_bv &= !component.mask();
+    }
+
+    /// Returns ST0 to ST7, unless the x87 state is in its initial state
+    #[inline]
+    pub fn x87(&self) -> Option<&[[u8; 16]; 8]> {
+        self.component(Component::X87).map(|_| &self.legacy().st)
+    }
+
+    /// Returns XMM0 to XMM15, unless the SSE state is in its initial state
+    #[inline]
+    pub fn sse(&self) -> Option<&[[u8; 16]; 16]> {
+        self.component(Component::Sse).map(|_| &self.legacy().xmm)
+    }
+
+    /// Returns the upper halves of YMM0 to YMM15
+    #[inline]
+    pub fn avx(&self) -> Option<&[[u8; 16]; 16]> {
+        self.array(Component::Avx)
+    }
+
+    /// Returns the opmask registers K0 to K7
+    #[inline]
+    pub fn opmask(&self) -> Option<[u64; 8]> {
+        self.array::<[[u8; 8]; 8]>(Component::Opmask)
+            .map(|bytes| bytes.map(u64::from_le_bytes))
+    }
+
+    /// Returns the upper halves of ZMM0 to ZMM15
+    #[inline]
+    pub fn zmm_hi256(&self) -> Option<&[[u8; 32]; 16]> {
+        self.array(Component::ZmmHi256)
+    }
+
+    /// Returns ZMM16 to ZMM31
+    #[inline]
+    pub fn hi16_zmm(&self) -> Option<&[[u8; 64]; 16]> {
+        self.array(Component::Hi16Zmm)
+    }
+
+    /// Returns the protection key rights register
+    #[inline]
+    pub fn pkru(&self) -> Option<u32> {
+        self.component(Component::Pkru)
+            .filter(|bytes| bytes.len() >= 4)
+            .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    /// Copies the legacy region and the modified components into `area`
+    fn copy_into(&self, mut area: Self, stored: u64) -> Self {
+        let xstate_bv = self.header().xstate_bv & (stored | LEGACY);
+        let legacy = self.legacy();
+        let target = area.legacy_mut();
+
+        target.mxcsr = legacy.mxcsr;
+        target.mxcsr_mask = legacy.mxcsr_mask;
+
+        if xstate_bv & Component::X87.mask() != 0 {
+            target.fcw = legacy.fcw;
+            target.fsw = legacy.fsw;
+            target.ftw = legacy.ftw;
+            target.fop = legacy.fop;
+            target.fip = legacy.fip;
+            target.fdp = legacy.fdp;
+            target.st = legacy.st;
+        }
+
+        if xstate_bv & Component::Sse.mask() != 0 {
+            target.xmm = legacy.xmm;
+        }
+
+        area.header_mut().xstate_bv = xstate_bv;
+        for index in extended(xstate_bv) {
+            if let Some(component) = Component::from_index(index) {
+                if let (Some(from), Some(to)) = (self.locate(component), area.locate(component)) {
+                    let size = from.1.min(to.1);
+                    area.page[to.0..][..size].copy_from_slice(&self.page[from.0..][..size]);
+                }
+            }
+        }
+
+        area
+    }
+
+    /// Converts the area to the standard format
+    ///
+    /// Components in their initial state are stored as `XSAVE` stores them.
+    /// Supervisor components are dropped, since the standard format has no
+    /// room for them.
+    pub fn to_standard(&self) -> Self {
+        let mut area = Self::new(self.layout);
+        area.legacy_mut().fcw = Legacy::FCW_INIT;
+
+        let user = (0..63)
+            .filter_map(Component::from_index)
+            .filter(|component| matches!(self.layout.get(*component), Some(c) if !c.supervisor))
+            .fold(0, |mask, component| mask | component.mask());
+
+        self.copy_into(area, user)
+    }
+
+    /// Converts the area to the compacted format with the given `XCOMP_BV`
+    ///
+    /// Only the components in `xcomp_bv` are kept, as with `XSAVEC`.
+    /// Components marked as aligned by the layout start at a multiple of 64
+    /// bytes.
+    pub fn to_compacted(&self, xcomp_bv: u64) -> Result<Self, ParseError> {
+        let xcomp_bv = xcomp_bv & !Header::COMPACTED;
+        self.layout.visit(xcomp_bv, true, |_, _, _| ())?;
+
+        let mut area = Self::new(self.layout);
+        area.header_mut().xcomp_bv = xcomp_bv | Header::COMPACTED;
+        Ok(self.copy_into(area, xcomp_bv))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::x86_64::cpuid::fixtures::{entry, milan};
+
+    /// Captured with `XSAVE` and a requested-feature bitmap of 0x2ff
+    const STANDARD: &[u8] = include_bytes!("fixtures/xsave_standard.bin");
+
+    /// Captured with `XSAVEC` from the same state
+    const COMPACTED: &[u8] = include_bytes!("fixtures/xsave_compacted.bin");
+
+    #[test]
+    fn standard() {
+        let area = Area::from_bytes(STANDARD, Layout::INTEL).unwrap();
+        assert!(!area.is_compacted());
+        assert_eq!(area.header().xstate_bv, 0x2e6);
+        assert_eq!(area.size(), STANDARD.len());
+        assert_eq!(area.as_bytes(), STANDARD);
+
+        let legacy = area.legacy();
+        assert_eq!(legacy.fcw, Legacy::FCW_INIT);
+        assert_eq!(legacy.mxcsr, Legacy::MXCSR_INIT);
+        assert_eq!(legacy.mxcsr_mask, 0xffff);
+
+        // The x87 state is in its initial state, the rest holds a pattern
+        assert_eq!(area.x87(), None);
+        assert_eq!(area.sse().unwrap()[0][..4], [0x03, 0x0a, 0x11, 0x18]);
+        assert_eq!(area.avx().unwrap()[0][..4], [0x73, 0x7a, 0x81, 0x88]);
+        assert_eq!(area.opmask().unwrap()[7], 0xff);
+        assert_eq!(area.zmm_hi256().unwrap()[0][..4], [0xe3, 0xea, 0xf1, 0xf8]);
+        assert_eq!(area.hi16_zmm().unwrap()[0][..4], [0x43, 0x4a, 0x51, 0x58]);
+        assert_eq!(area.pkru(), Some(0x8));
+        assert_eq!(area.offset(Component::Pkru), Some(2688));
+    }
+
+    #[test]
+    fn compacted() {
+        let area = Area::from_bytes(COMPACTED, Layout::INTEL).unwrap();
+        assert!(area.is_compacted());
+        assert_eq!(area.header().xcomp_bv, Header::COMPACTED | 0x2e7);
+        assert_eq!(area.size(), COMPACTED.len());
+
+        assert_eq!(area.offset(Component::Avx), Some(576));
+        assert_eq!(area.offset(Component::Opmask), Some(832));
+        assert_eq!(area.offset(Component::Pkru), Some(2432));
+        assert_eq!(area.offset(Component::BndRegs), None);
+
+        let standard = Area::from_bytes(STANDARD, Layout::INTEL).unwrap();
+        assert_eq!(area.sse(), standard.sse());
+        assert_eq!(area.avx(), standard.avx());
+        assert_eq!(area.opmask(), standard.opmask());
+        assert_eq!(area.hi16_zmm(), standard.hi16_zmm());
+        assert_eq!(area.pkru(), standard.pkru());
+    }
+
+    #[test]
+    fn round_trip() {
+        let standard = Area::from_bytes(STANDARD, Layout::INTEL).unwrap();
+        let compacted = Area::from_bytes(COMPACTED, Layout::INTEL).unwrap();
+
+        let converted = standard.to_compacted(0x2e7).unwrap();
+        assert_eq!(converted.as_bytes(), COMPACTED);
+        assert_eq!(compacted.to_standard().as_bytes(), STANDARD);
+        assert_eq!(standard.to_standard(), standard);
+
+        // Dropping AVX-512 moves PKRU down
+        let small = standard.to_compacted(0x207).unwrap();
+        assert_eq!(small.header().xstate_bv, 0x206);
+        assert_eq!(small.size(), 576 + 256 + 8);
+        assert_eq!(small.pkru(), Some(0x8));
+        assert_eq!(small.opmask(), None);
+
+        assert_eq!(
+            standard.to_compacted(1 << 17),
+            Err(ParseError::UnknownComponent(17))
+        );
+    }
+
+    #[test]
+    fn modify() {
+        let mut area = Area::new(Layout::INTEL);
+        assert_eq!(area.size(), 576);
+        assert_eq!(area.pkru(), None);
+
+        area.component_mut(Component::Pkru).unwrap()[0] = 0x4;
+        assert_eq!(area.pkru(), Some(0x4));
+        assert_eq!(area.size(), 2696);
+
+        area.clear(Component::Pkru);
+        assert_eq!(area.pkru(), None);
+
+        let mut compacted = area.to_compacted(Component::Avx.mask()).unwrap();
+        assert!(compacted.component_mut(Component::Pkru).is_none());
+        assert!(compacted.component_mut(Component::Avx).is_some());
+        assert_eq!(compacted.as_bytes().len(), 576 + 256);
+
+        let page = *compacted.as_ref();
+        assert_eq!(Area::from_bytes(&page, Layout::INTEL), Ok(compacted));
+    }
+
+    #[test]
+    fn errors() {
+        assert_eq!(
+            Area::from_bytes(&STANDARD[..575], Layout::INTEL),
+            Err(ParseError::Truncated)
+        );
+        assert_eq!(
+            Area::from_bytes(&STANDARD[..2690], Layout::INTEL),
+            Err(ParseError::Truncated)
+        );
+        assert_eq!(
+            Area::from_bytes(&[0; Page::SIZE + 1], Layout::INTEL),
+            Err(ParseError::TooLarge)
+        );
+
+        let mut bytes = [0u8; 576];
+        bytes[520] = 1;
+        assert_eq!(
+            Area::from_bytes(&bytes, Layout::INTEL),
+            Err(ParseError::InvalidHeader)
+        );
+
+        bytes[520] = 0;
+        bytes[512] = 0x4;
+        bytes[527] = 0x80;
+        assert_eq!(
+            Area::from_bytes(&bytes, Layout::INTEL),
+            Err(ParseError::InvalidHeader)
+        );
+
+        bytes[512] = 0;
+        bytes[514] = 0x2;
+        bytes[522] = 0x2;
+        assert_eq!(
+            Area::from_bytes(&bytes, Layout::INTEL),
+            Err(ParseError::UnknownComponent(17))
+        );
+    }
+
+    #[test]
+    fn layout() {
+        let amd = Layout::read(&milan()[..]).unwrap();
+        assert_eq!(amd.get(Component::Avx), Layout::INTEL.get(Component::Avx));
+        assert_eq!(amd.get(Component::Pkru).unwrap().offset, 0x980);
+        assert_eq!(amd.get(Component::Opmask), None);
+        assert!(amd.get(Component::CetUser).unwrap().supervisor);
+        assert_eq!(Layout::read(&milan()[..1]), None);
+
+        // A standard area saved with XCR0 = 0x207
+        let mut bytes = [0u8; 0x988];
+        bytes[512..514].copy_from_slice(&0x207u16.to_le_bytes());
+        bytes[0x980] = 0x4;
+        assert_eq!(
+            Area::from_bytes(&bytes, Layout::INTEL),
+            Err(ParseError::Truncated)
+        );
+
+        let area = Area::from_bytes(&bytes, amd).unwrap();
+        assert_eq!(area.size(), 0x988);
+        assert_eq!(area.pkru(), Some(0x4));
+        assert_eq!(area.offset(Component::Pkru), Some(0x980));
+        assert_eq!(area.opmask(), None);
+
+        let compacted = area.to_compacted(0x1a07).unwrap();
+        assert_eq!(compacted.offset(Component::Pkru), Some(576 + 256));
+        assert_eq!(compacted.offset(Component::CetUser), Some(576 + 256 + 8));
+        assert_eq!(compacted.pkru(), Some(0x4));
+        assert_eq!(compacted.to_standard().as_bytes(), &bytes[..]);
+        assert_eq!(
+            area.to_compacted(Component::Opmask.mask()),
+            Err(ParseError::UnknownComponent(5))
+        );
+
+        // Aligned components start at a multiple of 64 bytes when compacted
+        let mut aligned = milan();
+        for slot in aligned
+            .iter_mut()
+            .filter(|slot| slot.leaf == 0xd && slot.subleaf == 11)
+        {
+            *slot = entry(0xd, 11, 0x10, 0, 0x3, 0);
+        }
+        let aligned = Layout::read(&aligned[..]).unwrap();
+
+        let area = Area::from_bytes(&bytes, aligned).unwrap();
+        let compacted = area.to_compacted(0xa04).unwrap();
+        assert_eq!(compacted.offset(Component::CetUser), Some(896));
+        assert_eq!(compacted.size(), 896 + 16);
+    }
+}